@@ -3,20 +3,26 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 pub mod builtins;
+pub mod call_stack;
 pub mod completion;
 pub mod database;
 pub mod history;
 pub mod jobtable;
+pub mod keymap;
 pub mod options;
+pub mod xtrace;
 
 use self::completion::{Completion, CompletionEntry};
 use self::database::DataBase;
+use self::history::HistoryState;
+use self::keymap::KeyMap;
 use self::options::Options;
 use crate::core::jobtable::JobEntry;
 use crate::elements::substitution::Substitution;
 use crate::{error, proc_ctrl, signal};
 use nix::sys::signal::Signal;
 use nix::sys::time::{TimeSpec, TimeVal};
+use nix::time::{clock_gettime, ClockId};
 use nix::unistd::Pid;
 use nix::{fcntl, unistd};
 use std::collections::HashMap;
@@ -30,6 +36,8 @@ pub struct MeasuredTime {
     pub real: TimeSpec,
     pub user: TimeVal,
     pub sys: TimeVal,
+    pub posix: bool,
+    pub start: TimeSpec,
 }
 
 impl Default for MeasuredTime {
@@ -38,6 +46,8 @@ impl Default for MeasuredTime {
             real: TimeSpec::new(0, 0),
             user: TimeVal::new(0, 0),
             sys: TimeVal::new(0, 0),
+            posix: false,
+            start: clock_gettime(ClockId::CLOCK_MONOTONIC).unwrap(),
         }
     }
 }
@@ -50,8 +60,10 @@ pub struct ShellCore {
     pub db: DataBase,
     //pub aliases: HashMap<String, String>,
     pub alias_memo: Vec<(String, String)>,
-    pub rewritten_history: HashMap<usize, String>,
     pub history: Vec<String>,
+    pub history_state: HistoryState,
+    pub kill_ring: Vec<String>,
+    pub keymap: KeyMap,
     pub builtins: HashMap<String, BuiltinFn>,
     pub substitution_builtins: HashMap<String, SubstBuiltinFn>,
     pub sigint: Arc<AtomicBool>,
@@ -60,7 +72,9 @@ pub struct ShellCore {
     pub is_subshell: bool,
     pub source_function_level: i32,
     pub source_files: Vec<String>,
+    pub call_stack: Vec<(String, bool)>, // names of the frames and whether they are functions
     pub eval_level: i32,
+    pub trap_level: i32,
     pub loop_level: i32,
     pub break_counter: i32,
     pub continue_counter: i32,
@@ -70,6 +84,7 @@ pub struct ShellCore {
     pub job_table: Vec<JobEntry>,
     pub job_table_priority: Vec<usize>,
     current_dir: Option<path::PathBuf>, // the_current_working_directory
+    pub dir_stack: Vec<String>,         // element 0 is the current directory
     pub completion: Completion,
     pub measured_time: MeasuredTime,
     pub options: Options,
@@ -77,6 +92,11 @@ pub struct ShellCore {
     pub suspend_e_option: bool,
     pub script_name: String,
     pub exit_script: String,
+    pub err_script: String,
+    pub debug_script: String,
+    pub return_script: String,
+    pub debug_trap_running: bool,
+    pub return_trap_running: bool,
     pub exit_script_run: bool,
     pub valid_assoc_expand_once: bool,
     //pub process_sub: Vec<(Pid, RawFd)>,
@@ -96,20 +116,27 @@ impl ShellCore {
 
         if unistd::isatty(0) == Ok(true) && self.script_name == "-" {
             self.db.flags += "himH";
+            let _ = self.options.set("histexpand", true);
+            let _ = self.options.set("emacs", true);
             let _ = self.db.set_param("PS1", "🍣 ", None);
             let _ = self.db.set_param("PS2", "> ", None);
             let fd = fcntl::fcntl(0, fcntl::F_DUPFD_CLOEXEC(255))
                 .expect("sush(fatal): Can't allocate fd for tty FD");
             self.tty_fd = Some(unsafe { OwnedFd::from_raw_fd(fd) });
+            self.load_inputrc();
         } else {
             self.db.flags += "h";
         }
 
         let home = self.db.get_param("HOME").unwrap_or_default().to_string();
-        let _ = self
-            .db
-            .set_param("HISTFILE", &(home + "/.sush_history"), None);
-        let _ = self.db.set_param("HISTFILESIZE", "2000", None);
+        if env::var("HISTFILE").is_err() {
+            let _ = self
+                .db
+                .set_param("HISTFILE", &(home + "/.sush_history"), None);
+        }
+        if env::var("HISTFILESIZE").is_err() {
+            let _ = self.db.set_param("HISTFILESIZE", "2000", None);
+        }
 
         if let Ok("1") = env::var("SUSH_COMPAT_TEST_MODE").as_deref() {
             if self.db.flags.contains('i') {
@@ -124,6 +151,7 @@ impl ShellCore {
             let _ = self
                 .db
                 .set_param2("BASH_SOURCE", &zero, &self.script_name, None);
+            self.call_stack.push(("main".to_string(), false));
         }
     }
 
@@ -199,12 +227,27 @@ impl ShellCore {
         Ok(())
     }
 
+    pub fn inherits_err_trap(&self) -> bool {
+        self.db.flags.contains('E') || self.shopts.query("extdebug")
+    }
+
+    pub fn inherits_debug_return_traps(&self) -> bool {
+        self.db.flags.contains('T') || self.shopts.query("extdebug")
+    }
+
     pub fn initialize_as_subshell(&mut self, pid: Pid, pgid: Pid) {
         signal::restore(Signal::SIGINT);
         signal::restore(Signal::SIGTSTP);
         signal::restore(Signal::SIGPIPE);
 
         self.is_subshell = true;
+        if !self.inherits_err_trap() {
+            self.err_script.clear();
+        }
+        if !self.inherits_debug_return_traps() {
+            self.debug_script.clear();
+            self.return_script.clear();
+        }
         proc_ctrl::set_pgid(self, pid, pgid);
         let _ = self.set_subshell_parameters();
         //self.job_table.clear();
@@ -214,7 +257,10 @@ impl ShellCore {
 
     pub fn init_current_directory(&mut self) {
         match env::current_dir() {
-            Ok(path) => self.current_dir = Some(path),
+            Ok(path) => {
+                self.set_dir_stack_top(&path);
+                self.current_dir = Some(path)
+            }
             Err(err) => {
                 let msg = format!("pwd: error retrieving current directory: {err:?}");
                 error::print(&msg, self);
@@ -231,23 +277,21 @@ impl ShellCore {
 
     pub fn set_current_directory(&mut self, path: &path::PathBuf) -> Result<(), io::Error> {
         env::set_current_dir(path)?;
+        self.set_dir_stack_top(path);
         self.current_dir = Some(path.clone());
         Ok(())
     }
 
-    pub fn get_ps4(&mut self) -> String {
-        let ps4 = self
-            .db
-            .get_param("PS4")
-            .unwrap_or_default()
-            .trim_end()
-            .to_string();
-        let mut multi_ps4 = ps4.to_string();
-        for _ in 0..(self.source_files.len() as i32 + self.eval_level) {
-            multi_ps4 += &ps4;
+    /* DIRSTACK is a copy of the stack and is not recreated after unset */
+    fn set_dir_stack_top(&mut self, path: &path::Path) {
+        let dir = path.display().to_string();
+        match self.dir_stack.is_empty() {
+            true => self.dir_stack.push(dir.clone()),
+            false => self.dir_stack[0] = dir.clone(),
+        }
+        if self.db.exist("DIRSTACK") {
+            let _ = self.db.set_array_elem("DIRSTACK", &dir, 0, None);
         }
-
-        multi_ps4
     }
 
     pub fn replace_alias(&mut self, word: &mut String) -> bool {
@@ -3,11 +3,14 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 mod alias;
+mod bind;
+mod caller;
 mod cd;
 mod command;
 pub mod compgen;
 pub mod complete;
 mod compopt;
+pub mod dir_stack;
 mod echo;
 mod exec;
 mod getopts;
@@ -15,16 +18,19 @@ mod hash;
 mod history;
 mod job_commands;
 mod loop_control;
+mod mapfile;
 pub mod option;
 pub mod parameter;
 mod printf;
 mod pwd;
 mod read;
 pub mod source;
+mod test;
 mod trap;
 mod type_;
 #[cfg(not(target_os = "macos"))]
 mod ulimit;
+mod umask;
 mod unset;
 
 use crate::elements::expr::arithmetic::ArithmeticExpr;
@@ -43,14 +49,29 @@ pub fn error_exit(exit_status: i32, name: &str, msg: &str, core: &mut ShellCore)
     exit_status
 }
 
+/* prints the error and the usage line like "cd: usage: cd [-L|[-P [-e]] [-@]] [dir]" */
+pub fn usage_error(
+    exit_status: i32,
+    name: &str,
+    msg: &str,
+    usage: &str,
+    core: &mut ShellCore,
+) -> i32 {
+    error_exit(exit_status, name, msg, core);
+    eprintln!("{}: usage: {}", name, usage);
+    exit_status
+}
+
 impl ShellCore {
     pub fn set_builtins(&mut self) {
         self.builtins.insert(":".to_string(), true_);
+        self.builtins.insert("[".to_string(), test::test);
         self.builtins.insert("alias".to_string(), alias::alias);
         self.builtins.insert("bg".to_string(), job_commands::bg);
-        self.builtins.insert("bind".to_string(), bind);
+        self.builtins.insert("bind".to_string(), bind::bind);
         self.builtins
             .insert("break".to_string(), loop_control::break_);
+        self.builtins.insert("caller".to_string(), caller::caller);
         self.builtins
             .insert("builtin".to_string(), command::builtin);
         self.builtins.insert("cd".to_string(), cd::cd);
@@ -65,6 +86,7 @@ impl ShellCore {
         self.builtins
             .insert("continue".to_string(), loop_control::continue_);
         self.builtins.insert("debug".to_string(), debug);
+        self.builtins.insert("dirs".to_string(), dir_stack::dirs);
         self.builtins
             .insert("disown".to_string(), job_commands::disown);
         self.builtins.insert("echo".to_string(), echo::echo);
@@ -72,6 +94,7 @@ impl ShellCore {
         self.builtins.insert("exec".to_string(), exec::exec);
         self.builtins.insert("exit".to_string(), exit);
         self.builtins.insert("false".to_string(), false_);
+        self.builtins.insert("fc".to_string(), history::fc);
         self.builtins.insert("fg".to_string(), job_commands::fg);
         self.builtins
             .insert("getopts".to_string(), getopts::getopts);
@@ -81,12 +104,19 @@ impl ShellCore {
         self.builtins.insert("jobs".to_string(), job_commands::jobs);
         self.builtins.insert("kill".to_string(), job_commands::kill);
         self.builtins.insert("let".to_string(), let_);
+        self.builtins
+            .insert("mapfile".to_string(), mapfile::mapfile);
+        self.builtins.insert("popd".to_string(), dir_stack::popd);
         self.builtins.insert("printf".to_string(), printf::printf);
+        self.builtins.insert("pushd".to_string(), dir_stack::pushd);
         self.builtins.insert("pwd".to_string(), pwd::pwd);
         self.builtins.insert("read".to_string(), read::read);
+        self.builtins
+            .insert("readarray".to_string(), mapfile::mapfile);
         self.builtins
             .insert("return".to_string(), loop_control::return_);
         self.builtins.insert("set".to_string(), option::set);
+        self.builtins.insert("test".to_string(), test::test);
         self.builtins.insert("trap".to_string(), trap::trap);
         self.builtins.insert("type".to_string(), type_::type_);
         self.builtins.insert("shift".to_string(), option::shift);
@@ -100,6 +130,7 @@ impl ShellCore {
                     self.builtins.insert("ulimit".to_string(), ulimit_mac::ulimit);
                 }*/
 
+        self.builtins.insert("umask".to_string(), umask::umask);
         self.builtins.insert("unalias".to_string(), alias::unalias);
         self.builtins.insert("unset".to_string(), unset::unset);
         self.builtins.insert("source".to_string(), source::source);
@@ -179,10 +210,6 @@ pub fn true_(_: &mut ShellCore, _: &[String]) -> i32 {
     0
 }
 
-pub fn bind(_: &mut ShellCore, _: &[String]) -> i32 {
-    0
-}
-
 pub fn debug(core: &mut ShellCore, args: &[String]) -> i32 {
     dbg!("{:?}", &args);
     dbg!("{:?}", &core.db.get_param("depth"));
@@ -0,0 +1,257 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::core::keymap;
+use crate::core::keymap::{Binding, FUNCTIONS};
+use crate::ShellCore;
+
+#[derive(Debug, Default)]
+struct BindOptions {
+    flags: String,
+    keymap: Option<String>,
+    files: Vec<String>,
+    queries: Vec<String>,
+    unbinds: Vec<String>,
+    removes: Vec<String>,
+    commands: Vec<String>,
+    operands: Vec<String>,
+}
+
+const USAGE: &str = "bind [-lpsvPSVX] [-m keymap] [-f filename] [-q name] [-u name] [-r keyseq] [-x keyseq:shell-command] [keyseq:readline-function or readline-command]";
+
+fn bind_parse_options(core: &mut ShellCore, args: &[String]) -> Result<BindOptions, i32> {
+    let mut ans = BindOptions::default();
+    let mut i = 1;
+    while i < args.len() {
+        let a = &args[i];
+        if a == "--" {
+            i += 1;
+            break;
+        }
+        if !a.starts_with('-') || a.len() == 1 {
+            break;
+        }
+
+        for (n, c) in a[1..].chars().enumerate() {
+            match c {
+                'l' | 'p' | 'P' | 's' | 'S' | 'v' | 'V' | 'X' => ans.flags.push(c),
+                'm' | 'f' | 'q' | 'u' | 'r' | 'x' => {
+                    let rest = &a[n + 2..];
+                    let value = if !rest.is_empty() {
+                        rest.to_string()
+                    } else if i + 1 < args.len() {
+                        i += 1;
+                        args[i].clone()
+                    } else {
+                        let msg = format!("-{}: option requires an argument", c);
+                        return Err(super::usage_error(2, "bind", &msg, USAGE, core));
+                    };
+                    match c {
+                        'm' => ans.keymap = Some(value),
+                        'f' => ans.files.push(value),
+                        'q' => ans.queries.push(value),
+                        'u' => ans.unbinds.push(value),
+                        'r' => ans.removes.push(value),
+                        _ => ans.commands.push(value),
+                    }
+                    break;
+                }
+                _ => {
+                    let msg = format!("-{}: invalid option", c);
+                    return Err(super::usage_error(2, "bind", &msg, USAGE, core));
+                }
+            }
+        }
+        i += 1;
+    }
+
+    ans.operands = args[i..].to_vec();
+    Ok(ans)
+}
+
+fn print_functions(core: &ShellCore, readable: bool) {
+    let (map, _) = core.keymap.target();
+    println!();
+    for f in FUNCTIONS {
+        let keys = core.keymap.keys_of(map, f);
+        match (readable, keys.is_empty()) {
+            (true, true) => println!("# {} (not bound)", f),
+            (true, false) => keys.iter().for_each(|k| println!("{}: {}", k, f)),
+            (false, true) => println!("{} is not bound to any keys", f),
+            (false, false) => println!("{} can be found on {}.", f, keys.join(", ")),
+        }
+    }
+}
+
+fn print_macros(core: &ShellCore, readable: bool) {
+    let (map, _) = core.keymap.target();
+    for (keys, b) in core.keymap.bindings(map) {
+        if let Binding::Macro(m) = b {
+            let keys = keymap::untranslate(keys, true);
+            let m = keymap::untranslate(m, false);
+            match readable {
+                true => println!("\"{}\": \"{}\"", keys, m),
+                false => println!("{} outputs {}", keys, m),
+            }
+        }
+    }
+}
+
+fn print_commands(core: &ShellCore) {
+    let (map, _) = core.keymap.target();
+    for (keys, b) in core.keymap.bindings(map) {
+        if let Binding::Command(c) = b {
+            let keys = keymap::untranslate(keys, true);
+            let c = c.replace('\\', "\\\\").replace('"', "\\\"");
+            println!("\"{}\": \"{}\"", keys, c);
+        }
+    }
+}
+
+fn print_variables(core: &mut ShellCore, readable: bool) {
+    let mode = core.editing_mode().to_string();
+    core.keymap
+        .variables
+        .insert("editing-mode".to_string(), mode);
+
+    let vars = [core.keymap.bool_variables(), core.keymap.string_variables()].concat();
+    for (name, value) in vars {
+        match readable {
+            true => println!("set {} {}", name, value),
+            false => println!("{} is set to `{}'", name, value),
+        }
+    }
+}
+
+fn query(core: &mut ShellCore, name: &str) -> i32 {
+    if !FUNCTIONS.contains(&name) {
+        let msg = format!("`{}': unknown function name", name);
+        return super::error_exit(1, "bind", &msg, core);
+    }
+
+    let (map, _) = core.keymap.target();
+    let keys = core.keymap.keys_of(map, name);
+    match keys.is_empty() {
+        true => println!("{} is not bound to any keys.", name),
+        false => println!("{} can be invoked via {}.", name, keys.join(", ")),
+    }
+    0
+}
+
+/* -x "keyseq": shell-command */
+fn bind_command(core: &mut ShellCore, arg: &str) -> i32 {
+    let arg = arg.trim_start();
+    if !arg.starts_with('"') {
+        let msg = format!("{}: first non-whitespace character is not `\"'", arg);
+        return super::error_exit(1, "bind", &msg, core);
+    }
+
+    let (keys, command) = match keymap::split_binding(arg) {
+        Ok(b) => b,
+        Err(e) if e.contains("no closing") => {
+            let msg = format!("no closing `\"' in {}", arg);
+            return super::error_exit(1, "bind", &msg, core);
+        }
+        Err(_) => {
+            let msg = format!("{}: missing colon separator", arg);
+            return super::error_exit(1, "bind", &msg, core);
+        }
+    };
+
+    let command = match command.chars().next() {
+        Some(q) if (q == '"' || q == '\'') && command.len() > 1 && command.ends_with(q) => {
+            command[1..command.len() - 1].to_string()
+        }
+        _ => command,
+    };
+    let (map, prefix) = core.keymap.target();
+    let keys = [prefix, keys].concat();
+    core.keymap
+        .bindings_mut(map)
+        .insert(keys, Binding::Command(command));
+    0
+}
+
+pub fn bind(core: &mut ShellCore, args: &[String]) -> i32 {
+    if !core.options.query("emacs") && !core.options.query("vi") {
+        super::error_exit(0, "bind", "warning: line editing not enabled", core);
+    }
+
+    let opts = match bind_parse_options(core, args) {
+        Ok(o) => o,
+        Err(status) => return status,
+    };
+
+    let mut status = 0;
+    if let Some(m) = &opts.keymap {
+        if !keymap::is_keymap_name(m) {
+            let msg = format!("`{}': invalid keymap name", m);
+            return super::error_exit(1, "bind", &msg, core);
+        }
+    }
+    core.set_keymap(opts.keymap.as_deref());
+
+    if opts.flags.contains('l') {
+        FUNCTIONS.iter().for_each(|f| println!("{}", f));
+    }
+    if opts.flags.contains('p') {
+        print_functions(core, true);
+    }
+    if opts.flags.contains('P') {
+        print_functions(core, false);
+    }
+    if opts.flags.contains('v') {
+        print_variables(core, true);
+    }
+    if opts.flags.contains('V') {
+        print_variables(core, false);
+    }
+    if opts.flags.contains('s') {
+        print_macros(core, true);
+    }
+    if opts.flags.contains('S') {
+        print_macros(core, false);
+    }
+    if opts.flags.contains('X') {
+        print_commands(core);
+    }
+
+    for file in &opts.files {
+        if let Err(e) = core.read_inputrc(file) {
+            status = super::error_exit(1, "bind", &e, core);
+        }
+    }
+    for name in &opts.queries {
+        status = query(core, name);
+    }
+    for name in &opts.unbinds {
+        match FUNCTIONS.contains(&name.as_str()) {
+            true => {
+                let (map, _) = core.keymap.target();
+                core.keymap.unbind_function(map, name);
+            }
+            false => {
+                let msg = format!("`{}': unknown function name", name);
+                status = super::error_exit(1, "bind", &msg, core);
+            }
+        }
+    }
+    for keyseq in &opts.removes {
+        let (map, prefix) = core.keymap.target();
+        let keys = [prefix, keymap::translate(keyseq)].concat();
+        core.keymap.bindings_mut(map).remove(&keys);
+    }
+    for command in &opts.commands {
+        if bind_command(core, command) != 0 {
+            status = 1;
+        }
+    }
+
+    for line in &opts.operands {
+        if let Err(e) = core.parse_and_bind(line) {
+            eprintln!("readline: {}", e);
+        }
+    }
+
+    status
+}
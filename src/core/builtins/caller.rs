@@ -0,0 +1,57 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::ShellCore;
+
+pub fn caller(core: &mut ShellCore, args: &[String]) -> i32 {
+    let linenos = core.db.get_vec("BASH_LINENO", false).unwrap_or_default();
+    let sources = core.db.get_vec("BASH_SOURCE", false).unwrap_or_default();
+    if linenos.is_empty() || sources.is_empty() {
+        return 1;
+    }
+
+    let mut args = args.to_vec();
+    if args.get(1).is_some_and(|a| a == "--") {
+        args.remove(1);
+    } else if args
+        .get(1)
+        .is_some_and(|a| a.starts_with('-') && a.len() > 1)
+    {
+        let msg = format!("{}: invalid option", &args[1]);
+        return super::usage_error(2, "caller", &msg, "caller [expr]", core);
+    }
+
+    if args.len() < 2 {
+        let source = sources.get(1).map(|s| s.as_str()).unwrap_or("NULL");
+        println!("{} {}", &linenos[0], source);
+        return 0;
+    }
+
+    if core.call_stack.is_empty() {
+        return 1;
+    }
+
+    let n = match args[1].trim().parse::<isize>() {
+        Ok(n) => n,
+        Err(_) => {
+            let msg = format!("{}: invalid number", &args[1]);
+            return super::usage_error(2, "caller", &msg, "caller [expr]", core);
+        }
+    };
+    if n < 0 {
+        return 1;
+    }
+
+    let n = n as usize;
+    match (
+        linenos.get(n),
+        core.call_stack.get(n + 1),
+        sources.get(n + 1),
+    ) {
+        (Some(lineno), Some(frame), Some(source)) => {
+            println!("{} {} {}", lineno, frame.0, source);
+            0
+        }
+        _ => 1,
+    }
+}
@@ -2,54 +2,140 @@
 //SPDX-FileCopyrightText: 2023 @caro@mi.shellgei.org
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::utils::{arg, file};
-use crate::{error, ShellCore};
+use super::{error_exit, usage_error};
+use crate::utils::{arg, directory};
+use crate::ShellCore;
+use nix::errno::Errno;
+use nix::unistd;
+use std::path::PathBuf;
+
+#[derive(Debug, Default)]
+struct CdOptions {
+    physical: bool,
+    check_pwd: bool,
+}
 
 pub fn cd(core: &mut ShellCore, args: &[String]) -> i32 {
     if core.db.flags.contains('r') {
         return super::error_exit(1, &args[0], "restricted", core);
     }
 
-    let mut args = args.to_owned();
-    arg::consume_arg("--", &mut args);
-
-    if args.len() > 2 {
-        eprintln!("sush: cd: too many arguments");
-        return 1;
+    let args = arg::dissolve_options_with_values(args, "");
+    let mut options = CdOptions::default();
+    let mut pos = 1;
+    while pos < args.len() && args[pos].starts_with('-') && args[pos] != "-" {
+        pos += 1;
+        match args[pos - 1].as_str() {
+            "--" => break,
+            "-L" => options.physical = false,
+            "-P" => options.physical = true,
+            "-e" => options.check_pwd = true,
+            "-@" => {}
+            a => {
+                let msg = format!("{}: invalid option", a);
+                return usage_error(2, "cd", &msg, "cd [-L|[-P [-e]] [-@]] [dir]", core);
+            }
+        }
     }
 
-    // only "cd"
-    if args.len() == 1 {
-        set_oldpwd(core);
-        let home = core.db.get_param("HOME").unwrap_or_default();
-        return change_directory(core, &home);
+    if args.len() > pos + 1 {
+        return error_exit(1, "cd", "too many arguments", core);
     }
 
-    // cd -
-    if args[1] == "-" {
-        return cd_oldpwd(core);
+    let dir = match args.get(pos).map(|s| s.as_str()) {
+        None if !core.db.exist("HOME") => return error_exit(1, "cd", "HOME not set", core),
+        None => core.db.get_param("HOME").unwrap_or_default(),
+        Some("-") => return cd_oldpwd(core, &options),
+        Some(d) => d.to_string(),
+    };
+
+    if dir.is_empty() {
+        return 0;
+    }
+    if let Some(status) = cd_cdpath(core, &dir, &options) {
+        return status;
     }
 
-    // cd /some/dir
-    set_oldpwd(core);
-    change_directory(core, &args[1])
+    let err = match change_directory(core, &dir, &options) {
+        Ok(()) => return 0,
+        Err(e) => e,
+    };
+    if let Some(status) = cd_alternative(core, &dir, &options) {
+        return status;
+    }
+    error_exit(1, "cd", &format!("{}: {}", &dir, err), core)
 }
 
-fn cd_oldpwd(core: &mut ShellCore) -> i32 {
-    match core.db.get_param("OLDPWD") {
-        Ok(old) => {
+fn cd_oldpwd(core: &mut ShellCore, options: &CdOptions) -> i32 {
+    let old = core.db.get_param("OLDPWD").unwrap_or_default();
+    if old.is_empty() {
+        return error_exit(1, "cd", "OLDPWD not set", core);
+    }
+
+    match change_directory(core, &old, options) {
+        Ok(()) => {
             println!("{}", &old);
-            set_oldpwd(core);
-            change_directory(core, &old)
+            0
         }
-        Err(_) => {
-            error::print("cd: OLDPWD not set", core);
-            1
+        Err(e) => error_exit(1, "cd", &format!("{}: {}", &old, e), core),
+    }
+}
+
+/* CDPATH is not used for /dir, ./dir, ../dir, . and .. */
+fn cd_cdpath(core: &mut ShellCore, dir: &str, options: &CdOptions) -> Option<i32> {
+    if dir.starts_with('/') || is_dot_path(dir) {
+        return None;
+    }
+    let cdpath = core.db.get_param("CDPATH").unwrap_or_default();
+    if cdpath.is_empty() {
+        return None;
+    }
+
+    for entry in cdpath.split(':') {
+        let candidate = match entry {
+            "" => format!("./{}", dir),
+            e => format!("{}/{}", e.trim_end_matches('/'), dir),
+        };
+        if change_directory(core, &candidate, options).is_ok() {
+            if !entry.is_empty() {
+                print_pwd(core);
+            }
+            return Some(0);
         }
     }
+    None
 }
 
-fn set_oldpwd(core: &mut ShellCore) {
+fn is_dot_path(dir: &str) -> bool {
+    dir == "." || dir == ".." || dir.starts_with("./") || dir.starts_with("../")
+}
+
+/* cdable_vars and cdspell */
+fn cd_alternative(core: &mut ShellCore, dir: &str, options: &CdOptions) -> Option<i32> {
+    if core.shopts.query("cdable_vars") {
+        let value = core.db.get_param(dir).unwrap_or_default();
+        if !value.is_empty() && change_directory(core, &value, options).is_ok() {
+            print_pwd(core);
+            return Some(0);
+        }
+    }
+
+    if core.shopts.query("cdspell") && core.db.flags.contains('i') {
+        if let Some(corrected) = directory::spell_correct(dir) {
+            if change_directory(core, &corrected, options).is_ok() {
+                println!("{}", &corrected);
+                return Some(0);
+            }
+        }
+    }
+    None
+}
+
+fn print_pwd(core: &mut ShellCore) {
+    println!("{}", core.db.get_param("PWD").unwrap_or_default());
+}
+
+pub(super) fn set_oldpwd(core: &mut ShellCore) {
     if let Some(old) = core.get_current_directory() {
         let _ = core
             .db
@@ -57,15 +143,70 @@ fn set_oldpwd(core: &mut ShellCore) {
     };
 }
 
-fn change_directory(core: &mut ShellCore, target: &str) -> i32 {
-    let path = file::make_canonical_path(core, target);
-    if core.set_current_directory(&path).is_ok() {
+/* the path with . and .. removed without resolving symbolic links.
+ * None if the directory before .. does not exist. */
+fn logical_path(core: &mut ShellCore, dir: &str) -> Option<PathBuf> {
+    let mut path = match dir.starts_with('/') {
+        true => PathBuf::from("/"),
+        false => core.get_current_directory()?,
+    };
+
+    for component in dir.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                if !path.is_dir() {
+                    return None;
+                }
+                path.pop();
+            }
+            c => path.push(c),
+        }
+    }
+    Some(path)
+}
+
+fn set_directory(core: &mut ShellCore, path: &PathBuf) -> Result<(), Errno> {
+    let old = core.get_current_directory();
+    core.set_current_directory(path)
+        .map_err(|e| Errno::from_raw(e.raw_os_error().unwrap_or(0)))?;
+
+    if let Some(old) = old {
         let _ = core
             .db
-            .set_param("PWD", &path.display().to_string(), Some(0));
-        0
-    } else {
-        eprintln!("sush: cd: {:?}: No such file or directory", &path);
-        1
+            .set_param("OLDPWD", &old.display().to_string(), Some(0));
+    }
+    let _ = core
+        .db
+        .set_param("PWD", &path.display().to_string(), Some(0));
+    Ok(())
+}
+
+/* -L: the logical path is tried first. -P or its failure: the symbolic links are resolved. */
+fn change_directory(core: &mut ShellCore, dir: &str, options: &CdOptions) -> Result<(), String> {
+    let logical = logical_path(core, dir);
+    if !options.physical {
+        if let Some(path) = &logical {
+            if set_directory(core, path).is_ok() {
+                return Ok(());
+            }
+        }
     }
+
+    unistd::chdir(dir).map_err(|e| e.desc().to_string())?;
+    let path = match unistd::getcwd() {
+        Ok(cwd) => cwd,
+        Err(e) => {
+            let msg = format!(
+                "error retrieving current directory: getcwd: cannot access parent directories: {}",
+                e.desc()
+            );
+            if options.check_pwd {
+                return Err(msg);
+            }
+            error_exit(1, "cd", &msg, core);
+            logical.unwrap_or(PathBuf::from(dir))
+        }
+    };
+    set_directory(core, &path).map_err(|e| e.desc().to_string())
 }
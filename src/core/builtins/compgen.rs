@@ -34,7 +34,7 @@ pub fn compgen_f(core: &mut ShellCore, args: &[String], dir_only: bool) -> Vec<S
     };
 
     split.push("".to_string());
-    let org_dir = split.join("/");
+    let mut org_dir = split.join("/");
     let mut dir = org_dir.clone();
     if dir.starts_with("~") {
         let mut feeder = Feeder::new(&dir);
@@ -44,6 +44,13 @@ pub fn compgen_f(core: &mut ShellCore, args: &[String], dir_only: bool) -> Vec<S
         }
     }
 
+    if core.shopts.query("dirspell") && !dir.is_empty() && !file_check::is_dir(&dir) {
+        if let Some(d) = directory::spell_correct(dir.trim_end_matches('/')) {
+            dir = d + "/";
+            org_dir = dir.clone();
+        }
+    }
+
     if key.is_empty() {
         let mut files = directory::files(&dir);
         if dir_only {
@@ -0,0 +1,298 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use super::{cd, error_exit, usage_error};
+use crate::utils::file;
+use crate::ShellCore;
+
+fn current_dir(core: &mut ShellCore) -> String {
+    match core.get_current_directory() {
+        Some(p) => p.display().to_string(),
+        None => core.db.get_param("PWD").unwrap_or_default(),
+    }
+}
+
+/* the stack is kept in ShellCore. Element 0 always mirrors the current directory. */
+fn get_stack(core: &mut ShellCore) -> Vec<String> {
+    let mut stack = core.dir_stack.clone();
+    let cur = current_dir(core);
+
+    match stack.is_empty() {
+        true => stack.push(cur),
+        false => stack[0] = cur,
+    }
+    stack
+}
+
+fn set_stack(core: &mut ShellCore, stack: Vec<String>) {
+    if core.db.exist("DIRSTACK") {
+        let _ = core.db.set_array("DIRSTACK", Some(stack.clone()), None);
+    }
+    core.dir_stack = stack;
+}
+
+fn to_index(arg: &str, len: usize) -> Option<usize> {
+    let n = arg[1..].parse::<usize>().ok()?;
+    match arg.starts_with('-') {
+        true => (n < len).then(|| len - 1 - n),
+        false => (n < len).then_some(n),
+    }
+}
+
+fn is_number_arg(arg: &str) -> bool {
+    arg.len() > 1 && arg[1..].parse::<usize>().is_ok()
+}
+
+/* ~+N, ~-N and ~N in tilde expansion */
+pub fn get_entry(core: &mut ShellCore, spec: &str) -> Option<String> {
+    let spec = match spec.starts_with(['+', '-']) {
+        true => spec.to_string(),
+        false => "+".to_owned() + spec,
+    };
+    if !is_number_arg(&spec) {
+        return None;
+    }
+
+    let stack = get_stack(core);
+    let pos = to_index(&spec, stack.len())?;
+    Some(stack[pos].clone())
+}
+
+fn polite_format(dir: &str, home: &str) -> String {
+    if home.is_empty() || home == "/" {
+        return dir.to_string();
+    }
+
+    match dir.strip_prefix(home) {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => "~".to_owned() + rest,
+        _ => dir.to_string(),
+    }
+}
+
+fn print_stack(core: &mut ShellCore, stack: &[String]) {
+    let home = core.db.get_param("HOME").unwrap_or_default();
+    let dirs: Vec<String> = stack.iter().map(|d| polite_format(d, &home)).collect();
+    println!("{}", dirs.join(" "));
+}
+
+fn change_directory(core: &mut ShellCore, com: &str, target: &str) -> bool {
+    let path = file::make_canonical_path(core, target);
+    if !path.is_dir() {
+        let msg = format!("{}: No such file or directory", target);
+        error_exit(1, com, &msg, core);
+        return false;
+    }
+
+    cd::set_oldpwd(core);
+    if core.set_current_directory(&path).is_err() {
+        let msg = format!("{}: Permission denied", target);
+        error_exit(1, com, &msg, core);
+        return false;
+    }
+    let _ = core
+        .db
+        .set_param("PWD", &path.display().to_string(), Some(0));
+    true
+}
+
+pub fn dirs(core: &mut ShellCore, args: &[String]) -> i32 {
+    let usage = "dirs [-clpv] [+N] [-N]";
+    let (mut long, mut vertical, mut index, mut clear) = (false, false, false, false);
+    let mut target = None;
+
+    for a in &args[1..] {
+        match a.as_str() {
+            "-l" => long = true,
+            "-p" => vertical = true,
+            "-v" => {
+                vertical = true;
+                index = true;
+            }
+            "-c" => clear = true,
+            "--" => break,
+            _ if is_number_arg(a) => target = Some(a.clone()),
+            _ if a.starts_with(['+', '-']) => {
+                return usage_error(2, "dirs", &format!("{}: invalid number", a), usage, core)
+            }
+            _ => return usage_error(2, "dirs", &format!("{}: invalid option", a), usage, core),
+        }
+    }
+
+    let mut stack = get_stack(core);
+    if clear {
+        stack.truncate(1);
+        set_stack(core, stack);
+        return 0;
+    }
+
+    let home = match long {
+        true => String::new(),
+        false => core.db.get_param("HOME").unwrap_or_default(),
+    };
+
+    if let Some(t) = target {
+        let pos = match to_index(&t, stack.len()) {
+            Some(p) => p,
+            None => {
+                let msg = format!("{}: directory stack index out of range", &t[1..]);
+                return error_exit(1, "dirs", &msg, core);
+            }
+        };
+
+        let dir = polite_format(&stack[pos], &home);
+        match index {
+            true => println!("{:2}  {}", pos, dir),
+            false => println!("{}", dir),
+        }
+        return 0;
+    }
+
+    let dirs: Vec<String> = stack.iter().map(|d| polite_format(d, &home)).collect();
+    if !vertical {
+        println!("{}", dirs.join(" "));
+        return 0;
+    }
+
+    for (i, d) in dirs.iter().enumerate() {
+        match index {
+            true => println!("{:2}  {}", i, d),
+            false => println!("{}", d),
+        }
+    }
+    0
+}
+
+pub fn pushd(core: &mut ShellCore, args: &[String]) -> i32 {
+    let usage = "pushd [-n] [+N | -N | dir]";
+    let mut no_cd = false;
+    let mut rest = vec![];
+
+    let mut end_of_options = false;
+    for a in &args[1..] {
+        match a.as_str() {
+            "-n" if !end_of_options => no_cd = true,
+            "--" if !end_of_options => end_of_options = true,
+            _ if !end_of_options && a.starts_with('-') && a != "-" && !is_number_arg(a) => {
+                return usage_error(2, "pushd", &format!("{}: invalid number", a), usage, core)
+            }
+            _ => rest.push(a.clone()),
+        }
+    }
+
+    if rest.len() > 1 {
+        return error_exit(1, "pushd", "too many arguments", core);
+    }
+
+    let mut stack = get_stack(core);
+
+    if rest.is_empty() {
+        if no_cd {
+            return 0;
+        }
+        if stack.len() < 2 {
+            return error_exit(1, "pushd", "no other directory", core);
+        }
+
+        let next = stack[1].clone();
+        if !change_directory(core, "pushd", &next) {
+            return 1;
+        }
+        stack.swap(0, 1);
+        stack[0] = current_dir(core);
+        set_stack(core, stack.clone());
+        print_stack(core, &stack);
+        return 0;
+    }
+
+    let arg = &rest[0];
+    if arg.starts_with(['+', '-']) && is_number_arg(arg) {
+        let pos = match to_index(arg, stack.len()) {
+            Some(p) => p,
+            None if stack.len() < 2 => {
+                return error_exit(1, "pushd", "directory stack empty", core);
+            }
+            None => {
+                let msg = format!("{}: directory stack index out of range", arg);
+                return error_exit(1, "pushd", &msg, core);
+            }
+        };
+
+        stack.rotate_left(pos);
+        if no_cd {
+            set_stack(core, stack);
+            return 0;
+        }
+
+        let top = stack[0].clone();
+        if !change_directory(core, "pushd", &top) {
+            return 1;
+        }
+        set_stack(core, stack.clone());
+        print_stack(core, &stack);
+        return 0;
+    }
+
+    let dir = match arg.as_str() {
+        "-" => core.db.get_param("OLDPWD").unwrap_or_default(),
+        _ => arg.clone(),
+    };
+
+    if no_cd {
+        stack.insert(1, dir);
+    } else {
+        if !change_directory(core, "pushd", &dir) {
+            return 1;
+        }
+        stack.insert(0, current_dir(core));
+    }
+    set_stack(core, stack.clone());
+    print_stack(core, &stack);
+    0
+}
+
+pub fn popd(core: &mut ShellCore, args: &[String]) -> i32 {
+    let usage = "popd [-n] [+N | -N]";
+    let mut no_cd = false;
+    let mut target = None;
+
+    for a in &args[1..] {
+        match a.as_str() {
+            "-n" => no_cd = true,
+            "--" => break,
+            _ if a.starts_with(['+', '-']) && is_number_arg(a) => target = Some(a.clone()),
+            _ => return usage_error(2, "popd", &format!("{}: invalid argument", a), usage, core),
+        }
+    }
+
+    let mut stack = get_stack(core);
+    if stack.len() < 2 {
+        return error_exit(1, "popd", "directory stack empty", core);
+    }
+
+    let pos = match &target {
+        None if no_cd => 1,
+        None => 0,
+        Some(t) => match to_index(t, stack.len()) {
+            Some(p) => p,
+            None => {
+                let msg = format!("{}: directory stack index out of range", t);
+                return error_exit(1, "popd", &msg, core);
+            }
+        },
+    };
+
+    if pos == 0 && !no_cd {
+        let next = stack[1].clone();
+        if !change_directory(core, "popd", &next) {
+            return 1;
+        }
+        stack.remove(0);
+        stack[0] = current_dir(core);
+    } else {
+        stack.remove(pos);
+    }
+
+    set_stack(core, stack.clone());
+    print_stack(core, &stack);
+    0
+}
@@ -1,51 +1,418 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::utils::arg;
+use crate::utils::{arg, clock};
 use crate::ShellCore;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 
-pub fn history_c(core: &mut ShellCore) -> i32 {
-    core.rewritten_history.clear();
-    core.history.clear();
-    0
+#[derive(Debug, Default)]
+struct HistoryOptions {
+    clear: bool,
+    delete: Option<String>,
+    file_op: Option<char>, // a, n, r or w
+    store: bool,
+    print: bool,
+    operands: Vec<String>,
 }
 
-pub fn history(core: &mut ShellCore, args: &[String]) -> i32 {
-    let args = args.to_owned();
-    let mut args = arg::dissolve_options(&args);
-    if arg::consume_arg("-c", &mut args) {
-        return history_c(core);
+const USAGE: &str =
+    "history [-c] [-d offset] [n] or history -anrw [filename] or history -ps arg [arg...]";
+
+fn parse_options(core: &mut ShellCore, args: &[String]) -> Result<HistoryOptions, i32> {
+    let mut args = arg::dissolve_options_with_values(args, "d");
+    args.remove(0);
+    let mut opts = HistoryOptions::default();
+
+    while !args.is_empty() && args[0].starts_with('-') && args[0] != "-" {
+        let opt = args.remove(0);
+        match opt.as_str() {
+            "--" => break,
+            "-c" => opts.clear = true,
+            "-s" => opts.store = true,
+            "-p" => opts.print = true,
+            "-d" if args.is_empty() => {
+                let msg = "-d: option requires an argument";
+                return Err(super::usage_error(2, "history", msg, USAGE, core));
+            }
+            "-d" => opts.delete = Some(args.remove(0)),
+            "-a" | "-n" | "-r" | "-w" => {
+                let c = opt.chars().nth(1).unwrap();
+                if opts.file_op.is_some_and(|o| o != c) {
+                    let msg = "cannot use more than one of -anrw";
+                    return Err(super::error_exit(1, "history", msg, core));
+                }
+                opts.file_op = Some(c);
+            }
+            _ => {
+                let msg = format!("{}: invalid option", &opt);
+                return Err(super::usage_error(2, "history", &msg, USAGE, core));
+            }
+        }
+    }
+
+    opts.operands = args;
+    Ok(opts)
+}
+
+fn out_of_range(core: &mut ShellCore, arg: &str) -> i32 {
+    let msg = format!("{}: history position out of range", arg);
+    super::error_exit(1, "history", &msg, core)
+}
+
+/* the position from the oldest entry. A negative offset counts back from the end. */
+fn history_pos(core: &ShellCore, arg: &str) -> Option<usize> {
+    let n = arg.parse::<i64>().ok()?;
+    let len = core.history.len() as i64;
+    let pos = match n < 0 {
+        true => len + n,
+        false => n - core.history_state.base as i64 - 1,
+    };
+    match pos >= 0 && pos < len {
+        true => Some(pos as usize),
+        false => None,
+    }
+}
+
+fn delete(core: &mut ShellCore, arg: &str) -> i32 {
+    let range = match arg.get(1..).and_then(|a| a.find('-')) {
+        Some(i) => (&arg[..i + 1], &arg[i + 2..]),
+        None => (arg, arg),
+    };
+
+    match (history_pos(core, range.0), history_pos(core, range.1)) {
+        (Some(start), Some(end)) if start <= end => {
+            if end + 1 == core.history.len() {
+                core.history_state.current_added = false; // history -d -1
+            }
+            for pos in (start..=end).rev() {
+                core.remove_history(pos);
+            }
+            0
+        }
+        _ => out_of_range(core, arg),
     }
+}
 
-    if args.len() > 1 {
-        let msg = format!("{}: invalid option", &args[1]);
-        return super::error_exit(1, "history", &msg, core);
+fn print_list(core: &mut ShellCore, operands: &[String]) -> i32 {
+    if operands.len() > 1 {
+        return super::error_exit(1, "history", "too many arguments", core);
     }
+    let len = core.history.len();
+    let num = match operands.first() {
+        None => len,
+        Some(a) => match a.parse::<i64>() {
+            Ok(n) => std::cmp::min(n.unsigned_abs() as usize, len),
+            Err(_) => {
+                let msg = format!("{}: numeric argument required", a);
+                return super::error_exit(1, "history", &msg, core);
+            }
+        },
+    };
 
-    let mut number = 1;
+    let format = core.db.get_param("HISTTIMEFORMAT").unwrap_or_default();
+    for pos in len - num..len {
+        let time = match (format.is_empty(), core.history_time(pos)) {
+            (true, _) => String::new(),
+            (false, 0) => "??".to_string(),
+            (false, t) => clock::strftime(&format, t),
+        };
+        let entry = core.history[len - 1 - pos].replace("↵ \0", "\n");
+        println!("{:5}  {}{}", core.history_number(pos), time, entry);
+    }
+    0
+}
 
-    let filename = core.db.get_param("HISTFILE").unwrap_or_default();
+/* history -p: the expansion without recording */
+fn print_expansion(core: &mut ShellCore, args: &[String]) -> i32 {
+    core.remove_current_history();
+    for a in args {
+        match core.expand_history_with(a, false) {
+            Ok(Some((ans, _))) => println!("{}", ans),
+            Ok(None) => println!("{}", a),
+            Err(_) => {
+                let msg = format!("{}: history expansion failed", a);
+                return super::error_exit(1, "history", &msg, core);
+            }
+        }
+    }
+    0
+}
+
+fn file_operation(core: &mut ShellCore, op: char, operands: &[String]) -> i32 {
+    let filename = match operands.first() {
+        Some(f) => f.clone(),
+        None => core.db.get_param("HISTFILE").unwrap_or_default(),
+    };
     if filename.is_empty() {
+        return 1;
+    }
+
+    let result = match op {
+        'a' => core.write_history(&filename, true),
+        'w' => core.write_history(&filename, false),
+        'r' => core.read_history(&filename, 0),
+        _ => {
+            let skip = core.history_state.file_lines;
+            core.read_history(&filename, skip)
+        }
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) if op == 'a' || op == 'w' => super::error_exit(1, "history", &e, core),
+        Err(_) => 1,
+    }
+}
+
+pub fn history(core: &mut ShellCore, args: &[String]) -> i32 {
+    let opts = match parse_options(core, args) {
+        Ok(o) => o,
+        Err(status) => return status,
+    };
+
+    if opts.clear {
+        core.clear_history();
+        if opts.operands.is_empty() {
+            return 0;
+        }
+    }
+
+    if opts.store {
+        if !opts.operands.is_empty() {
+            core.remove_current_history();
+            core.history.insert(0, opts.operands.join(" "));
+            core.record_history();
+        }
         return 0;
     }
+    if opts.print {
+        return print_expansion(core, &opts.operands);
+    }
+    if let Some(arg) = &opts.delete {
+        return delete(core, arg);
+    }
+
+    match opts.file_op {
+        Some(op) => file_operation(core, op, &opts.operands),
+        None if opts.clear => 0,
+        None => {
+            core.stifle_history(0);
+            print_list(core, &opts.operands)
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct FcOptions {
+    list: bool,
+    no_number: bool,
+    reverse: bool,
+    reexec: bool,
+    editor: Option<String>,
+    operands: Vec<String>,
+}
+
+const FC_USAGE: &str = "fc [-e ename] [-lnr] [first] [last] or fc -s [pat=rep] [command]";
+
+fn fc_parse_options(core: &mut ShellCore, args: &[String]) -> Result<FcOptions, i32> {
+    let mut ans = FcOptions::default();
+    let mut i = 1;
+    while i < args.len() {
+        let a = &args[i];
+        if a == "--" {
+            i += 1;
+            break;
+        }
+        if !a.starts_with('-') || a.len() == 1 || a[1..].chars().all(|c| c.is_ascii_digit()) {
+            break;
+        }
+
+        for (n, c) in a[1..].chars().enumerate() {
+            match c {
+                'l' => ans.list = true,
+                'n' => ans.no_number = true,
+                'r' => ans.reverse = true,
+                's' => ans.reexec = true,
+                'e' => {
+                    let rest = &a[n + 2..];
+                    if !rest.is_empty() {
+                        ans.editor = Some(rest.to_string());
+                    } else if i + 1 < args.len() {
+                        i += 1;
+                        ans.editor = Some(args[i].clone());
+                    } else {
+                        let msg = "-e: option requires an argument";
+                        return Err(super::usage_error(2, "fc", msg, FC_USAGE, core));
+                    }
+                    break;
+                }
+                _ => {
+                    let msg = format!("-{}: invalid option", c);
+                    return Err(super::usage_error(2, "fc", &msg, FC_USAGE, core));
+                }
+            }
+        }
+        i += 1;
+    }
+
+    ans.operands = args[i..].to_vec();
+    Ok(ans)
+}
+
+/* the index of a history entry. A number out of range is None. */
+fn fc_index(spec: &str, history: &[String], last: usize) -> Result<Option<usize>, ()> {
+    if let Ok(n) = spec.parse::<i64>() {
+        return Ok(match n {
+            n if n < 0 => Some((last as i64 + 1 + n).max(0) as usize),
+            0 => Some(last),
+            n if n as usize > last + 1 => None,
+            n => Some(n as usize - 1),
+        });
+    }
 
-    let file = match File::open(&filename) {
-        Ok(f) => f,
-        _ => return 0,
+    match history[..=last].iter().rposition(|h| h.starts_with(spec)) {
+        Some(i) => Ok(Some(i)),
+        None => Err(()),
+    }
+}
+
+fn fc_range(opts: &FcOptions, history: &[String], last: usize) -> Result<(usize, usize), ()> {
+    let first = match opts.operands.first() {
+        Some(s) => fc_index(s, history, last)?,
+        None => match opts.list {
+            true => Some(last.saturating_sub(15)),
+            false => Some(last),
+        },
+    };
+    let end = match opts.operands.get(1) {
+        Some(s) => fc_index(s, history, last)?,
+        None if opts.list => Some(last),
+        None => first,
     };
 
-    let f = BufReader::new(file);
-    for line in f.lines() {
-        println!("{:5} {}", number, &line.unwrap());
-        number += 1;
+    match (first, end) {
+        (Some(f), Some(e)) => Ok((f, e)),
+        (None, Some(e)) => Ok((if opts.list { 0 } else { last }, e)),
+        (f, None) => Ok((f.unwrap_or(if opts.list { 0 } else { last }), last)),
     }
+}
 
-    for h in core.history.iter().rev() {
-        println!("{:5} {}", number, &h);
-        number += 1;
+fn fc_list(opts: &FcOptions, history: &[String], first: usize, last: usize) -> i32 {
+    let mut range: Vec<usize> = match first <= last {
+        true => (first..=last).collect(),
+        false => (last..=first).rev().collect(),
+    };
+    if opts.reverse {
+        range.reverse();
     }
 
+    for i in range {
+        match opts.no_number {
+            true => println!("\t {}", &history[i]),
+            false => println!("{}\t {}", i + 1, &history[i]),
+        }
+    }
     0
 }
+
+fn fc_exec(core: &mut ShellCore, script: &str) -> i32 {
+    eprintln!("{}", script.trim_end());
+    for line in script.lines().filter(|ln| !ln.is_empty()) {
+        core.history.insert(0, line.to_string());
+        core.record_history();
+    }
+    super::eval(core, &["eval".to_string(), script.to_string()])
+}
+
+fn fc_reexec(core: &mut ShellCore, opts: &FcOptions, history: &[String], last: usize) -> i32 {
+    let mut operands = opts.operands.clone();
+    let mut substs = vec![];
+    while let Some((p, r)) = operands.first().and_then(|s| s.split_once('=')) {
+        substs.push((p.to_string(), r.to_string()));
+        operands.remove(0);
+    }
+
+    let index = match operands.first() {
+        Some(spec) => fc_index(spec, history, last).map(|i| i.unwrap_or(last)),
+        None => Ok(last),
+    };
+    let mut command = match index {
+        Ok(i) => history[i].clone(),
+        Err(_) => return super::error_exit(1, "fc", "no command found", core),
+    };
+
+    for (pat, rep) in substs.iter().filter(|s| !s.0.is_empty()) {
+        command = command.replace(pat, rep);
+    }
+    fc_exec(core, &command)
+}
+
+fn fc_edit(core: &mut ShellCore, opts: &FcOptions, history: &[String], last: usize) -> i32 {
+    let (first, end) = match fc_range(opts, history, last) {
+        Ok(r) => r,
+        Err(_) => return super::error_exit(1, "fc", "no command found", core),
+    };
+    let mut range: Vec<usize> = match first <= end {
+        true => (first..=end).collect(),
+        false => (end..=first).rev().collect(),
+    };
+    if opts.reverse {
+        range.reverse();
+    }
+
+    let editor = match &opts.editor {
+        Some(e) => e.clone(),
+        None => ["FCEDIT", "EDITOR"]
+            .iter()
+            .map(|v| core.db.get_param(v).unwrap_or_default())
+            .find(|e| !e.is_empty())
+            .unwrap_or("vi".to_string()),
+    };
+
+    let path = std::env::temp_dir().join(format!("sush-fc-{}", std::process::id()));
+    let text: String = range.iter().map(|i| history[*i].clone() + "\n").collect();
+    if let Err(e) = std::fs::write(&path, text) {
+        let msg = format!("{}: {}", path.display(), e);
+        return super::error_exit(1, "fc", &msg, core);
+    }
+
+    let command = format!("{} {}", editor, path.display());
+    let status = super::eval(core, &["eval".to_string(), command]);
+    let script = std::fs::read_to_string(&path).unwrap_or_default();
+    let _ = std::fs::remove_file(&path);
+    if status != 0 {
+        return status;
+    }
+    fc_exec(core, &script)
+}
+
+pub fn fc(core: &mut ShellCore, args: &[String]) -> i32 {
+    let opts = match fc_parse_options(core, args) {
+        Ok(o) => o,
+        Err(status) => return status,
+    };
+    if !opts.list {
+        core.remove_current_history(); // replaced with the executed commands
+    }
+
+    let mut history = core.history_list();
+    if opts.list {
+        history.pop(); // this fc command
+    }
+    if history.is_empty() {
+        return super::error_exit(1, "fc", "history specification out of range", core);
+    }
+    let last = history.len() - 1;
+
+    if opts.reexec || opts.editor.as_deref() == Some("-") {
+        return fc_reexec(core, &opts, &history, last);
+    }
+
+    if opts.list {
+        return match fc_range(&opts, &history, last) {
+            Ok((first, end)) => fc_list(&opts, &history, first, end),
+            Err(_) => super::error_exit(1, "fc", "no command found", core),
+        };
+    }
+
+    fc_edit(core, &opts, &history, last)
+}
@@ -292,6 +292,7 @@ fn print(core: &mut ShellCore, args: &[String]) {
 }
 
 fn remove(core: &mut ShellCore, pos: usize) {
+    core.reap_coprocs();
     let job_id = core.job_table[pos].id;
     core.job_table.remove(pos);
     core.job_table_priority.retain(|id| *id != job_id);
@@ -0,0 +1,205 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use super::{error_exit, usage_error};
+use crate::core::xtrace::{self, QuoteStyle};
+use crate::utils::arg;
+use crate::{utils, ShellCore};
+use nix::errno::Errno;
+use nix::{fcntl, unistd};
+use std::os::fd::RawFd;
+
+struct MapfileOptions {
+    name: String,
+    delim: u8,
+    count: usize,
+    origin: Option<usize>,
+    skip: usize,
+    fd: RawFd,
+    callback: Option<String>,
+    quantum: usize,
+    trim: bool,
+}
+
+fn read_record(fd: RawFd, delim: u8) -> Result<Option<Vec<u8>>, Errno> {
+    let mut ans = vec![];
+    let mut ch = [0; 1];
+
+    loop {
+        match unistd::read(fd, &mut ch) {
+            Ok(0) => break,
+            Ok(_) => {
+                ans.push(ch[0]);
+                if ch[0] == delim {
+                    break;
+                }
+            }
+            Err(Errno::EINTR) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    match ans.is_empty() {
+        true => Ok(None),
+        false => Ok(Some(ans)),
+    }
+}
+
+fn parse_number(core: &mut ShellCore, s: &str, msg: &str) -> Result<usize, i32> {
+    match s.parse::<usize>() {
+        Ok(n) => Ok(n),
+        Err(_) => Err(error_exit(1, "mapfile", &format!("{s}: {msg}"), core)),
+    }
+}
+
+const USAGE: &str = "mapfile [-d delim] [-n count] [-O origin] [-s count] [-t] [-u fd] [-C callback] [-c quantum] [array]";
+
+fn parse_options(core: &mut ShellCore, args: &[String]) -> Result<MapfileOptions, i32> {
+    let mut args = arg::dissolve_options_with_values(args, "dnOsuCc");
+    let mut opts = MapfileOptions {
+        name: "MAPFILE".to_string(),
+        delim: b'\n',
+        count: 0,
+        origin: None,
+        skip: 0,
+        fd: 0,
+        callback: None,
+        quantum: 5000,
+        trim: false,
+    };
+
+    while args.len() > 1 && args[1].starts_with('-') && args[1] != "-" {
+        let opt = args.remove(1);
+        if opt == "--" {
+            break;
+        }
+        if "-d -n -O -s -u -C -c".split(' ').any(|o| o == opt) && args.len() < 2 {
+            let msg = format!("{}: option requires an argument", &opt);
+            return Err(usage_error(2, "mapfile", &msg, USAGE, core));
+        }
+
+        match opt.as_str() {
+            "-t" => opts.trim = true,
+            "-d" => opts.delim = args.remove(1).bytes().next().unwrap_or(0),
+            "-n" => opts.count = parse_number(core, &args.remove(1), "invalid line count")?,
+            "-O" => {
+                let n = args.remove(1);
+                opts.origin = Some(parse_number(core, &n, "invalid array origin")?);
+            }
+            "-s" => opts.skip = parse_number(core, &args.remove(1), "invalid line count")?,
+            "-u" => {
+                let n = args.remove(1);
+                opts.fd = match n.parse::<RawFd>() {
+                    Ok(fd) if fd >= 0 => fd,
+                    _ => {
+                        let msg = format!("{}: invalid file descriptor specification", &n);
+                        return Err(error_exit(1, "mapfile", &msg, core));
+                    }
+                };
+            }
+            "-C" => opts.callback = Some(args.remove(1)),
+            "-c" => {
+                let n = args.remove(1);
+                opts.quantum = match n.parse::<usize>() {
+                    Ok(q) if q > 0 => q,
+                    _ => {
+                        let msg = format!("{}: invalid callback quantum", &n);
+                        return Err(error_exit(1, "mapfile", &msg, core));
+                    }
+                };
+            }
+            _ => {
+                let msg = format!("{}: invalid option", &opt);
+                return Err(usage_error(2, "mapfile", &msg, USAGE, core));
+            }
+        }
+    }
+
+    if args.len() > 1 {
+        opts.name = args[1].clone();
+    }
+    if !utils::is_name(&opts.name, core) {
+        let msg = format!("`{}': not a valid identifier", &opts.name);
+        return Err(error_exit(1, "mapfile", &msg, core));
+    }
+
+    Ok(opts)
+}
+
+fn run_callback(core: &mut ShellCore, callback: &str, index: usize, line: &str) {
+    let script = format!(
+        "{} {} {}",
+        callback,
+        index,
+        xtrace::quote(line, QuoteStyle::Always)
+    );
+    super::eval(core, &["eval".to_string(), script]);
+}
+
+pub fn mapfile(core: &mut ShellCore, args: &[String]) -> i32 {
+    let opts = match parse_options(core, args) {
+        Ok(o) => o,
+        Err(es) => return es,
+    };
+
+    if core.db.is_assoc(&opts.name) {
+        let msg = format!("{}: not an indexed array", &opts.name);
+        return error_exit(1, "mapfile", &msg, core);
+    }
+
+    if fcntl::fcntl(opts.fd, fcntl::F_GETFD).is_err() {
+        let msg = format!("{}: invalid file descriptor: Bad file descriptor", opts.fd);
+        return error_exit(1, "mapfile", &msg, core);
+    }
+
+    let mut index = match opts.origin {
+        Some(n) => n,
+        None => {
+            if let Err(e) = core.db.set_array(&opts.name, Some(vec![]), None) {
+                return error_exit(1, "mapfile", &String::from(&e), core);
+            }
+            0
+        }
+    };
+
+    let mut read_num = 0;
+    let mut stored_num = 0;
+    while opts.count == 0 || stored_num < opts.count {
+        let mut record = match read_record(opts.fd, opts.delim) {
+            Ok(Some(r)) => r,
+            Ok(None) => break,
+            Err(e) => {
+                let msg = format!("read error: {}: {}", opts.fd, e.desc());
+                return error_exit(1, "mapfile", &msg, core);
+            }
+        };
+
+        read_num += 1;
+        if read_num <= opts.skip {
+            continue;
+        }
+
+        if opts.trim && record.last() == Some(&opts.delim) {
+            record.pop();
+        }
+        let line = String::from_utf8_lossy(&record).to_string();
+
+        if let Some(cb) = &opts.callback {
+            if (stored_num + 1) % opts.quantum == 0 {
+                run_callback(core, cb, index, &line);
+            }
+        }
+
+        if let Err(e) = core
+            .db
+            .set_array_elem(&opts.name, &line, index as isize, None)
+        {
+            return error_exit(1, "mapfile", &String::from(&e), core);
+        }
+
+        index += 1;
+        stored_num += 1;
+    }
+
+    0
+}
@@ -53,20 +53,24 @@ pub fn set_options(core: &mut ShellCore, args: &mut Vec<String>) -> Result<(), E
     check_invalid_options(args)
 }
 
+const SHORT_OPTIONS: [(char, &str); 13] = [
+    ('t', "onecmd"),
+    ('m', "monitor"),
+    ('C', "noclobber"),
+    ('a', "allexport"),
+    ('B', "braceexpand"),
+    ('e', "errexit"),
+    ('E', "errtrace"),
+    ('T', "functrace"),
+    ('u', ""),
+    ('r', ""),
+    ('H', "histexpand"),
+    ('x', ""),
+    ('v', ""),
+];
+
 pub fn set_short_options(core: &mut ShellCore, args: &mut Vec<String>) {
-    for (short, long) in [
-        ('t', "onecmd"),
-        ('m', "monitor"),
-        ('C', "noclobber"),
-        ('a', "allexport"),
-        ('B', "braceexpand"),
-        ('u', ""),
-        ('e', ""),
-        ('r', ""),
-        ('H', ""),
-        ('x', ""),
-        ('v', ""),
-    ] {
+    for (short, long) in SHORT_OPTIONS {
         let minus_opt = format!("-{short}");
         let plus_opt = format!("+{short}");
 
@@ -131,11 +135,11 @@ pub fn set(core: &mut ShellCore, args: &[String]) -> i32 {
             core.options.print_all(positive);
             return 0;
         } else {
-            if args[2] == "monitor" {
-                if positive && !core.db.flags.contains('m') {
-                    core.db.flags.push('m');
+            if let Some((short, _)) = SHORT_OPTIONS.iter().find(|(_, l)| *l == args[2]) {
+                if positive && !core.db.flags.contains(*short) {
+                    core.db.flags.push(*short);
                 } else if !positive {
-                    core.db.flags.retain(|f| f != 'm');
+                    core.db.flags.retain(|f| f != *short);
                 }
             }
 
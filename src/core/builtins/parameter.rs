@@ -52,6 +52,18 @@ fn set_substitution(
         layer = 0;
     }
 
+    if arg::has_option("-n", args) {
+        set_nameref(core, sub, layer)?;
+        if read_only {
+            core.db.set_flag(&sub.left_hand.name, 'r', Some(layer));
+        }
+        return Ok(());
+    }
+
+    if arg::has_option("+n", args) {
+        core.db.unset_flag(&sub.left_hand.name, 'n', Some(layer));
+    }
+
     if arg::has_option("+i", args) && core.db.has_flag_layer(&sub.left_hand.name, 'i', layer) {
         core.db.int_to_str_type(&sub.left_hand.name, layer)?;
     }
@@ -119,8 +131,48 @@ fn set_substitution(
     res
 }
 
+fn set_nameref(
+    core: &mut ShellCore,
+    sub: &mut Substitution,
+    layer: usize,
+) -> Result<(), ExecError> {
+    let name = sub.left_hand.name.clone();
+    if sub.left_hand.index.is_some() {
+        return Err(ExecError::InvalidName(sub.left_hand.text.clone()));
+    }
+
+    let target = if sub.has_right {
+        sub.eval_right_hand(core, true)?;
+        sub.right_hand.evaluated_string.clone().unwrap_or_default()
+    } else if let Some(t) = core.db.get_nameref(&name) {
+        t
+    } else if core.db.params[layer].contains_key(&name) {
+        core.db.get_param(&name)?
+    } else {
+        String::new()
+    };
+
+    core.db.set_nameref(&name, &target, Some(layer))
+}
+
+fn declare_print_nameref(core: &mut ShellCore, name: &str, target: &str) {
+    let opt = match core.db.is_readonly(name) {
+        true => "nr",
+        false => "n",
+    };
+    match target.is_empty() {
+        true => println!("declare -{} {}", opt, name),
+        false => println!("declare -{} {}=\"{}\"", opt, name, target),
+    }
+}
+
 fn declare_print(core: &mut ShellCore, names: &[String], com: &str) -> i32 {
     for n in names {
+        if let Some(target) = core.db.get_nameref(n) {
+            declare_print_nameref(core, n, &target);
+            continue;
+        }
+
         let mut opt = if core.db.is_assoc(n) {
             "A"
         } else if core.db.is_array(n) {
@@ -128,7 +180,7 @@ fn declare_print(core: &mut ShellCore, names: &[String], com: &str) -> i32 {
         } else if core.db.exist(n) {
             ""
         } else {
-            return error_exit(1, n, "not found", core);
+            return error_exit(1, com, &format!("{}: not found", n), core);
         }
         .to_string();
 
@@ -197,6 +249,11 @@ fn declare_print_all(core: &mut ShellCore, args: &[String]) -> i32 {
         options += "A";
     }
 
+    if arg::has_option("-n", args) {
+        names.retain(|n| core.db.is_nameref(n));
+        options += "n";
+    }
+
     if arg::has_option("-r", args) {
         names.retain(|n| core.db.is_readonly(n));
         if !core.options.query("posix") {
@@ -1,402 +1,564 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
 //SPDX-License-Identifier: BSD-3-Clause
 
+mod number;
+
+use self::number::NumError;
+use super::error_exit;
+use crate::core::xtrace::{self, QuoteStyle};
 use crate::elements::substitution::Substitution;
-use crate::error::arith::ArithError;
-use crate::error::exec::ExecError;
-use crate::{error, Feeder, ShellCore};
+use crate::utils::{arg, clock};
+use crate::{Feeder, ShellCore};
+use nix::errno::Errno;
+use nix::unistd;
 use std::io::{stdout, Write};
 
-#[derive(Debug, Clone)]
-enum PrintfToken {
-    B(String),
-    DI(String),
-    F(String),
-    O(String),
-    S(String),
-    U(String),
-    X(String),
-    LargeX(String),
-    Q,
-    Other(String),
-    Normal(String),
-    EscapedChar(char),
+/* the flags, the width and the precision of a conversion */
+#[derive(Debug, Default)]
+struct Spec {
+    left: bool,
+    plus: bool,
+    space: bool,
+    alt: bool,
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
 }
 
-impl PrintfToken {
-    fn continue_(&self) -> bool {
-        !matches!(self, Self::Normal(_) | Self::EscapedChar(_))
+impl Spec {
+    fn pad(&self, text: &[u8]) -> Vec<u8> {
+        let fill = vec![b' '; self.width.saturating_sub(text.len())];
+        match self.left {
+            true => [text, &fill].concat(),
+            false => [&fill, text].concat(),
+        }
     }
 
-    fn to_int(s: &String) -> Result<isize, ExecError> {
-        match s.parse::<isize>() {
-            Ok(n) => Ok(n),
-            Err(_) => Err(ArithError::InvalidNumber(s.to_string()).into()),
+    /* with the 0 flag, zeros are put between the prefix (a sign or 0x) and the digits */
+    fn pad_number(&self, prefix: &str, digits: &str, zero_allowed: bool) -> Vec<u8> {
+        if self.zero && zero_allowed && !self.left {
+            let len = self.width.saturating_sub(prefix.len() + digits.len());
+            return format!("{}{}{}", prefix, "0".repeat(len), digits).into_bytes();
         }
+        self.pad(format!("{}{}", prefix, digits).as_bytes())
     }
 
-    fn to_float(s: &String) -> Result<f64, ExecError> {
-        match s.parse::<f64>() {
-            Ok(n) => Ok(n),
-            Err(_) => Err(ArithError::InvalidNumber(s.to_string()).into()),
+    fn truncate<'a>(&self, text: &'a [u8]) -> &'a [u8] {
+        match self.precision {
+            Some(p) if p < text.len() => &text[..p],
+            _ => text,
         }
     }
+}
 
-    //TODO: implement!
-    fn padding_float(_: &mut String, mut _fmt: String) {
-        if _fmt.is_empty() {}
-    }
+fn push_char(buf: &mut Vec<u8>, c: char) {
+    let mut tmp = [0; 4];
+    buf.extend(c.encode_utf8(&mut tmp).as_bytes());
+}
 
-    fn padding(s: &mut String, mut fmt: String, is_int: bool) {
-        if fmt.is_empty() {
-            return;
-        }
+fn digits_len(chars: &[char], radix: u32, max: usize) -> usize {
+    chars
+        .iter()
+        .take(max)
+        .take_while(|c| c.is_digit(radix))
+        .count()
+}
 
-        let mut right = false;
-        let mut padding = ' ';
+/* None if the value overflows */
+fn digits_value(chars: &[char], radix: u32) -> Option<u32> {
+    chars.iter().try_fold(0u32, |n, c| {
+        n.checked_mul(radix)?
+            .checked_add(c.to_digit(radix).unwrap())
+    })
+}
 
-        if fmt.starts_with("-") {
-            fmt.remove(0);
-            right = true;
-        }
-        if fmt.starts_with("0") {
-            padding = fmt.remove(0);
+/* expands the escape sequence whose backslash is just before chars[pos] into buf.
+ * in_b: for %b, where \c stops the output and \0nnn is an octal number.
+ * returns the position after the sequence and whether \c appears. */
+fn expand_escape(
+    core: &mut ShellCore,
+    chars: &[char],
+    pos: usize,
+    in_b: bool,
+    buf: &mut Vec<u8>,
+) -> (usize, bool) {
+    let c = match chars.get(pos) {
+        Some(c) => *c,
+        None => {
+            buf.push(b'\\');
+            return (pos, false);
         }
+    };
 
-        if !is_int {
-            padding = ' ';
+    let byte = match c {
+        'a' => 7,
+        'b' => 8,
+        'e' | 'E' => 27,
+        'f' => 12,
+        'n' => b'\n',
+        'r' => b'\r',
+        't' => b'\t',
+        'v' => 11,
+        '\\' => b'\\',
+        '\'' | '"' | '?' if !in_b => c as u8,
+        'c' if in_b => return (pos + 1, true),
+        '0'..='7' => {
+            let start = if in_b && c == '0' { pos + 1 } else { pos };
+            let len = digits_len(&chars[start..], 8, 3);
+            buf.push(digits_value(&chars[start..start + len], 8).unwrap_or(0) as u8);
+            return (start + len, false);
         }
-
-        let len = fmt.parse::<usize>().unwrap_or(0);
-        if right {
-            while s.len() < len {
-                s.push(' ');
+        'x' | 'u' | 'U' => {
+            let max = match c {
+                'x' => 2,
+                'u' => 4,
+                _ => 8,
+            };
+            let len = digits_len(&chars[pos + 1..], 16, max);
+            if len == 0 {
+                let kind = if c == 'x' { "hex" } else { "unicode" };
+                let msg = format!("missing {} digit for \\{}", kind, c);
+                error_exit(1, "printf", &msg, core);
+                buf.push(b'\\');
+                push_char(buf, c);
+                return (pos + 1, false);
             }
-            return;
-        }
 
-        if is_int && s.starts_with("-") && padding == '0' {
-            while s.len() < len {
-                s.insert(1, '0');
+            let n = digits_value(&chars[pos + 1..pos + 1 + len], 16).unwrap_or(0);
+            match (c, char::from_u32(n)) {
+                ('x', _) => buf.push(n as u8),
+                (_, Some(ch)) => push_char(buf, ch),
+                (_, None) => buf.extend(
+                    chars[pos - 1..pos + 1 + len]
+                        .iter()
+                        .collect::<String>()
+                        .bytes(),
+                ),
             }
-            return;
+            return (pos + 1 + len, false);
         }
+        c => {
+            buf.push(b'\\');
+            push_char(buf, c);
+            return (pos + 1, false);
+        }
+    };
+    buf.push(byte);
+    (pos + 1, false)
+}
 
-        while s.len() < len {
-            s.insert(0, padding);
+#[derive(Debug, Default)]
+struct Printf {
+    args: Vec<String>,
+    pos: usize,
+    out: Vec<u8>,
+    status: i32,
+    stopped: bool,
+    to_var: bool,
+}
+
+impl Printf {
+    fn next_arg(&mut self) -> Option<String> {
+        let ans = self.args.get(self.pos).cloned();
+        if ans.is_some() {
+            self.pos += 1;
         }
+        ans
     }
 
-    fn render_value(&mut self, args: &mut Vec<String>) -> Result<String, ExecError> {
-        match self {
-            Self::DI(fmt) => {
-                let mut a = pop(args);
-                Self::padding(&mut a, fmt.clone(), true);
-                Ok(a)
-            }
-            Self::U(fmt) => {
-                let mut a = pop(args);
-                if a.starts_with("-") {
-                    a.remove(0);
-                    let mut num = a.parse::<u64>()?;
-                    num = u64::MAX - num + 1;
-                    let mut a = num.to_string();
-                    Self::padding(&mut a, fmt.clone(), true);
-                    Ok(a)
-                } else {
-                    Self::padding(&mut a, fmt.clone(), true);
-                    Ok(a)
-                }
-            }
-            Self::F(fmt) => {
-                let mut a = format!("{:.6}", Self::to_float(&pop(args))?);
-                Self::padding_float(&mut a, fmt.clone());
-                Ok(a)
-            }
-            Self::S(fmt) => {
-                let mut a = pop(args);
-                Self::padding(&mut a, fmt.clone(), false);
-                Ok(a)
-            }
-            Self::B(fmt) => {
-                let mut a = replace_escape(&pop(args));
-                Self::padding(&mut a, fmt.clone(), false);
-                Ok(a)
-            }
-            Self::X(fmt) => {
-                let mut a = format!("{:x}", Self::to_int(&pop(args))?);
-                Self::padding(&mut a, fmt.clone(), true);
-                Ok(a)
-            }
-            Self::O(fmt) => {
-                let mut a = format!("{:o}", Self::to_int(&pop(args))?);
-                Self::padding(&mut a, fmt.clone(), true);
-                Ok(a)
-            }
-            Self::LargeX(fmt) => {
-                let mut a = format!("{:X}", Self::to_int(&pop(args))?);
-                Self::padding(&mut a, fmt.clone(), true);
-                Ok(a)
-            }
-            Self::Q => {
-                let a = pop(args);
-                let q = a
-                    .replace("\\", "\\\\")
-                    .replace("$", "\\$")
-                    .replace("|", "\\|")
-                    .replace("\"", "\\\"")
-                    .replace("'", "\\\'")
-                    .replace("~", "\\~")
-                    .replace("(", "\\(")
-                    .replace(")", "\\)")
-                    .replace("{", "\\{")
-                    .replace("}", "\\}")
-                    .replace("!", "\\!")
-                    .replace("&", "\\&");
-                Ok(q)
-            }
-            Self::Other(s) => {
-                let a = pop(args);
-                let formatted = match sprintf::sprintf!(&s, a) {
-                    Ok(res) => res,
-                    Err(e) => {
-                        let msg = format!("{} {} {}", &e, &s, &a);
-                        return Err(ExecError::Other(msg));
-                    }
-                };
+    fn string_arg(&mut self) -> String {
+        self.next_arg().unwrap_or_default()
+    }
 
-                Ok(formatted)
+    fn check_number(&mut self, core: &mut ShellCore, arg: &str, err: Option<NumError>) {
+        let msg = match err {
+            None => return,
+            Some(NumError::OutOfRange) => {
+                let msg = format!("warning: {}: Numerical result out of range", arg);
+                error_exit(1, "printf", &msg, core);
+                return;
             }
-            Self::EscapedChar(c) => Ok(esc_to_str(*c)),
-            Self::Normal(s) => Ok(s.clone()),
-        }
+            Some(NumError::Invalid) if arg.starts_with("0x") => "invalid hex number",
+            Some(NumError::Invalid)
+                if arg.len() > 1 && arg.starts_with('0') && arg.as_bytes()[1].is_ascii_digit() =>
+            {
+                "invalid octal number"
+            }
+            Some(NumError::Invalid) => "invalid number",
+        };
+        error_exit(1, "printf", &format!("{}: {}", arg, msg), core);
+        self.status = 1;
     }
-}
 
-fn pop(args: &mut Vec<String>) -> String {
-    match args.is_empty() {
-        true => "".to_string(),
-        false => args.remove(0),
+    fn int_arg(&mut self, core: &mut ShellCore) -> i64 {
+        let arg = self.string_arg();
+        let (n, err) = number::parse_int(&arg);
+        self.check_number(core, &arg, err);
+        n
     }
-}
 
-fn esc_to_str(ch: char) -> String {
-    match ch {
-        'a' => char::from(7).to_string(),
-        'b' => char::from(8).to_string(),
-        'e' | 'E' => char::from(27).to_string(),
-        'f' => char::from(12).to_string(),
-        'n' => "\n".to_string(),
-        'r' => "\r".to_string(),
-        't' => "\t".to_string(),
-        'v' => char::from(11).to_string(),
-        '\\' => "\\".to_string(),
-        '\'' => "'".to_string(),
-        '"' => "\"".to_string(),
-        _ => ("\\".to_owned() + &ch.to_string()).to_string(),
+    /* a width or a precision with `*`, which is clamped into the range of int */
+    fn spec_arg(&mut self, core: &mut ShellCore) -> i64 {
+        let arg = self.string_arg();
+        let (n, err) = number::parse_int(&arg);
+        let err = match i32::try_from(n) {
+            Ok(_) => err,
+            Err(_) => err.or(Some(NumError::OutOfRange)),
+        };
+        self.check_number(core, &arg, err);
+        n.clamp(i32::MIN as i64, i32::MAX as i64)
     }
-}
 
-fn replace_escape(s: &str) -> String {
-    let mut ans = String::new();
-    let mut esc = false;
-
-    for ch in s.chars() {
-        if esc || ch == '\\' {
-            if esc {
-                ans.push_str(&esc_to_str(ch));
+    /* a width or a precision written in the format */
+    fn spec_digits(&mut self, core: &mut ShellCore, chars: &[char]) -> usize {
+        match digits_value(chars, 10).filter(|n| *n <= i32::MAX as u32) {
+            Some(n) => n as usize,
+            None => {
+                let text: String = chars.iter().collect();
+                self.check_number(core, &text, Some(NumError::OutOfRange));
+                i32::MAX as usize
             }
-            esc = !esc;
-            continue;
         }
-
-        ans.push(ch);
     }
 
-    ans
-}
+    fn uint_arg(&mut self, core: &mut ShellCore) -> u64 {
+        let arg = self.string_arg();
+        let (n, err) = number::parse_uint(&arg);
+        self.check_number(core, &arg, err);
+        n
+    }
 
-fn scanner_normal(remaining: &str) -> usize {
-    let mut pos = 0;
+    fn float_arg(&mut self, core: &mut ShellCore) -> f64 {
+        let arg = self.string_arg();
+        let (f, err) = number::parse_float(&arg);
+        self.check_number(core, &arg, err);
+        f
+    }
 
-    for c in remaining.chars() {
-        if c == '%' || c == '\\' {
-            break;
+    /* -1: the current time, -2: the time when the shell started */
+    fn time_arg(&mut self, core: &mut ShellCore) -> i64 {
+        let now = clock::epoch_seconds();
+        let time = match self.args.get(self.pos) {
+            None => -1,
+            Some(_) => self.int_arg(core),
+        };
+
+        match time {
+            -1 => now,
+            -2 => {
+                let elapsed =
+                    clock::monotonic_time().as_secs() as i64 - core.measured_time.start.tv_sec();
+                now - elapsed
+            }
+            t => t,
         }
-        pos += c.len_utf8();
     }
-    pos
-}
 
-fn scanner_escaped_char(remaining: &str) -> usize {
-    if !remaining.starts_with("\\") {
-        return 0;
+    fn invalid_character(&mut self, core: &mut ShellCore, c: char) -> Result<usize, ()> {
+        let msg = format!("`{}': invalid format character", c);
+        error_exit(1, "printf", &msg, core);
+        self.status = 1;
+        Err(())
     }
 
-    match remaining.chars().nth(1) {
-        Some(ch) => 1 + ch.len_utf8(),
-        _ => 0,
-    }
-}
+    fn read_spec(&mut self, core: &mut ShellCore, chars: &[char], pos: &mut usize) -> Spec {
+        let mut spec = Spec::default();
+        while let Some(c) = chars.get(*pos) {
+            match c {
+                '-' => spec.left = true,
+                '+' => spec.plus = true,
+                ' ' => spec.space = true,
+                '#' => spec.alt = true,
+                '0' => spec.zero = true,
+                '\'' => {} // grouping of digits, which is nothing in the C locale
+                _ => break,
+            }
+            *pos += 1;
+        }
 
-fn scanner_format_num(remaining: &str) -> usize {
-    let mut ans = 0;
-    for c in remaining.chars() {
-        if !"-.".contains(c) && !c.is_ascii_digit() {
-            break;
+        if chars.get(*pos) == Some(&'*') {
+            *pos += 1;
+            let width = self.spec_arg(core);
+            spec.left |= width < 0;
+            spec.width = width.unsigned_abs() as usize;
+        } else {
+            let len = digits_len(&chars[*pos..], 10, usize::MAX);
+            spec.width = self.spec_digits(core, &chars[*pos..*pos + len]);
+            *pos += len;
         }
 
-        ans += 1;
-    }
-    ans
-}
+        if chars.get(*pos) == Some(&'.') {
+            *pos += 1;
+            if chars.get(*pos) == Some(&'*') {
+                *pos += 1;
+                let precision = self.spec_arg(core);
+                spec.precision = usize::try_from(precision).ok();
+            } else {
+                let len = digits_len(&chars[*pos..], 10, usize::MAX);
+                spec.precision = Some(self.spec_digits(core, &chars[*pos..*pos + len]));
+                *pos += len;
+            }
+        }
 
-fn parse(pattern: &str) -> Vec<PrintfToken> {
-    let mut remaining = pattern.to_string();
-    let mut ans = vec![];
-
-    while !remaining.is_empty() {
-        let len = scanner_normal(&remaining);
-        if len > 0 {
-            let tail = remaining.split_off(len);
-            ans.push(PrintfToken::Normal(remaining));
-            remaining = tail;
-            continue;
+        while chars.get(*pos).is_some_and(|c| "hjlLtz".contains(*c)) {
+            *pos += 1;
         }
+        spec
+    }
 
-        let len = scanner_escaped_char(&remaining);
-        if len > 0 {
-            remaining.remove(0);
-            ans.push(PrintfToken::EscapedChar(remaining.remove(0)));
-            continue;
+    /* %(datefmt)T */
+    fn time(
+        &mut self,
+        core: &mut ShellCore,
+        chars: &[char],
+        pos: usize,
+        spec: &Spec,
+    ) -> Option<usize> {
+        let end = pos + chars[pos..].iter().position(|c| *c == ')')?;
+        if chars.get(end + 1) != Some(&'T') {
+            return None;
         }
 
-        if remaining.starts_with("%") {
-            remaining.remove(0); // %
+        let format: String = chars[pos..end].iter().collect();
+        let format = if format.is_empty() { "%X" } else { &format };
+        let time = self.time_arg(core);
+        let text = clock::strftime(format, time);
+        self.out.extend(spec.pad(spec.truncate(text.as_bytes())));
+        Some(end + 2)
+    }
 
-            let mut num_part = String::new();
-            let len = scanner_format_num(&remaining);
-            if len > 0 {
-                let tail = remaining.split_off(len);
-                num_part = remaining.clone();
-                remaining = tail;
-            }
+    /* the conversion from the % at chars[start]. Err if the output stops there. */
+    fn conversion(
+        &mut self,
+        core: &mut ShellCore,
+        chars: &[char],
+        start: usize,
+    ) -> Result<usize, ()> {
+        let mut pos = start + 1;
+        if chars.get(pos) == Some(&'%') {
+            self.out.push(b'%');
+            return Ok(pos + 1);
+        }
 
-            let token = match remaining.chars().next() {
-                Some('b') => PrintfToken::B(num_part),
-                Some('d') => PrintfToken::DI(num_part),
-                Some('i') => PrintfToken::DI(num_part),
-                Some('f') => PrintfToken::F(num_part),
-                Some('o') => PrintfToken::O(num_part),
-                Some('s') => PrintfToken::S(num_part),
-                Some('u') => PrintfToken::U(num_part),
-                Some('x') => PrintfToken::X(num_part),
-                Some('X') => PrintfToken::LargeX(num_part),
-                Some('q') => PrintfToken::Q,
-                Some(c) => PrintfToken::Other("%".to_owned() + &num_part + &c.to_string()),
-                None => PrintfToken::Normal("%".to_string()),
-            };
+        let spec = self.read_spec(core, chars, &mut pos);
+        let conv = match chars.get(pos) {
+            Some(c) => *c,
+            None => {
+                let text: String = chars[start..].iter().collect();
+                let msg = format!("`{}': missing format character", text);
+                error_exit(1, "printf", &msg, core);
+                self.status = 1;
+                return Err(());
+            }
+        };
+        pos += 1;
 
-            remaining.remove(0);
-            ans.push(token);
+        match conv {
+            'd' | 'i' => {
+                let n = self.int_arg(core);
+                self.out.extend(number::format_int(n, &spec));
+            }
+            'u' | 'o' | 'x' | 'X' => {
+                let n = self.uint_arg(core);
+                self.out.extend(number::format_uint(n, conv, &spec));
+            }
+            'f' | 'F' | 'e' | 'E' | 'g' | 'G' | 'a' | 'A' => {
+                let f = self.float_arg(core);
+                self.out.extend(number::format_float(f, conv, &spec));
+            }
+            'c' => {
+                let arg = self.string_arg();
+                let c = arg.as_bytes().first().copied().unwrap_or(0);
+                self.out.extend(spec.pad(&[c]));
+            }
+            's' => {
+                let arg = self.string_arg();
+                self.out.extend(spec.pad(spec.truncate(arg.as_bytes())));
+            }
+            'b' => {
+                let arg: Vec<char> = self.string_arg().chars().collect();
+                let mut buf = vec![];
+                let mut i = 0;
+                while i < arg.len() && !self.stopped {
+                    match arg[i] {
+                        '\\' => {
+                            (i, self.stopped) = expand_escape(core, &arg, i + 1, true, &mut buf)
+                        }
+                        c => {
+                            push_char(&mut buf, c);
+                            i += 1;
+                        }
+                    }
+                }
+                self.out.extend(spec.pad(spec.truncate(&buf)));
+            }
+            'q' => {
+                let arg = xtrace::quote(&self.string_arg(), QuoteStyle::Backslash);
+                self.out.extend(spec.pad(spec.truncate(arg.as_bytes())));
+            }
+            'Q' => {
+                let arg = self.string_arg();
+                let arg = String::from_utf8_lossy(spec.truncate(arg.as_bytes())).to_string();
+                self.out
+                    .extend(spec.pad(xtrace::quote(&arg, QuoteStyle::Backslash).as_bytes()));
+            }
+            '(' => {
+                if let Some(next) = self.time(core, chars, pos, &spec) {
+                    return Ok(next);
+                }
+                let c = match chars[pos..].iter().position(|c| *c == ')') {
+                    Some(end) => chars.get(pos + end + 1).copied().unwrap_or('\0'),
+                    None => '\0',
+                };
+                let msg = format!("warning: `{}': invalid time format specification", c);
+                error_exit(1, "printf", &msg, core);
+                self.out.extend(b"%(");
+            }
+            c => return self.invalid_character(core, c),
         }
+        Ok(pos)
     }
 
-    ans
-}
-
-fn format(pattern: &str, args: &mut Vec<String>) -> Result<String, ExecError> {
-    let mut ans = String::new();
+    fn format_once(&mut self, core: &mut ShellCore, chars: &[char]) -> Result<(), ()> {
+        let mut pos = 0;
+        while pos < chars.len() && !self.stopped {
+            match chars[pos] {
+                '\\' => {
+                    let mut buf = vec![];
+                    (pos, _) = expand_escape(core, chars, pos + 1, false, &mut buf);
+                    self.out.extend(buf);
+                }
+                '%' => pos = self.conversion(core, chars, pos)?,
+                c => {
+                    push_char(&mut self.out, c);
+                    pos += 1;
+                }
+            }
+        }
+        Ok(())
+    }
 
-    let mut tokens = parse(pattern);
-    let mut fin = true;
+    /* the output of each pass is written unless it is assigned with -v */
+    fn flush(&mut self, core: &mut ShellCore) -> Result<(), ()> {
+        if self.to_var {
+            return Ok(());
+        }
 
-    for tok in tokens.iter_mut() {
-        if tok.continue_() {
-            fin = false;
+        let _ = stdout().flush();
+        let mut pos = 0;
+        while pos < self.out.len() {
+            match unistd::write(stdout(), &self.out[pos..]) {
+                Ok(n) => pos += n,
+                Err(Errno::EINTR) => {}
+                Err(e) => {
+                    let desc = match e {
+                        Errno::EBADF => "Bad file descriptor",
+                        e => e.desc(),
+                    };
+                    let msg = format!("write error: {}", desc);
+                    error_exit(1, "printf", &msg, core);
+                    self.status = 1;
+                    return Err(());
+                }
+            }
         }
-        ans += &tok.render_value(args)?;
+        self.out.clear();
+        Ok(())
     }
 
-    if !args.is_empty() && !fin {
-        if let Ok(s) = format(pattern, args) {
-            ans += &s;
+    /* the format is reused while it consumes the remaining arguments */
+    fn format(&mut self, core: &mut ShellCore, format: &str) {
+        let chars: Vec<char> = format.chars().collect();
+        loop {
+            let start = self.pos;
+            let result = self.format_once(core, &chars);
+            if self.flush(core).is_err() || result.is_err() || self.stopped {
+                return;
+            }
+            if self.pos == start || self.pos >= self.args.len() {
+                return;
+            }
         }
     }
-    Ok(ans)
 }
 
-fn arg_check(core: &mut ShellCore, args: &[String]) -> i32 {
-    if args.len() < 2 || args[1] == "--help" || args[1] == "-v" && args.len() == 3 {
-        let msg = "printf: usage: printf [-v var] format [arguments]".to_string();
-        error::print(&msg, core);
-        return 2;
-    }
+const USAGE: &str = "printf [-v var] format [arguments]";
 
-    if args[1] == "-v" && args.len() == 2 {
-        let msg = "printf: -v: option requires an argument".to_string();
-        error::print(&msg, core);
-        let msg = "printf: usage: printf [-v var] format [arguments]".to_string();
-        error::print(&msg, core);
-        return 2;
-    }
-
-    0
+fn usage() -> i32 {
+    eprintln!("printf: usage: {}", USAGE);
+    2
 }
 
-fn printf_v(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    if args[3] == "--" {
-        args.remove(3);
-    }
+fn printf_v(core: &mut ShellCore, name: &str, value: &[u8]) -> i32 {
+    let value = String::from_utf8_lossy(value).replace('\0', "");
 
-    let s = match format(&args[3], &mut args[4..].to_vec()) {
-        Ok(ans) => ans,
-        Err(e) => {
-            let msg = String::from(&e);
-            return super::error_exit(1, "printf", &msg, core);
-        }
-    };
-
-    if args[2].contains("[") {
-        let mut f = Feeder::new(&(args[2].clone() + "=" + &s));
+    if name.contains("[") {
+        let mut f =
+            Feeder::new(&(name.to_string() + "=" + &xtrace::quote(&value, QuoteStyle::Backslash)));
         if let Ok(Some(mut a)) = Substitution::parse(&mut f, core, false) {
             if let Err(e) = a.eval(core, None, false) {
                 let msg = String::from(&e);
-                return super::error_exit(2, "printf", &msg, core);
+                return error_exit(2, "printf", &msg, core);
             }
         } else {
             return 1;
         }
         return 0;
     }
-    if let Err(e) = core.db.set_param(&args[2], &s, None) {
+    if let Err(e) = core.db.set_param(name, &value, None) {
         let msg = String::from(&e);
-        return super::error_exit(2, "printf", &msg, core);
+        return error_exit(2, "printf", &msg, core);
     }
 
     0
 }
 
 pub fn printf(core: &mut ShellCore, args: &[String]) -> i32 {
-    let mut args = args.to_owned();
-    match arg_check(core, &args) {
-        0 => {}
-        n => return n,
+    if args.len() > 1 && args[1] == "--help" {
+        return usage();
     }
 
-    if args[1] == "-v" {
-        return printf_v(core, &mut args);
+    let args = arg::dissolve_options_with_values(args, "v");
+    let mut var = None;
+    let mut pos = 1;
+    while pos < args.len() && args[pos].starts_with('-') && args[pos] != "-" {
+        pos += 1;
+        match args[pos - 1].as_str() {
+            "--" => break,
+            "-v" => match args.get(pos) {
+                Some(name) => {
+                    var = Some(name.clone());
+                    pos += 1;
+                }
+                None => {
+                    let msg = "-v: option requires an argument";
+                    return super::usage_error(2, "printf", msg, USAGE, core);
+                }
+            },
+            a => {
+                let msg = format!("{}: invalid option", a);
+                return super::usage_error(2, "printf", &msg, USAGE, core);
+            }
+        }
     }
 
-    let s = match format(&args[1], &mut args[2..].to_vec()) {
-        Ok(ans) => ans,
-        Err(e) => {
-            let msg = format!("printf: {e:?}");
-            error::print(&msg, core);
-            return 1;
-        }
+    let format = match args.get(pos) {
+        Some(f) => f,
+        None => return usage(),
     };
-    print!("{}", &s);
-    stdout().flush().unwrap();
-    0
+
+    let mut printf = Printf {
+        args: args[pos + 1..].to_vec(),
+        to_var: var.is_some(),
+        ..Default::default()
+    };
+    printf.format(core, format);
+
+    if let Some(name) = var {
+        return match printf_v(core, &name, &printf.out) {
+            0 => printf.status,
+            n => n,
+        };
+    }
+    printf.status
 }
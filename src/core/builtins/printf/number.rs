@@ -0,0 +1,369 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use super::Spec;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumError {
+    Invalid,
+    OutOfRange,
+}
+
+/* 'A or "A: the code of the character */
+fn char_code(s: &str) -> Option<u32> {
+    let rest = s.strip_prefix(['\'', '"'])?;
+    Some(rest.chars().next().map(|c| c as u32).unwrap_or(0))
+}
+
+fn split_sign(s: &str) -> (bool, &str) {
+    match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    }
+}
+
+/* the sign and the absolute value like strtoimax(3) with base 0 */
+fn parse_integer(s: &str) -> (bool, u128, Option<NumError>) {
+    let s = s.trim_start_matches([' ', '\t', '\n', '\r', '\x0b', '\x0c']);
+    if let Some(code) = char_code(s) {
+        return (false, code as u128, None);
+    }
+
+    let (negative, s) = split_sign(s);
+    let (radix, digits) = match s.strip_prefix("0x").or(s.strip_prefix("0X")) {
+        Some(d) => (16, d),
+        None if s.starts_with('0') => (8, s),
+        None => (10, s),
+    };
+
+    let len = digits.chars().take_while(|c| c.is_digit(radix)).count();
+    if len == 0 {
+        return (negative, 0, Some(NumError::Invalid));
+    }
+    let n = digits[..len].chars().fold(0u128, |n, c| {
+        n.saturating_mul(radix as u128)
+            .saturating_add(c.to_digit(radix).unwrap() as u128)
+    });
+
+    match len < digits.len() {
+        true => (negative, n, Some(NumError::Invalid)),
+        false => (negative, n, None),
+    }
+}
+
+pub fn parse_int(s: &str) -> (i64, Option<NumError>) {
+    if s.is_empty() {
+        return (0, None);
+    }
+
+    let (negative, n, err) = parse_integer(s);
+    let limit = i64::MAX as u128 + negative as u128;
+    if n > limit {
+        let n = if negative { i64::MIN } else { i64::MAX };
+        return (n, err.or(Some(NumError::OutOfRange)));
+    }
+    match negative {
+        true => ((n as i128).wrapping_neg() as i64, err),
+        false => (n as i64, err),
+    }
+}
+
+/* a negative number is converted to the unsigned one like strtoumax(3) */
+pub fn parse_uint(s: &str) -> (u64, Option<NumError>) {
+    if s.is_empty() {
+        return (0, None);
+    }
+
+    let (negative, n, err) = parse_integer(s);
+    if n > u64::MAX as u128 {
+        return (u64::MAX, err.or(Some(NumError::OutOfRange)));
+    }
+    match negative {
+        true => ((n as u64).wrapping_neg(), err),
+        false => (n as u64, err),
+    }
+}
+
+fn digit_len(s: &str, radix: u32) -> usize {
+    s.chars().take_while(|c| c.is_digit(radix)).count()
+}
+
+/* the value and the length of a hexadecimal floating point number (e.g. 1.8p3) */
+fn parse_hex_float(s: &str) -> (f64, usize) {
+    let int_part = &s[..digit_len(s, 16)];
+    let mut pos = int_part.len();
+    let mut frac_part = "";
+    if s[pos..].starts_with('.') {
+        frac_part = &s[pos + 1..pos + 1 + digit_len(&s[pos + 1..], 16)];
+        pos += 1 + frac_part.len();
+    }
+    if int_part.is_empty() && frac_part.is_empty() {
+        return (0.0, 0);
+    }
+
+    let mut exp = -4 * frac_part.len() as i32;
+    if s[pos..].starts_with(['p', 'P']) {
+        let (negative, e) = split_sign(&s[pos + 1..]);
+        let len = digit_len(e, 10);
+        if len > 0 {
+            let n = e[..len].parse::<i32>().unwrap_or(i32::MAX / 2);
+            exp += if negative { -n } else { n };
+            pos = s.len() - e.len() + len;
+        }
+    }
+
+    let m = int_part
+        .chars()
+        .chain(frac_part.chars())
+        .fold(0.0, |m, c| m * 16.0 + c.to_digit(16).unwrap() as f64);
+    (m * 2f64.powi(exp), pos)
+}
+
+/* the value and the length of a decimal floating point number */
+fn parse_decimal_float(s: &str) -> (f64, usize) {
+    let int_len = digit_len(s, 10);
+    let mut pos = int_len;
+    let mut frac_len = 0;
+    if s[pos..].starts_with('.') {
+        frac_len = digit_len(&s[pos + 1..], 10);
+        pos += 1 + frac_len;
+    }
+    if int_len + frac_len == 0 {
+        return (0.0, 0);
+    }
+
+    if s[pos..].starts_with(['e', 'E']) {
+        let (_, e) = split_sign(&s[pos + 1..]);
+        let len = digit_len(e, 10);
+        if len > 0 {
+            pos = s.len() - e.len() + len;
+        }
+    }
+
+    let text = s[..pos].replace(".e", ".0e").replace(".E", ".0E");
+    (text.parse::<f64>().unwrap_or(0.0), pos)
+}
+
+/* like strtold(3) */
+pub fn parse_float(s: &str) -> (f64, Option<NumError>) {
+    if s.is_empty() {
+        return (0.0, None);
+    }
+    let s = s.trim_start_matches([' ', '\t', '\n', '\r', '\x0b', '\x0c']);
+    if let Some(code) = char_code(s) {
+        return (code as f64, None);
+    }
+
+    let (negative, body) = split_sign(s);
+    let lower = body.to_ascii_lowercase();
+    let (value, len) = if lower.starts_with("infinity") {
+        (f64::INFINITY, 8)
+    } else if lower.starts_with("inf") {
+        (f64::INFINITY, 3)
+    } else if lower.starts_with("nan") {
+        (f64::NAN, 3)
+    } else if lower.starts_with("0x") {
+        match parse_hex_float(&body[2..]) {
+            (_, 0) => (0.0, 1),
+            (v, len) => (v, len + 2),
+        }
+    } else {
+        parse_decimal_float(body)
+    };
+
+    let value = if negative { -value } else { value };
+    match len {
+        0 => (0.0, Some(NumError::Invalid)),
+        n if n < body.len() => (value, Some(NumError::Invalid)),
+        _ => (value, None),
+    }
+}
+
+/* the precision gives the minimum number of digits */
+fn with_precision(digits: String, spec: &Spec) -> String {
+    match spec.precision {
+        Some(0) if digits == "0" => String::new(),
+        Some(p) if digits.len() < p => "0".repeat(p - digits.len()) + &digits,
+        _ => digits,
+    }
+}
+
+/* %d, %i */
+pub fn format_int(n: i64, spec: &Spec) -> Vec<u8> {
+    let sign = if n < 0 {
+        "-"
+    } else if spec.plus {
+        "+"
+    } else if spec.space {
+        " "
+    } else {
+        ""
+    };
+    let digits = with_precision(n.unsigned_abs().to_string(), spec);
+    spec.pad_number(sign, &digits, spec.precision.is_none())
+}
+
+/* %u, %o, %x, %X */
+pub fn format_uint(n: u64, conv: char, spec: &Spec) -> Vec<u8> {
+    let digits = match conv {
+        'o' => format!("{:o}", n),
+        'x' => format!("{:x}", n),
+        'X' => format!("{:X}", n),
+        _ => n.to_string(),
+    };
+    let mut digits = with_precision(digits, spec);
+
+    let prefix = match (spec.alt, conv) {
+        (true, 'o') if !digits.starts_with('0') => {
+            digits.insert(0, '0');
+            ""
+        }
+        (true, 'x') if n != 0 => "0x",
+        (true, 'X') if n != 0 => "0X",
+        _ => "",
+    };
+    spec.pad_number(prefix, &digits, spec.precision.is_none())
+}
+
+/* %e: the exponent has a sign and two digits at least */
+fn exponential(a: f64, precision: usize, alt: bool) -> String {
+    let s = format!("{:.*e}", precision, a);
+    let (mantissa, exp) = s.split_once('e').unwrap();
+    let exp = exp.parse::<i32>().unwrap_or(0);
+    let dot = if alt && precision == 0 { "." } else { "" };
+    let sign = if exp < 0 { '-' } else { '+' };
+    format!("{}{}e{}{:02}", mantissa, dot, sign, exp.abs())
+}
+
+/* %g: %e or %f. Trailing zeros are removed without # */
+fn general(a: f64, precision: Option<usize>, alt: bool) -> String {
+    let p = match precision {
+        Some(0) => 1,
+        Some(p) => p,
+        None => 6,
+    };
+    let s = format!("{:.*e}", p - 1, a);
+    let exp = s.split_once('e').unwrap().1.parse::<i32>().unwrap_or(0);
+
+    let s = match exp < p as i32 && exp >= -4 {
+        true => format!("{:.*}", (p as i32 - 1 - exp) as usize, a),
+        false => exponential(a, p - 1, false),
+    };
+    let (mantissa, exp) = match s.find('e') {
+        Some(pos) => s.split_at(pos),
+        None => (s.as_str(), ""),
+    };
+
+    let mantissa = if alt {
+        match mantissa.contains('.') {
+            true => mantissa.to_string(),
+            false => mantissa.to_string() + ".",
+        }
+    } else if mantissa.contains('.') {
+        mantissa
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    } else {
+        mantissa.to_string()
+    };
+    mantissa + exp
+}
+
+/* %a: the leading hex digit is 8-f as the long double of x86 */
+fn hexadecimal(a: f64, precision: Option<usize>, alt: bool) -> String {
+    let dot = |digits: &str| match digits.is_empty() && !alt {
+        true => "",
+        false => ".",
+    };
+    if a == 0.0 {
+        let digits = "0".repeat(precision.unwrap_or(0));
+        return format!("0{}{}p+0", dot(&digits), digits);
+    }
+
+    let bits = a.to_bits();
+    let mut exp = ((bits >> 52) & 0x7ff) as i64;
+    let mut mantissa = bits & ((1 << 52) - 1);
+    if exp == 0 {
+        exp = 1;
+        while mantissa & (1 << 52) == 0 {
+            mantissa <<= 1;
+            exp -= 1;
+        }
+    } else {
+        mantissa |= 1 << 52;
+    }
+    let mut exp = exp - 1023 - 3;
+    let mantissa = mantissa << 11;
+    let mut lead = mantissa >> 60;
+    let frac = mantissa & ((1 << 60) - 1);
+
+    let digits = match precision {
+        None => format!("{:015x}", frac).trim_end_matches('0').to_string(),
+        Some(p) if p >= 15 => format!("{:015x}", frac) + &"0".repeat(p - 15),
+        Some(p) => {
+            let drop = (15 - p) * 4;
+            let mut kept = frac >> drop;
+            let rest = frac & ((1 << drop) - 1);
+            let half = 1 << (drop - 1);
+            if rest > half || (rest == half && kept & 1 == 1) {
+                kept += 1;
+            }
+            if kept >> (p * 4) != 0 {
+                kept = 0;
+                lead += 1;
+            }
+            if lead == 16 {
+                lead = 1;
+                exp += 4;
+            }
+            match p {
+                0 => String::new(),
+                _ => format!("{:0width$x}", kept, width = p),
+            }
+        }
+    };
+    format!("{:x}{}{}p{:+}", lead, dot(&digits), digits, exp)
+}
+
+/* %f, %F, %e, %E, %g, %G, %a, %A */
+pub fn format_float(f: f64, conv: char, spec: &Spec) -> Vec<u8> {
+    let upper = conv.is_ascii_uppercase();
+    let sign = if f.is_sign_negative() && !f.is_nan() {
+        "-"
+    } else if spec.plus {
+        "+"
+    } else if spec.space {
+        " "
+    } else {
+        ""
+    };
+    let a = f.abs();
+
+    if !a.is_finite() {
+        let body = if a.is_nan() { "nan" } else { "inf" };
+        let body = if upper {
+            body.to_uppercase()
+        } else {
+            body.to_string()
+        };
+        return spec.pad_number(sign, &body, false);
+    }
+
+    let precision = spec.precision.unwrap_or(6);
+    let (prefix, body) = match conv.to_ascii_lowercase() {
+        'e' => (sign.to_string(), exponential(a, precision, spec.alt)),
+        'g' => (sign.to_string(), general(a, spec.precision, spec.alt)),
+        'a' => (
+            sign.to_string() + if upper { "0X" } else { "0x" },
+            hexadecimal(a, spec.precision, spec.alt),
+        ),
+        _ => {
+            let dot = if spec.alt && precision == 0 { "." } else { "" };
+            (sign.to_string(), format!("{:.*}{}", precision, a, dot))
+        }
+    };
+
+    let body = if upper { body.to_uppercase() } else { body };
+    spec.pad_number(&prefix, &body, true)
+}
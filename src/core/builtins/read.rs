@@ -1,247 +1,418 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
 //SPDX-License-Identifier: BSD-3-Clause
 
-use super::error_exit;
+use super::{error_exit, usage_error};
 use crate::elements::substitution::variable::Variable;
-use crate::{arg, error, utils, ShellCore};
+use crate::feeder::terminal;
+use crate::utils::arg;
+use crate::ShellCore;
+use nix::errno::Errno;
+use nix::poll::{PollFd, PollFlags, PollTimeout};
+use nix::sys::stat::{self, SFlag};
+use nix::sys::termios;
+use nix::sys::termios::{LocalFlags, SetArg, SpecialCharacterIndices, Termios};
+use nix::{fcntl, poll, unistd};
+use std::os::fd::{BorrowedFd, RawFd};
+use std::time::{Duration, Instant};
+
+const TIMEOUT_STATUS: i32 = 142; // 128 + SIGALRM
+
+#[derive(Default)]
+struct ReadOptions {
+    names: Vec<String>,
+    array: Option<String>,
+    raw: bool,
+    silent: bool,
+    edit: bool,
+    text: String,
+    delim: char,
+    limit: Option<usize>,
+    exact: bool, // -N: the delimiter is ignored and the input is not split
+    prompt: String,
+    timeout: Option<f64>,
+    fd: RawFd,
+}
 
-fn check_word_limit(word: &mut String, limit: &mut usize) -> bool {
-    let mut pos = 0;
-    for c in word.chars() {
-        if *limit == 0 {
-            let _ = word.split_off(pos);
-            return true;
-        }
-        *limit -= 1;
+/* a char of the input and whether it is escaped with a backslash */
+type InputChar = (char, bool);
 
-        pos += c.len_utf8();
-    }
-    false
+struct Input {
+    fd: RawFd,
+    deadline: Option<Instant>,
+    line: Option<Vec<char>>, // given by the line editor with -e
 }
 
-pub fn read_(
-    core: &mut ShellCore,
-    args: &mut Vec<String>,
-    ignore_escape: bool,
-    limit: &mut usize,
-    delim: &String,
-) -> i32 {
-    let mut remaining = utils::read_line_stdin_unbuffered(delim).unwrap_or("".to_string());
-    if remaining.is_empty() {
-        return 1;
+impl Input {
+    fn wait(&self) -> Result<(), i32> {
+        let deadline = match self.deadline {
+            Some(d) => d,
+            None => return Ok(()),
+        };
+
+        let rest = deadline.saturating_duration_since(Instant::now());
+        let timeout = PollTimeout::try_from(rest).unwrap_or(PollTimeout::MAX);
+        let fd = unsafe { BorrowedFd::borrow_raw(self.fd) };
+        loop {
+            match poll::poll(&mut [PollFd::new(fd, PollFlags::POLLIN)], timeout) {
+                Ok(0) => return Err(TIMEOUT_STATUS),
+                Err(Errno::EINTR) => continue,
+                _ => return Ok(()),
+            }
+        }
     }
 
-    let ifs = match core.db.exist("IFS") {
-        true => core.db.get_param("IFS").unwrap(),
-        false => " \t\n".to_string(),
-    };
+    fn read_byte(&mut self) -> Result<Option<u8>, i32> {
+        let mut ch = [0; 1];
+        loop {
+            self.wait()?;
+            match unistd::read(self.fd, &mut ch) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(ch[0])),
+                Err(Errno::EINTR) => continue,
+                Err(_) => return Ok(None),
+            }
+        }
+    }
 
-    let mut tail_space = ifs
-        .chars()
-        .filter(|i| " \t\n".contains(*i))
-        .collect::<String>();
-    tail_space += delim;
+    /* Ok(None) at EOF and Err(status) at the timeout */
+    fn next_char(&mut self) -> Result<Option<char>, i32> {
+        if let Some(line) = self.line.as_mut() {
+            return Ok(match line.is_empty() {
+                true => None,
+                false => Some(line.remove(0)),
+            });
+        }
 
-    args.remove(0);
-    if args.is_empty() {
-        args.push("REPLY".to_string());
+        let first = match self.read_byte()? {
+            Some(b) => b,
+            None => return Ok(None),
+        };
+        let len = match first {
+            0xf0..=0xf7 => 4,
+            0xe0..=0xef => 3,
+            0xc0..=0xdf => 2,
+            _ => 1,
+        };
+
+        let mut bytes = vec![first];
+        while bytes.len() < len {
+            match self.read_byte()? {
+                Some(b) => bytes.push(b),
+                None => break,
+            }
+        }
+        let s = String::from_utf8_lossy(&bytes);
+        Ok(s.chars().next())
     }
+}
 
-    consume_ifs(&mut remaining, " \t", limit);
+const USAGE: &str = "read [-ers] [-a array] [-d delim] [-i text] [-n nchars] [-N nchars] [-p prompt] [-t timeout] [-u fd] [name ...]";
 
-    while !args.is_empty() && !remaining.is_empty() && *limit != 0 {
-        let mut word = match eat_word(core, &mut remaining, &ifs, ignore_escape, delim) {
-            Some(w) => w,
-            None => break,
-        };
+fn parse_options(core: &mut ShellCore, args: &[String]) -> Result<ReadOptions, i32> {
+    let mut args = arg::dissolve_options_with_values(args, "adinNptu");
+    args.remove(0);
 
-        check_word_limit(&mut word, limit);
+    let mut opts = ReadOptions {
+        delim: '\n',
+        ..Default::default()
+    };
 
-        if args.len() == 1 && *limit != 0 {
-            let bkup = remaining.clone();
-            consume_ifs(&mut remaining, &ifs, limit);
+    while !args.is_empty() && args[0].starts_with('-') && args[0] != "-" {
+        let opt = args.remove(0);
+        if opt == "--" {
+            break;
+        }
+        if "-a -d -i -n -N -p -t -u".split(' ').any(|o| o == opt) && args.is_empty() {
+            let msg = format!("{}: option requires an argument", &opt);
+            return Err(usage_error(2, "read", &msg, USAGE, core));
+        }
 
-            if remaining.is_empty() || remaining == "\n" {
-            } else {
-                word += &bkup;
+        match opt.as_str() {
+            "-r" => opts.raw = true,
+            "-s" => opts.silent = true,
+            "-e" => opts.edit = true,
+            "-a" => opts.array = Some(args.remove(0)),
+            "-d" => opts.delim = args.remove(0).chars().next().unwrap_or('\0'),
+            "-i" => opts.text = args.remove(0),
+            "-p" => opts.prompt = args.remove(0),
+            "-n" | "-N" => {
+                let n = args.remove(0);
+                opts.limit = match n.parse::<usize>() {
+                    Ok(n) => Some(n),
+                    Err(_) => {
+                        let msg = format!("{}: invalid number", &n);
+                        return Err(error_exit(1, "read", &msg, core));
+                    }
+                };
+                opts.exact = opt == "-N";
+            }
+            "-t" => {
+                let t = args.remove(0);
+                match t.parse::<f64>() {
+                    Ok(f) if f >= 0.0 && f.is_finite() => opts.timeout = Some(f),
+                    _ => {
+                        let msg = format!("{}: invalid timeout specification", &t);
+                        return Err(error_exit(1, "read", &msg, core));
+                    }
+                }
+            }
+            "-u" => {
+                let fd = args.remove(0);
+                opts.fd = match fd.parse::<RawFd>() {
+                    Ok(n) if n >= 0 => n,
+                    _ => {
+                        let msg = format!("{}: invalid file descriptor specification", &fd);
+                        return Err(error_exit(1, "read", &msg, core));
+                    }
+                };
+            }
+            _ => {
+                let msg = format!("{}: invalid option", &opt);
+                return Err(usage_error(2, "read", &msg, USAGE, core));
             }
         }
+    }
 
-        consume_tail_ifs(&mut word, &tail_space);
-
-        if let Err(e) = Variable::parse_and_set(&args[0], &word, core) {
-            return super::error_exit(1, "read", &String::from(&e), core);
+    if opts.timeout.is_none() {
+        if let Ok(t) = core
+            .db
+            .get_param("TMOUT")
+            .unwrap_or_default()
+            .parse::<f64>()
+        {
+            if t > 0.0 && t.is_finite() {
+                opts.timeout = Some(t);
+            }
         }
+    }
 
-        args.remove(0);
-        consume_ifs(&mut remaining, &ifs, limit);
+    if fcntl::fcntl(opts.fd, fcntl::F_GETFD).is_err() {
+        let msg = format!("{}: invalid file descriptor: Bad file descriptor", opts.fd);
+        return Err(error_exit(1, "read", &msg, core));
     }
 
-    0
+    opts.names = args;
+    Ok(opts)
 }
 
-pub fn read_a(
-    core: &mut ShellCore,
-    name: &str,
-    ignore_escape: bool,
-    limit: &mut usize,
-    delim: &String,
-) -> i32 {
-    let mut remaining = utils::read_line_stdin_unbuffered(delim).unwrap_or("".to_string());
-    if remaining.is_empty() {
-        return 1;
+/* -s and -n (-N) on a terminal: the echo and the line buffering are stopped */
+fn set_terminal(opts: &ReadOptions) -> Option<Termios> {
+    if (!opts.silent && opts.limit.is_none()) || opts.edit || unistd::isatty(opts.fd) != Ok(true) {
+        return None;
     }
 
-    let ifs = match core.db.exist("IFS") {
-        true => core.db.get_param("IFS").unwrap(),
-        false => " \t\n".to_string(),
-    };
+    let fd = unsafe { BorrowedFd::borrow_raw(opts.fd) };
+    let bkup = termios::tcgetattr(fd).ok()?;
+    let mut t = bkup.clone();
+    if opts.silent {
+        t.local_flags.remove(LocalFlags::ECHO);
+    }
+    if opts.limit.is_some() {
+        t.local_flags.remove(LocalFlags::ICANON);
+        t.control_chars[SpecialCharacterIndices::VMIN as usize] = 1;
+        t.control_chars[SpecialCharacterIndices::VTIME as usize] = 0;
+    }
+    termios::tcsetattr(fd, SetArg::TCSANOW, &t).ok()?;
+    Some(bkup)
+}
 
-    let mut tail_space = ifs
-        .chars()
-        .filter(|i| " \t\n".contains(*i))
-        .collect::<String>();
-    tail_space += delim;
+fn reset_terminal(opts: &ReadOptions, bkup: Option<Termios>) {
+    if let Some(t) = bkup {
+        let fd = unsafe { BorrowedFd::borrow_raw(opts.fd) };
+        let _ = termios::tcsetattr(fd, SetArg::TCSANOW, &t);
+    }
+}
 
-    consume_ifs(&mut remaining, " \t", limit);
+/* reads chars until the delimiter, the limit, EOF or the timeout. Backslashes are removed here. */
+fn read_chars(input: &mut Input, opts: &ReadOptions) -> (Vec<InputChar>, i32) {
+    let mut ans = vec![];
+    loop {
+        if opts.limit.is_some_and(|n| ans.len() >= n) {
+            return (ans, 0);
+        }
 
-    let mut pos = 0;
-    while !remaining.is_empty() {
-        let mut word = match eat_word(core, &mut remaining, &ifs, ignore_escape, delim) {
-            Some(w) => w,
-            None => break,
+        let c = match input.next_char() {
+            Ok(Some(c)) => c,
+            Ok(None) => return (ans, 1),
+            Err(status) => return (ans, status),
         };
-        check_word_limit(&mut word, limit);
-        consume_tail_ifs(&mut word, &tail_space);
 
-        if let Err(e) = core.db.set_array_elem(name, &word, pos, None) {
-            let msg = format!("{:?}", &e);
-            error::print(&msg, core);
-            return 1;
+        if c == opts.delim && !opts.exact {
+            return (ans, 0);
+        }
+        if c == '\0' {
+            continue;
         }
-        pos += 1;
-        consume_ifs(&mut remaining, &ifs, limit);
-    }
-
-    0
-}
 
-pub fn read(core: &mut ShellCore, args: &[String]) -> i32 {
-    if args.is_empty() {
-        return 0;
+        if c == '\\' && !opts.raw {
+            match input.next_char() {
+                Ok(Some('\n')) => {}
+                Ok(Some(c)) => ans.push((c, true)),
+                Ok(None) => return (ans, 1),
+                Err(status) => return (ans, status),
+            }
+            continue;
+        }
+        ans.push((c, false));
     }
+}
 
-    let mut args = arg::dissolve_options(args);
-    let r_opt = arg::consume_arg("-r", &mut args);
-    let mut limit = usize::MAX;
-    let limit_str = arg::consume_with_next_arg("-n", &mut args);
-    let delim = match arg::consume_with_next_arg("-d", &mut args) {
-        Some(c) => c,
-        None => "\n".to_string(),
+fn read_input(core: &mut ShellCore, opts: &ReadOptions) -> (Vec<InputChar>, i32) {
+    let mut input = Input {
+        fd: opts.fd,
+        deadline: opts
+            .timeout
+            .map(|t| Instant::now() + Duration::from_secs_f64(t)),
+        line: None,
     };
 
-    if limit_str.is_some() {
-        let s = limit_str.unwrap();
-        match s.parse::<usize>() {
-            Ok(n) => limit = n,
-            Err(_) => {
-                let err = format!("{}: invalid number", &s);
-                return error_exit(1, "read", &err, core);
-            }
-        };
+    if opts.edit && opts.fd == 0 && unistd::isatty(0) == Ok(true) {
+        match terminal::read_line_plain(core, &opts.prompt, &opts.text) {
+            Ok(line) => input.line = Some(line.chars().collect()),
+            Err(_) => return (vec![], 1),
+        }
+    } else if !opts.prompt.is_empty() && unistd::isatty(opts.fd) == Ok(true) {
+        eprint!("{}", &opts.prompt);
     }
 
-    if let Some(a) = arg::consume_with_next_arg("-a", &mut args) {
-        return read_a(core, &a, r_opt, &mut limit, &delim);
+    let bkup = set_terminal(opts);
+    let ans = read_chars(&mut input, opts);
+    reset_terminal(opts, bkup);
+    ans
+}
+
+/* -t 0: checks whether the input is available. A regular file is always
+ * ready and a pipe is ready if it has data or its writer is closed. */
+fn poll_input(fd: RawFd) -> i32 {
+    if let Ok(st) = stat::fstat(fd) {
+        if SFlag::from_bits_truncate(st.st_mode) & SFlag::S_IFMT == SFlag::S_IFREG {
+            return 0;
+        }
     }
 
-    read_(core, &mut args, r_opt, &mut limit, &delim)
+    let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+    let mut fds = [PollFd::new(fd, PollFlags::POLLIN)];
+    match poll::poll(&mut fds, PollTimeout::ZERO) {
+        Ok(n) if n > 0 => 0,
+        _ => 1,
+    }
 }
 
-pub fn eat_word(
-    _core: &mut ShellCore,
-    remaining: &mut String,
-    ifs: &str,
-    ignore_escape: bool,
-    delim: &String,
-) -> Option<String> {
-    let mut esc = false;
-    let mut pos = 0;
-    let mut escape_pos = vec![];
+fn is_ifs(c: &InputChar, ifs: &str) -> bool {
+    !c.1 && ifs.contains(c.0)
+}
 
-    for c in remaining.chars() {
-        if (esc || c == '\\') && !ignore_escape {
-            esc = !esc;
-            if esc {
-                escape_pos.push(pos);
-            }
-            pos += c.len_utf8();
-            continue;
-        }
+fn is_ifs_space(c: &InputChar, ifs: &str) -> bool {
+    is_ifs(c, ifs) && " \t\n".contains(c.0)
+}
 
-        if ifs.contains(c) {
-            break;
-        }
-        pos += c.len_utf8();
+fn skip_ifs_space(chars: &[InputChar], pos: &mut usize, ifs: &str) {
+    while *pos < chars.len() && is_ifs_space(&chars[*pos], ifs) {
+        *pos += 1;
     }
+}
 
-    if let Some(p) = escape_pos.last() {
-        if p + 2 == remaining.len() && remaining.ends_with('\n') {
-            remaining.pop();
-            remaining.pop();
+fn to_string(chars: &[InputChar]) -> String {
+    chars.iter().map(|c| c.0).collect()
+}
 
-            let line = utils::read_line_stdin_unbuffered(delim).unwrap_or("".to_string());
-            if !line.is_empty() {
-                *remaining += &line;
-                return eat_word(_core, remaining, ifs, ignore_escape, delim);
-            }
-        }
+/* a field and the following delimiter, which is IFS spaces with at most one other IFS char */
+fn eat_word(chars: &[InputChar], pos: &mut usize, ifs: &str) -> String {
+    let start = *pos;
+    while *pos < chars.len() && !is_ifs(&chars[*pos], ifs) {
+        *pos += 1;
     }
+    let ans = to_string(&chars[start..*pos]);
 
-    let tail = remaining.split_off(pos);
-    let mut ans = remaining.clone();
-    *remaining = tail;
+    skip_ifs_space(chars, pos, ifs);
+    if *pos < chars.len() && is_ifs(&chars[*pos], ifs) {
+        *pos += 1;
+        skip_ifs_space(chars, pos, ifs);
+    }
+    ans
+}
 
-    for p in escape_pos {
-        ans.remove(p);
+/* the last variable gets the rest of the line without the trailing IFS spaces */
+fn rest_of_line(chars: &[InputChar], pos: usize, ifs: &str) -> String {
+    let mut word_end = pos;
+    let word = eat_word(chars, &mut word_end, ifs);
+    if word_end == chars.len() {
+        return word;
     }
 
-    Some(ans)
+    let mut end = chars.len();
+    while end > pos && is_ifs_space(&chars[end - 1], ifs) {
+        end -= 1;
+    }
+    to_string(&chars[pos..end])
 }
 
-pub fn consume_tail_ifs(remaining: &mut String, ifs: &str) {
-    loop {
-        if let Some(c) = remaining.chars().last() {
-            if ifs.contains(c) {
-                remaining.pop();
-                continue;
-            }
+fn split(chars: &[InputChar], num: usize, ifs: &str) -> Vec<String> {
+    let mut ans = vec![];
+    let mut pos = 0;
+    skip_ifs_space(chars, &mut pos, ifs);
+
+    while pos < chars.len() {
+        if ans.len() + 1 == num {
+            ans.push(rest_of_line(chars, pos, ifs));
+            break;
         }
-        break;
+        ans.push(eat_word(chars, &mut pos, ifs));
     }
+    ans
 }
 
-pub fn consume_ifs(remaining: &mut String, ifs: &str, limit: &mut usize) {
-    let special_ifs: Vec<char> = ifs.chars().filter(|s| !" \t\n".contains(*s)).collect();
-    let mut pos = 0;
-    let mut special_ifs_exist = false;
+fn set_variables(core: &mut ShellCore, opts: &ReadOptions, chars: &[InputChar]) -> i32 {
+    let ifs = match core.db.exist("IFS") {
+        true => core.db.get_param("IFS").unwrap_or_default(),
+        false => " \t\n".to_string(),
+    };
 
-    for ch in remaining.chars() {
-        if !ifs.contains(ch) || *limit == 0 {
-            break;
+    if let Some(name) = &opts.array {
+        let values = match opts.exact {
+            true => vec![to_string(chars)],
+            false => split(chars, usize::MAX, &ifs),
+        };
+        if let Err(e) = core.db.set_array(name, Some(values), None) {
+            return error_exit(1, "read", &String::from(&e), core);
         }
+        return 0;
+    }
 
-        if special_ifs.contains(&ch) {
-            if special_ifs_exist {
-                break;
-            }
+    let (names, values) = if opts.names.is_empty() {
+        (vec!["REPLY".to_string()], vec![to_string(chars)])
+    } else if opts.exact {
+        (opts.names.clone(), vec![to_string(chars)])
+    } else {
+        (opts.names.clone(), split(chars, opts.names.len(), &ifs))
+    };
 
-            special_ifs_exist = true;
+    for (i, name) in names.iter().enumerate() {
+        let value = values.get(i).map(|v| v.as_str()).unwrap_or("");
+        if let Err(e) = Variable::parse_and_set(name, value, core) {
+            return error_exit(1, "read", &String::from(&e), core);
         }
-        pos += ch.len_utf8();
-        *limit -= 1;
     }
+    0
+}
 
-    let tail = remaining.split_off(pos);
-    *remaining = tail;
+pub fn read(core: &mut ShellCore, args: &[String]) -> i32 {
+    if args.is_empty() {
+        return 0;
+    }
+
+    let opts = match parse_options(core, args) {
+        Ok(o) => o,
+        Err(status) => return status,
+    };
+
+    if opts.timeout == Some(0.0) {
+        return poll_input(opts.fd);
+    }
+
+    let (chars, status) = read_input(core, &opts);
+    match set_variables(core, &opts, &chars) {
+        0 => status,
+        e => e,
+    }
 }
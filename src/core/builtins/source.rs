@@ -2,7 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::error::parse::ParseError;
-use crate::{file_check, Feeder, Script, ShellCore};
+use crate::{file_check, signal, Feeder, Script, ShellCore};
 
 fn check_error(core: &mut ShellCore, args: &[String]) -> i32 {
     if core.db.flags.contains('r') && args[1].contains('/') {
@@ -36,19 +36,10 @@ pub fn source(core: &mut ShellCore, args: &[String]) -> i32 {
         return 1;
     }
 
-    let mut source = match core.db.get_vec("BASH_SOURCE", false) {
-        Ok(s) => s,
-        Err(e) => {
-            e.print(core);
-            return 1;
-        }
-    };
-
     core.source_function_level += 1;
     core.source_files.push(args[1].to_string());
     core.db.position_parameters.push(args[1..].to_vec());
-    source.insert(0, args[1].clone());
-    let _ = core.db.set_array("BASH_SOURCE", Some(source.clone()), None);
+    core.push_call_frame("source", &args[1], false);
 
     feeder.main_feeder = true;
     while let Ok(()) = feeder.feed_line(core) {
@@ -65,8 +56,10 @@ pub fn source(core: &mut ShellCore, args: &[String]) -> i32 {
         }
     }
 
-    source.remove(0);
-    let _ = core.db.set_array("BASH_SOURCE", Some(source), None);
+    core.return_flag = false;
+    signal::run_return_trap(core);
+
+    core.pop_call_frame();
     core.db.position_parameters.pop();
     core.source_function_level -= 1;
     core.source_files.pop();
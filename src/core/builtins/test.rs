@@ -0,0 +1,231 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use super::error_exit;
+use crate::elements::expr::conditional::ConditionalExpr;
+use crate::utils::file_check;
+use crate::ShellCore;
+
+const UNARY_OPS: &str = "abcdefghknoprstuvwxzGLNOS";
+const BINARY_OPS: [&str; 14] = [
+    "=", "==", "!=", "<", ">", "-eq", "-ne", "-lt", "-le", "-gt", "-ge", "-ef", "-nt", "-ot",
+];
+
+fn is_unary_op(s: &str) -> bool {
+    s.len() == 2 && s.starts_with('-') && UNARY_OPS.contains(&s[1..])
+}
+
+fn is_binary_op(s: &str) -> bool {
+    BINARY_OPS.contains(&s)
+}
+
+fn to_int(s: &str) -> Result<i64, String> {
+    let t = s.trim_matches([' ', '\t', '\n']);
+    let digits = t.strip_prefix(['+', '-']).unwrap_or(t);
+    match !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+        true => t
+            .parse::<i64>()
+            .map_err(|_| format!("{}: integer expression expected", s)),
+        false => Err(format!("{}: integer expression expected", s)),
+    }
+}
+
+struct Test<'a> {
+    args: &'a [String],
+    pos: usize,
+}
+
+impl Test<'_> {
+    fn arg(&self, n: usize) -> &str {
+        &self.args[self.pos + n]
+    }
+
+    fn advance(&mut self, need_more: bool) -> Result<(), String> {
+        self.pos += 1;
+        match need_more && self.pos >= self.args.len() {
+            true => Err("argument expected".to_string()),
+            false => Ok(()),
+        }
+    }
+
+    fn unary(&mut self, core: &mut ShellCore) -> Result<bool, String> {
+        let op = self.arg(0).to_string();
+        self.advance(true)?;
+        let operand = self.arg(0).to_string();
+        self.advance(false)?;
+        ConditionalExpr::unary_test(&op, &operand, core).map_err(|e| String::from(&e))
+    }
+
+    fn binary(&mut self) -> Result<bool, String> {
+        let (left, op, right) = (self.arg(0), self.arg(1), self.arg(2));
+        let ans = match op {
+            "=" | "==" => left == right,
+            "!=" => left != right,
+            "<" => left < right,
+            ">" => left > right,
+            "-ef" | "-nt" | "-ot" => file_check::metadata_comp(left, right, op),
+            _ => {
+                let (l, r) = (to_int(left)?, to_int(right)?);
+                match op {
+                    "-eq" => l == r,
+                    "-ne" => l != r,
+                    "-lt" => l < r,
+                    "-le" => l <= r,
+                    "-gt" => l > r,
+                    _ => l >= r,
+                }
+            }
+        };
+        self.pos += 3;
+        Ok(ans)
+    }
+
+    fn expr(&mut self, core: &mut ShellCore) -> Result<bool, String> {
+        if self.pos >= self.args.len() {
+            return Err("argument expected".to_string());
+        }
+        self.or(core)
+    }
+
+    fn or(&mut self, core: &mut ShellCore) -> Result<bool, String> {
+        let left = self.and(core)?;
+        if self.pos < self.args.len() && self.arg(0) == "-o" {
+            self.advance(false)?;
+            let right = self.or(core)?;
+            return Ok(left || right);
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self, core: &mut ShellCore) -> Result<bool, String> {
+        let left = self.term(core)?;
+        if self.pos < self.args.len() && self.arg(0) == "-a" {
+            self.advance(false)?;
+            let right = self.and(core)?;
+            return Ok(left && right);
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self, core: &mut ShellCore) -> Result<bool, String> {
+        if self.pos >= self.args.len() {
+            return Err("argument expected".to_string());
+        }
+
+        if self.arg(0) == "!" {
+            let mut negate = false;
+            while self.pos < self.args.len() && self.arg(0) == "!" {
+                self.advance(true)?;
+                negate = !negate;
+            }
+            return Ok(self.term(core)? != negate);
+        }
+
+        if self.arg(0) == "(" {
+            self.advance(true)?;
+            let ans = self.expr(core)?;
+            if self.pos >= self.args.len() {
+                return Err("`)' expected".to_string());
+            }
+            if self.arg(0) != ")" {
+                return Err(format!("`)' expected, found {}", self.arg(0)));
+            }
+            self.advance(false)?;
+            return Ok(ans);
+        }
+
+        let rest = self.args.len() - self.pos;
+        if rest >= 3 && is_binary_op(self.arg(1)) {
+            self.binary()
+        } else if rest >= 2 && is_unary_op(self.arg(0)) {
+            self.unary(core)
+        } else {
+            let ans = !self.arg(0).is_empty();
+            self.advance(false)?;
+            Ok(ans)
+        }
+    }
+
+    fn two_args(&mut self, core: &mut ShellCore) -> Result<bool, String> {
+        if self.arg(0) == "!" {
+            let ans = self.arg(1).is_empty();
+            self.pos += 2;
+            return Ok(ans);
+        }
+        if is_unary_op(self.arg(0)) {
+            return self.unary(core);
+        }
+        Err(format!("{}: unary operator expected", self.arg(0)))
+    }
+
+    fn three_args(&mut self, core: &mut ShellCore) -> Result<bool, String> {
+        if is_binary_op(self.arg(1)) {
+            return self.binary();
+        }
+
+        let ans = match (self.arg(0), self.arg(1), self.arg(2)) {
+            (l, "-a", r) => !l.is_empty() && !r.is_empty(),
+            (l, "-o", r) => !l.is_empty() || !r.is_empty(),
+            ("!", _, _) => {
+                self.pos += 1;
+                return Ok(!self.two_args(core)?);
+            }
+            ("(", s, ")") => !s.is_empty(),
+            _ => return Err(format!("{}: binary operator expected", self.arg(1))),
+        };
+        self.pos += 3;
+        Ok(ans)
+    }
+
+    /* the rules of POSIX depending on the number of arguments */
+    fn eval(&mut self, core: &mut ShellCore) -> Result<bool, String> {
+        let ans = match self.args.len() {
+            0 => false,
+            1 => {
+                self.pos = 1;
+                !self.args[0].is_empty()
+            }
+            2 => self.two_args(core)?,
+            3 => self.three_args(core)?,
+            4 if self.arg(0) == "!" => {
+                self.pos += 1;
+                !self.three_args(core)?
+            }
+            4 if self.arg(0) == "(" && self.arg(3) == ")" => {
+                self.pos += 1;
+                let ans = self.two_args(core)?;
+                self.pos += 1;
+                ans
+            }
+            _ => self.expr(core)?,
+        };
+
+        if self.pos < self.args.len() {
+            return match self.arg(0).starts_with('-') {
+                true => Err(format!("syntax error: `{}' unexpected", self.arg(0))),
+                false => Err("too many arguments".to_string()),
+            };
+        }
+        Ok(ans)
+    }
+}
+
+pub fn test(core: &mut ShellCore, args: &[String]) -> i32 {
+    let mut targets = &args[1..];
+    if args[0] == "[" {
+        match targets.last() {
+            Some(s) if s == "]" => targets = &targets[..targets.len() - 1],
+            _ => return error_exit(2, "[", "missing `]'", core),
+        }
+    }
+
+    let mut t = Test {
+        args: targets,
+        pos: 0,
+    };
+    match t.eval(core) {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(msg) => error_exit(2, &args[0], &msg, core),
+    }
+}
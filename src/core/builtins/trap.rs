@@ -4,6 +4,7 @@
 
 use crate::error::exec::ExecError;
 use crate::signal;
+use crate::utils::arg;
 use crate::ShellCore;
 use nix::sys::signal::Signal;
 use signal_hook::iterator::Signals;
@@ -23,6 +24,15 @@ pub fn trap(core: &mut ShellCore, args: &[String]) -> i32 {
                 println!("trap -- '{}' {}", &e.1, &s);
             }
         }
+        for (name, script) in [
+            ("DEBUG", &core.debug_script),
+            ("ERR", &core.err_script),
+            ("RETURN", &core.return_script),
+        ] {
+            if !script.is_empty() {
+                println!("trap -- '{}' {}", script, name);
+            }
+        }
         return 0;
     }
 
@@ -32,8 +42,22 @@ pub fn trap(core: &mut ShellCore, args: &[String]) -> i32 {
         return 2;
     }
 
+    let mut sigspecs = args[2..].to_vec();
+    for (name, script) in [
+        ("DEBUG", &mut core.debug_script),
+        ("ERR", &mut core.err_script),
+        ("RETURN", &mut core.return_script),
+    ] {
+        if arg::consume_arg(name, &mut sigspecs) {
+            *script = match args[1].as_str() {
+                "-" => String::new(),
+                s => s.to_string(),
+            };
+        }
+    }
+
     let forbiddens = Vec::from(signal_hook::consts::FORBIDDEN);
-    let signals = match args_to_nums(&args[2..], &forbiddens) {
+    let signals = match args_to_nums(&sigspecs, &forbiddens) {
         Ok(v) => v,
         Err(e) => {
             e.print(core);
@@ -0,0 +1,176 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use super::{error_exit, usage_error};
+use crate::utils::arg;
+use crate::ShellCore;
+use nix::sys::stat::{self, Mode};
+
+#[derive(Debug, Default)]
+struct UmaskOptions {
+    print: bool,
+    symbolic: bool,
+}
+
+const USAGE: &str = "umask [-p] [-S] [mode]";
+
+/* the mask is read by setting and restoring it */
+fn get_umask() -> u32 {
+    let mask = stat::umask(Mode::empty());
+    stat::umask(mask);
+    mask.bits() as u32
+}
+
+fn set_umask(mask: u32) {
+    stat::umask(Mode::from_bits_truncate((mask & 0o777) as _));
+}
+
+/* u=rwx,g=rx,o=rx for 0022 */
+fn symbolic_string(mask: u32) -> String {
+    let perm = !mask & 0o777;
+    let classes = [('u', 6), ('g', 3), ('o', 0)];
+    let to_string = |(c, shift): &(char, u32)| {
+        let bits: String = [('r', 4), ('w', 2), ('x', 1)]
+            .iter()
+            .filter(|(_, b)| perm >> shift & b != 0)
+            .map(|(p, _)| *p)
+            .collect();
+        format!("{}={}", c, bits)
+    };
+    classes.iter().map(to_string).collect::<Vec<_>>().join(",")
+}
+
+fn print_umask(options: &UmaskOptions) {
+    let mask = get_umask();
+    let text = match options.symbolic {
+        true => symbolic_string(mask),
+        false => format!("{:04o}", mask),
+    };
+
+    match (options.print, options.symbolic) {
+        (true, true) => println!("umask -S {}", text),
+        (true, false) => println!("umask {}", text),
+        _ => println!("{}", text),
+    }
+}
+
+/* the octal mask. None if a digit is not octal or the number exceeds 07777 */
+fn parse_octal(mode: &str) -> Option<u32> {
+    let mut ans = 0;
+    for c in mode.chars() {
+        ans = ans * 8 + c.to_digit(8)?;
+        if ans > 0o7777 {
+            return None;
+        }
+    }
+    Some(ans)
+}
+
+/* clauses like u=rwx,g+w,o-x are applied to the permissions (not the mask) */
+fn parse_symbolic(mode: &str, mask: u32) -> Result<u32, String> {
+    let chars: Vec<char> = mode.chars().collect();
+    let mut perm = !mask & 0o777;
+    let mut pos = 0;
+
+    loop {
+        let mut who = 0;
+        while let Some(c) = chars.get(pos) {
+            who |= match c {
+                'u' => 0o700,
+                'g' => 0o070,
+                'o' => 0o007,
+                'a' => 0o777,
+                _ => break,
+            };
+            pos += 1;
+        }
+
+        let op = chars.get(pos).copied().unwrap_or('\0');
+        if !"+-=".contains(op) {
+            return Err(format!("`{}': invalid symbolic mode operator", op));
+        }
+        pos += 1;
+
+        let mut bits = 0;
+        while let Some(c) = chars.get(pos) {
+            bits |= match c {
+                'r' => 0o444,
+                'w' => 0o222,
+                'x' => 0o111,
+                _ => break,
+            };
+            pos += 1;
+        }
+        match chars.get(pos) {
+            None | Some(',') => {}
+            Some(c) => return Err(format!("`{}': invalid symbolic mode character", c)),
+        }
+
+        if who == 0 {
+            who = 0o777;
+        }
+        match op {
+            '+' => perm |= who & bits,
+            '-' => perm &= !(who & bits),
+            _ => perm = (perm & !who) | (who & bits),
+        }
+
+        match chars.get(pos) {
+            Some(',') => pos += 1,
+            _ => break,
+        }
+    }
+    Ok(!perm & 0o777)
+}
+
+pub fn umask(core: &mut ShellCore, args: &[String]) -> i32 {
+    if args.len() > 1 && args[1] == "--help" {
+        println!("umask: {}", USAGE);
+        return 0;
+    }
+
+    let args = arg::dissolve_options_with_values(args, "");
+    let mut options = UmaskOptions::default();
+    let mut pos = 1;
+    while pos < args.len() && args[pos].starts_with('-') && args[pos] != "-" {
+        pos += 1;
+        match args[pos - 1].as_str() {
+            "--" => break,
+            "-p" => options.print = true,
+            "-S" => options.symbolic = true,
+            a => {
+                let msg = format!("{}: invalid option", a);
+                return usage_error(2, "umask", &msg, USAGE, core);
+            }
+        }
+    }
+
+    let mode = match args.get(pos) {
+        Some(m) => m,
+        None => {
+            print_umask(&options);
+            return 0;
+        }
+    };
+
+    let mask = if mode.starts_with(|c: char| c.is_ascii_digit()) {
+        match parse_octal(mode) {
+            Some(m) => m,
+            None => {
+                let msg = format!("{}: octal number out of range", mode);
+                return error_exit(1, "umask", &msg, core);
+            }
+        }
+    } else {
+        match parse_symbolic(mode, get_umask()) {
+            Ok(m) => m,
+            Err(msg) => return error_exit(1, "umask", &msg, core),
+        }
+    };
+
+    set_umask(mask);
+    if options.symbolic {
+        println!("{}", symbolic_string(mask));
+    }
+    0
+}
@@ -4,12 +4,14 @@
 use crate::ShellCore;
 
 fn unset_all(core: &mut ShellCore, name: &str) -> i32 {
-    core.db.unset(name);
+    let name = core.db.solve_name(name);
+    core.db.unset(&name);
     0
 }
 
 fn unset_var(core: &mut ShellCore, name: &str) -> i32 {
-    core.db.unset_var(name);
+    let name = core.db.solve_name(name);
+    core.db.unset_var(&name);
     0
 }
 
@@ -32,8 +34,15 @@ fn unset_one(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
                 return unset_var(core, &name);
             }
         }
+        "-n" => {
+            if args.len() > 2 {
+                let name = args.remove(2);
+                core.db.unset_var(&name);
+                return 0;
+            }
+        }
         name => {
-            let name = name.to_string();
+            let name = core.db.solve_name(name);
             args.remove(1);
             if !name.contains("[") {
                 return unset_all(core, &name);
@@ -68,7 +77,7 @@ pub fn unset(core: &mut ShellCore, args: &[String]) -> i32 {
             break;
         }
 
-        if (args[1] == "-v" || args[1] == "-f") && args.len() == 2 {
+        if (args[1] == "-v" || args[1] == "-f" || args[1] == "-n") && args.len() == 2 {
             break;
         }
 
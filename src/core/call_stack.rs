@@ -0,0 +1,56 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::ShellCore;
+
+impl ShellCore {
+    /* FUNCNAME is visible only while a function is running */
+    fn set_funcname(&mut self) {
+        let names = match self.call_stack.iter().any(|f| f.1) {
+            true => Some(self.call_stack.iter().map(|f| f.0.clone()).collect()),
+            false => None,
+        };
+        let _ = self.db.set_array("FUNCNAME", names, None);
+    }
+
+    /* the frames of the call stack are put in the front of FUNCNAME, BASH_SOURCE, and BASH_LINENO */
+    pub fn push_call_frame(&mut self, name: &str, file: &str, is_function: bool) {
+        let lineno = self.db.get_param("LINENO").unwrap_or_default();
+        for (array, value) in [("BASH_SOURCE", file.to_string()), ("BASH_LINENO", lineno)] {
+            let mut v = self.db.get_vec(array, false).unwrap_or_default();
+            v.insert(0, value);
+            let _ = self.db.set_array(array, Some(v), None);
+        }
+
+        self.call_stack.insert(0, (name.to_string(), is_function));
+        self.set_funcname();
+    }
+
+    pub fn pop_call_frame(&mut self) {
+        for array in ["BASH_SOURCE", "BASH_LINENO"] {
+            let mut v = self.db.get_vec(array, false).unwrap_or_default();
+            if !v.is_empty() {
+                v.remove(0);
+            }
+            let _ = self.db.set_array(array, Some(v), None);
+        }
+
+        if !self.call_stack.is_empty() {
+            self.call_stack.remove(0);
+        }
+        self.set_funcname();
+    }
+
+    /* the name of the file being read, which is recorded in function definitions */
+    pub fn current_source_name(&self) -> String {
+        if let Some(f) = self.source_files.last() {
+            f.clone()
+        } else if self.script_name != "-" {
+            self.script_name.clone()
+        } else if self.db.flags.contains('c') {
+            "environment".to_string()
+        } else {
+            "main".to_string()
+        }
+    }
+}
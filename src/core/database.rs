@@ -4,6 +4,7 @@
 mod data;
 mod database_checker;
 mod database_getter;
+mod database_nameref;
 mod database_setter;
 
 use self::data::array::ArrayData;
@@ -72,6 +73,9 @@ impl DataBase {
     }
 
     pub fn init(&mut self, name: &str, layer: usize) {
+        let (name, layer) = self.solve_nameref(name, Some(layer));
+        let layer = self.get_target_layer(&name, layer);
+        let name = name.as_str();
         if let Some(d) = self.params[layer].get_mut(name) {
             d.clear();
         }
@@ -98,6 +102,7 @@ impl DataBase {
     }
 
     pub fn unset_array_elem(&mut self, name: &str, key: &str) -> Result<(), ExecError> {
+        let name = &self.solve_name(name);
         if self.is_single(name) && (key == "0" || key == "@" || key == "*") {
             self.unset_var(name);
             return Ok(());
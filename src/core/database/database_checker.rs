@@ -7,6 +7,7 @@ use crate::utils;
 
 impl DataBase {
     pub fn has_array_value(&mut self, name: &str, index: &str) -> bool {
+        let name = &self.solve_name(name);
         let num = self.params.len();
         for layer in (0..num).rev() {
             if let Some(e) = self.params[layer].get(name) {
@@ -38,6 +39,7 @@ impl DataBase {
     }
 
     pub fn exist(&mut self, name: &str) -> bool {
+        let name = &self.solve_name(name);
         if let Ok(n) = name.parse::<usize>() {
             let layer = self.position_parameters.len() - 1;
             return n < self.position_parameters[layer].len();
@@ -53,6 +55,7 @@ impl DataBase {
     }
 
     pub fn has_key(&mut self, name: &str, key: &str) -> Result<bool, ExecError> {
+        let name = &self.solve_name(name);
         let num = self.params.len();
         for layer in (0..num).rev() {
             if let Some(e) = self.params[layer].get_mut(name) {
@@ -85,6 +88,7 @@ impl DataBase {
     }
 
     pub fn is_assoc(&mut self, name: &str) -> bool {
+        let name = &self.solve_name(name);
         match self.get_ref(name) {
             Some(d) => d.is_assoc(),
             None => false,
@@ -92,6 +96,7 @@ impl DataBase {
     }
 
     pub fn is_single(&mut self, name: &str) -> bool {
+        let name = &self.solve_name(name);
         match self.get_ref(name) {
             Some(d) => d.is_single(),
             _ => false,
@@ -99,6 +104,7 @@ impl DataBase {
     }
 
     pub fn is_single_num(&mut self, name: &str) -> bool {
+        let name = &self.solve_name(name);
         match self.get_ref(name) {
             Some(d) => d.is_single_num(),
             _ => false,
@@ -106,6 +112,7 @@ impl DataBase {
     }
 
     pub fn is_array(&mut self, name: &str) -> bool {
+        let name = &self.solve_name(name);
         match self.get_ref(name) {
             Some(d) => d.is_array(),
             _ => false,
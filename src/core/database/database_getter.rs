@@ -2,6 +2,7 @@
 //SPDXLicense-Identifier: BSD-3-Clause
 
 use super::data::Data;
+use super::database_nameref::split_subscript;
 use super::DataBase;
 use crate::error::exec::ExecError;
 use std::collections::HashSet;
@@ -60,6 +61,7 @@ impl DataBase {
     }
 
     pub fn get_indexes_all(&mut self, name: &str) -> Vec<String> {
+        let name = &self.solve_name(name);
         let layer = self.position_parameters.len() - 1;
         if name == "@" {
             return self.position_parameters[layer].clone();
@@ -77,6 +79,7 @@ impl DataBase {
         pos: usize,
         flatten: bool,
     ) -> Result<Vec<String>, ExecError> {
+        let name = &self.solve_name(name);
         let layer = self.position_parameters.len() - 1;
         if name == "@" {
             return Ok(self.position_parameters[layer].clone());
@@ -99,6 +102,7 @@ impl DataBase {
     }
 
     pub fn len(&mut self, name: &str) -> usize {
+        let name = &self.solve_name(name);
         if let Some(d) = self.get_ref(name) {
             return d.len();
         }
@@ -106,6 +110,7 @@ impl DataBase {
     }
 
     pub fn index_based_len(&mut self, name: &str) -> usize {
+        let name = &self.solve_name(name);
         if let Some(d) = self.get_ref(name) {
             return d.index_based_len();
         }
@@ -117,6 +122,7 @@ impl DataBase {
     }
 
     pub fn get_elem(&mut self, name: &str, pos: &str) -> Result<String, ExecError> {
+        let name = &self.solve_name(name);
         Self::name_check(name)?;
 
         let layer = self.get_layer_pos(name);
@@ -143,6 +149,7 @@ impl DataBase {
     }
 
     pub fn get_elem_len(&mut self, name: &str, key: &str) -> Result<usize, ExecError> {
+        let name = &self.solve_name(name);
         Self::name_check(name)?;
 
         if let Some(v) = self.get_ref(name) {
@@ -157,6 +164,7 @@ impl DataBase {
     }
 
     pub fn get_len(&mut self, name: &str) -> Result<usize, ExecError> {
+        let name = &self.solve_name(name);
         Self::name_check(name)?;
 
         if name == "@" || name == "*" {
@@ -186,6 +194,17 @@ impl DataBase {
     pub fn get_param(&mut self, name: &str) -> Result<String, ExecError> {
         Self::name_check(name)?;
 
+        if self.is_nameref(name) {
+            let target = self.solve_name(name);
+            if let Some((n, index)) = split_subscript(&target) {
+                return self.get_elem(n, index);
+            }
+            if self.is_nameref(&target) {
+                return Ok("".to_string()); // circular or no target
+            }
+            return self.get_param(&target);
+        }
+
         if let Some(val) = special_param(self, name) {
             return Ok(val);
         }
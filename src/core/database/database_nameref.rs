@@ -0,0 +1,103 @@
+//SPDXFileCopyrightText: 2025 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDXLicense-Identifier: BSD-3-Clause
+
+use super::SingleData;
+use crate::core::DataBase;
+use crate::error::exec::ExecError;
+use crate::utils;
+
+impl DataBase {
+    pub fn is_nameref(&mut self, name: &str) -> bool {
+        self.has_flag(name, 'n')
+    }
+
+    /* the name of the target, which is stored as the value of the reference */
+    pub fn get_nameref(&mut self, name: &str) -> Option<String> {
+        if !self.is_nameref(name) {
+            return None;
+        }
+
+        let layer = self.get_layer_pos(name)?;
+        self.params[layer].get_mut(name)?.get_as_single().ok()
+    }
+
+    pub fn set_nameref(
+        &mut self,
+        name: &str,
+        target: &str,
+        layer: Option<usize>,
+    ) -> Result<(), ExecError> {
+        Self::name_check(name)?;
+        self.write_check(name)?;
+
+        if !target.is_empty() && !utils::is_param(target) && !is_subscripted_name(target) {
+            let msg = format!("`{}': invalid variable name for name reference", target);
+            return Err(ExecError::Other(msg));
+        }
+        if target == name {
+            let msg = format!("{}: nameref variable self references not allowed", name);
+            return Err(ExecError::Other(msg));
+        }
+
+        let layer = self.get_target_layer(name, layer);
+        self.params[layer].insert(name.to_string(), Box::new(SingleData::from(target)));
+        if !self.has_flag_layer(name, 'n', layer) {
+            self.set_flag(name, 'n', Some(layer));
+        }
+        Ok(())
+    }
+
+    /* follows the chain of references. A layer given for a reference
+     * doesn't apply to its target. */
+    pub fn solve_nameref(&mut self, name: &str, layer: Option<usize>) -> (String, Option<usize>) {
+        let mut ans = name.to_string();
+        let mut chain = vec![];
+
+        while let Some(target) = self.get_nameref(&ans) {
+            if target.is_empty() {
+                break;
+            }
+            if chain.contains(&target) || target == name {
+                self.warn_circular_nameref(name);
+                return (name.to_string(), layer);
+            }
+            chain.push(ans);
+            ans = target;
+        }
+
+        match chain.is_empty() {
+            true => (ans, layer),
+            false => (ans, None),
+        }
+    }
+
+    pub fn solve_name(&mut self, name: &str) -> String {
+        self.solve_nameref(name, None).0
+    }
+
+    fn warn_circular_nameref(&mut self, name: &str) {
+        let shellname = self.get_param("0").unwrap_or_default();
+        let lineno = self.get_param("LINENO").unwrap_or_default();
+        match self.flags.contains('i') {
+            true => eprintln!("{}: warning: {}: circular name reference", &shellname, name),
+            false => eprintln!(
+                "{}: line {}: warning: {}: circular name reference",
+                &shellname, &lineno, name
+            ),
+        }
+    }
+}
+
+/* for a reference to an array element like declare -n r='a[1]' */
+pub fn split_subscript(name: &str) -> Option<(&str, &str)> {
+    let pos = name.find('[')?;
+    let index = name[pos + 1..].strip_suffix(']')?;
+    Some((&name[..pos], index))
+}
+
+fn is_subscripted_name(name: &str) -> bool {
+    match split_subscript(name) {
+        Some((n, i)) => utils::is_param(n) && !i.is_empty(),
+        None => false,
+    }
+}
@@ -8,6 +8,7 @@ use super::data::epochseconds::EpochSeconds;
 use super::data::random::RandomVar;
 use super::data::seconds::Seconds;
 use super::data::srandom::SRandomVar;
+use super::database_nameref::split_subscript;
 use super::{
     ArrayData, AssocData, Data, IntArrayData, IntAssocData, IntData, SingleData, UninitArray,
     UninitAssoc,
@@ -33,6 +34,8 @@ impl DataBase {
         value: &str,
         layer: Option<usize>,
     ) -> Result<(), ExecError> {
+        let (name, layer) = self.solve_nameref(name, layer);
+        let name = name.as_str();
         Self::name_check(name)?;
         self.write_check(name)?;
         restricted_shell::check(self, name, &Some(vec![value.to_string()]))?;
@@ -67,6 +70,11 @@ impl DataBase {
         val: &str,
         layer: Option<usize>,
     ) -> Result<(), ExecError> {
+        let (name, layer) = self.solve_nameref(name, layer);
+        if let Some((n, index)) = split_subscript(&name) {
+            return self.set_param2(n, index, val, layer);
+        }
+        let name = name.as_str();
         Self::name_check(name)?;
         self.write_check(name)?;
         restricted_shell::check(self, name, &Some(vec![val.to_string()]))?;
@@ -117,6 +125,8 @@ impl DataBase {
         val: &str,
         layer: Option<usize>,
     ) -> Result<(), ExecError> {
+        let (name, layer) = self.solve_nameref(name, layer);
+        let name = name.as_str();
         Self::name_check(name)?;
         self.write_check(name)?;
         restricted_shell::check(self, name, &Some(vec![val.to_string()]))?;
@@ -161,6 +171,8 @@ impl DataBase {
         val: &str,
         layer: Option<usize>,
     ) -> Result<(), ExecError> {
+        let (name, layer) = self.solve_nameref(name, layer);
+        let name = name.as_str();
         if index.is_empty() {
             return self.set_param(name, val, layer);
         }
@@ -191,6 +203,8 @@ impl DataBase {
         val: &str,
         layer: Option<usize>,
     ) -> Result<(), ExecError> {
+        let (name, layer) = self.solve_nameref(name, layer);
+        let name = name.as_str();
         if index.is_empty() {
             return self.append_param(name, val, layer);
         }
@@ -221,6 +235,8 @@ impl DataBase {
         pos: isize,
         layer: Option<usize>,
     ) -> Result<(), ExecError> {
+        let (name, layer) = self.solve_nameref(name, layer);
+        let name = name.as_str();
         Self::name_check(name)?;
         self.write_check(name)?;
         restricted_shell::check(self, name, &Some(vec![val.to_string()]))?;
@@ -242,6 +258,8 @@ impl DataBase {
         pos: isize,
         layer: Option<usize>,
     ) -> Result<(), ExecError> {
+        let (name, layer) = self.solve_nameref(name, layer);
+        let name = name.as_str();
         Self::name_check(name)?;
         self.write_check(name)?;
         restricted_shell::check(self, name, &Some(vec![val.to_string()]))?;
@@ -258,6 +276,8 @@ impl DataBase {
         val: &str,
         layer: Option<usize>,
     ) -> Result<(), ExecError> {
+        let (name, layer) = self.solve_nameref(name, layer);
+        let name = name.as_str();
         Self::name_check(name)?;
         self.write_check(name)?;
         restricted_shell::check(self, name, &Some(vec![val.to_string()]))?;
@@ -289,6 +309,8 @@ impl DataBase {
         val: &str,
         layer: Option<usize>,
     ) -> Result<(), ExecError> {
+        let (name, layer) = self.solve_nameref(name, layer);
+        let name = name.as_str();
         Self::name_check(name)?;
         self.write_check(name)?;
         restricted_shell::check(self, name, &Some(vec![val.to_string()]))?;
@@ -304,6 +326,8 @@ impl DataBase {
         v: Option<Vec<String>>,
         layer: Option<usize>,
     ) -> Result<(), ExecError> {
+        let (name, layer) = self.solve_nameref(name, layer);
+        let name = name.as_str();
         Self::name_check(name)?;
         self.write_check(name)?;
         restricted_shell::check(self, name, &v)?;
@@ -332,6 +356,8 @@ impl DataBase {
         v: Option<Vec<String>>,
         layer: Option<usize>,
     ) -> Result<(), ExecError> {
+        let (name, layer) = self.solve_nameref(name, layer);
+        let name = name.as_str();
         Self::name_check(name)?;
         self.write_check(name)?;
         restricted_shell::check(self, name, &v)?;
@@ -358,6 +384,8 @@ impl DataBase {
     }
 
     pub fn set_int_assoc(&mut self, name: &str, layer: Option<usize>) -> Result<(), ExecError> {
+        let (name, layer) = self.solve_nameref(name, layer);
+        let name = name.as_str();
         Self::name_check(name)?;
         self.write_check(name)?;
         restricted_shell::check(self, name, &None)?;
@@ -382,6 +410,8 @@ impl DataBase {
         layer: Option<usize>,
         set_array: bool,
     ) -> Result<(), ExecError> {
+        let (name, layer) = self.solve_nameref(name, layer);
+        let name = name.as_str();
         Self::name_check(name)?;
         self.write_check(name)?;
         restricted_shell::check(self, name, &None)?;
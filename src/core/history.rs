@@ -1,82 +1,381 @@
 //SPDXFileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDXLicense-Identifier: BSD-3-Clause
 
+use crate::utils::{clock, glob};
 use crate::ShellCore;
-use rev_lines::RevLines;
-use std::fs::File;
+use std::fs;
 use std::fs::OpenOptions;
-use std::io::{BufReader, BufWriter, Write};
+use std::io::Write;
+
+mod cmdhist;
+mod expansion;
+
+#[derive(Debug, Default)]
+pub struct HistoryState {
+    /* the times of the entries from the oldest one. 0 if not recorded. */
+    pub times: Vec<i64>,
+    /* the number of the entries removed from the head of the list */
+    pub base: usize,
+    /* whether the current command is recorded in history[0] */
+    pub current_added: bool,
+    /* the number of the entries which are not appended to the file yet */
+    pub session_lines: usize,
+    /* the number of the entries read from the file */
+    pub file_lines: usize,
+}
+
+/* entries with the times given by "#epoch" lines */
+fn read_history_file(filename: &str) -> Result<Vec<(i64, String)>, String> {
+    let text = fs::read(filename).map_err(|e| e.to_string())?;
+    let text = String::from_utf8_lossy(&text);
+    let mut ans = vec![];
+    let mut time = None;
+    for line in text.lines() {
+        if let Some(t) = line.strip_prefix('#').and_then(|t| t.parse::<i64>().ok()) {
+            time = Some(t);
+            continue;
+        }
+        ans.push((time.take().unwrap_or(0), line.to_string()));
+    }
+    Ok(ans)
+}
 
 impl ShellCore {
+    /* oldest first. The last one is the current line. */
+    pub fn history_list(&mut self) -> Vec<String> {
+        let entries = self.history.iter().rev().filter(|h| !h.is_empty());
+        entries.map(|h| h.replace("↵ \0", "\n")).collect()
+    }
+
+    /* the number shown by the history builtin for the position from the oldest entry */
+    pub fn history_number(&self, pos: usize) -> usize {
+        self.history_state.base + pos + 1
+    }
+
+    /* the times of new entries are set and those of removed entries are discarded.
+     * A non-interactive shell records the times only with HISTTIMEFORMAT. */
+    fn sync_history_times(&mut self) {
+        let format = self.db.get_param("HISTTIMEFORMAT").unwrap_or_default();
+        let time = match self.db.flags.contains('i') || !format.is_empty() {
+            true => clock::epoch_seconds(),
+            false => 0,
+        };
+        let times = &mut self.history_state.times;
+        times.truncate(self.history.len());
+        while times.len() < self.history.len() {
+            times.push(time);
+        }
+    }
+
+    pub fn history_time(&mut self, pos: usize) -> i64 {
+        self.sync_history_times();
+        self.history_state.times[pos]
+    }
+
+    /* the entry at the position from the oldest one */
+    pub fn remove_history(&mut self, pos: usize) {
+        self.sync_history_times();
+        let len = self.history.len();
+        if pos < len {
+            self.history.remove(len - 1 - pos);
+            self.history_state.times.remove(pos);
+        }
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+        self.history_state = HistoryState {
+            file_lines: self.history_state.file_lines,
+            ..Default::default()
+        };
+    }
+
+    /* removes the current command (e.g. history -s, fc) from the list */
+    pub fn remove_current_history(&mut self) {
+        if self.history_state.current_added && !self.history.is_empty() {
+            self.history.remove(0);
+            self.history_state.session_lines = self.history_state.session_lines.saturating_sub(1);
+        }
+        self.history_state.current_added = false;
+    }
+
+    /* HISTCONTROL and HISTIGNORE */
+    fn history_ignored(&mut self, entry: &str) -> bool {
+        let prev = self.history.get(1).cloned().unwrap_or_default();
+        let control = self.db.get_param("HISTCONTROL").unwrap_or_default();
+        for c in control.split(':') {
+            match c {
+                "ignorespace" if entry.starts_with(' ') => return true,
+                "ignoredups" if entry == prev => return true,
+                "ignoreboth" if entry.starts_with(' ') || entry == prev => return true,
+                _ => {}
+            }
+        }
+
+        let line = entry.split("↵ \0").next().unwrap_or("");
+        let prev = prev.split("↵ \0").next().unwrap_or("");
+        let extglob = self.shopts.query("extglob");
+        let ignore = self.db.get_param("HISTIGNORE").unwrap_or_default();
+        ignore
+            .split(':')
+            .filter(|p| !p.is_empty())
+            .any(|p| glob::parse_and_compare(line, &p.replace('&', prev), extglob))
+    }
+
+    fn erase_history_dups(&mut self, entry: &str) {
+        let mut i = 1;
+        while i < self.history.len() {
+            match self.history[i] == entry {
+                true => self.remove_history(self.history.len() - 1 - i),
+                false => i += 1,
+            }
+        }
+    }
+
+    /* HISTSIZE: the oldest entries are removed. As bash does, the base is
+     * reset to the number of removed entries when the older entries (other
+     * than the added ones) exceed HISTSIZE, and advanced one by one for
+     * the added ones after that. */
+    pub fn stifle_history(&mut self, added: usize) {
+        let size = match self
+            .db
+            .get_param("HISTSIZE")
+            .unwrap_or_default()
+            .parse::<i64>()
+        {
+            Ok(n) if n >= 0 => n as usize,
+            _ => return,
+        };
+
+        self.sync_history_times();
+        let older = self.history.len().saturating_sub(added);
+        if older > size {
+            self.remove_oldest_history(older - size);
+            self.history_state.base = older - size - 1;
+        }
+        while self.history.len() > size {
+            self.remove_oldest_history(1);
+            self.history_state.base += 1;
+        }
+        if self.history.is_empty() {
+            self.history_state.current_added = false;
+        }
+    }
+
+    fn remove_oldest_history(&mut self, num: usize) {
+        self.history.truncate(self.history.len() - num);
+        self.history_state.times.drain(..num);
+    }
+
+    /* checks the current entry in history[0] before the execution */
+    pub fn record_history(&mut self) {
+        self.history_state.current_added = false;
+        let entry = match self.history.first() {
+            Some(h) => h.clone(),
+            None => return,
+        };
+
+        if entry.trim().is_empty() || self.history_ignored(&entry) {
+            self.history.remove(0);
+            self.sync_history_times();
+            return;
+        }
+
+        let control = self.db.get_param("HISTCONTROL").unwrap_or_default();
+        if control.split(':').any(|c| c == "erasedups") {
+            self.erase_history_dups(&entry);
+        }
+
+        self.history_state.current_added = true;
+        self.history_state.session_lines += 1;
+        self.sync_history_times();
+        self.stifle_history(1);
+    }
+
+    /* a multi-line entry is kept as one entry with cmdhist. Newlines are
+     * kept with lithist, or replaced with "; " or a space without it. */
+    pub fn arrange_multiline_history(&mut self) {
+        let entry = match self.history.first() {
+            Some(h) if h.contains("↵ \0") => h.clone(),
+            _ => return,
+        };
+        let lines: Vec<&str> = entry.split("↵ \0").collect();
+
+        if !self.shopts.query("cmdhist") {
+            self.history_state.session_lines += lines.len() - 1;
+            self.history.remove(0);
+            for line in lines {
+                self.history.insert(0, line.to_string());
+            }
+        } else if !self.shopts.query("lithist") {
+            self.history[0] = cmdhist::join_lines(&lines);
+        }
+    }
+
+    /* the last word of the nth previous command for yank-last-arg */
+    pub fn history_last_arg(&mut self, nth: usize) -> Option<String> {
+        let mut list = self.history_list();
+        if self.history.first().is_some_and(|h| !h.is_empty()) {
+            list.pop(); // the current line
+        }
+        let line = list.len().checked_sub(nth).map(|i| list[i].clone())?;
+        expansion::split_words(&line).pop()
+    }
+
     pub fn fetch_history(&mut self, pos: usize, prev: usize, prev_str: String) -> String {
         if prev < self.history.len() {
             self.history[prev] = prev_str;
-        } else {
-            self.rewritten_history
-                .insert(prev + 1 - self.history.len(), prev_str);
         }
 
-        if pos < self.history.len() {
-            self.history[pos].clone()
-        } else {
-            self.fetch_history_file(pos + 1 - self.history.len())
+        match pos < self.history.len() {
+            true => self.history[pos].clone(),
+            false => String::new(),
+        }
+    }
+
+    /* for incremental search. Positions are the same as those of fetch_history.
+     * Entries are checked from the position toward older (or newer) ones. */
+    pub fn search_history(
+        &mut self,
+        word: &str,
+        from: usize,
+        forward: bool,
+    ) -> Option<(usize, String)> {
+        let mut pos = from;
+
+        loop {
+            let entry = self.history.get(pos)?.clone();
+            if !word.is_empty() && entry.contains(word) {
+                return Some((pos, entry));
+            }
+
+            match forward {
+                true => pos = pos.checked_sub(1)?,
+                false => pos += 1,
+            }
+        }
+    }
+
+    /* the newest entry which starts with the prefix for autosuggestion */
+    pub fn history_suggestion(&mut self, prefix: &str) -> Option<String> {
+        self.history
+            .iter()
+            .skip(1)
+            .find(|h| h.starts_with(prefix) && h.len() > prefix.len() && !h.contains("↵ \0"))
+            .cloned()
+    }
+
+    /* the position of the oldest entry for beginning-of-history */
+    pub fn oldest_history_pos(&mut self) -> usize {
+        self.history.len().saturating_sub(1)
+    }
+
+    /* adds the entries in the file to the list. The first skip entries are ignored (history -n). */
+    pub fn read_history(&mut self, filename: &str, skip: usize) -> Result<(), String> {
+        let entries = read_history_file(filename)?;
+        self.sync_history_times();
+        self.history_state.file_lines = entries.len();
+        let len = self.history.len();
+        for (time, entry) in entries.into_iter().skip(skip) {
+            self.history.insert(0, entry);
+            self.history_state.times.push(time);
         }
+        self.stifle_history(self.history.len() - len);
+        Ok(())
     }
 
-    pub fn fetch_history_file(&mut self, pos: usize) -> String {
-        if let Some(s) = self.rewritten_history.get(&pos) {
-            return s.to_string();
+    /* the whole list, or the entries of this session with append */
+    pub fn write_history(&mut self, filename: &str, append: bool) -> Result<(), String> {
+        self.sync_history_times();
+        let len = self.history.len();
+        let num = match append {
+            true => std::cmp::min(self.history_state.session_lines, len),
+            false => len,
+        };
+
+        let stamp = !self
+            .db
+            .get_param("HISTTIMEFORMAT")
+            .unwrap_or_default()
+            .is_empty();
+        let mut text = String::new();
+        for pos in len - num..len {
+            let time = self.history_state.times[pos];
+            if stamp && time > 0 {
+                text += &format!("#{}\n", time);
+            }
+            text += &self.history[len - 1 - pos];
+            text += "\n";
+        }
+
+        let mut file = match append {
+            true => OpenOptions::new().create(true).append(true).open(filename),
+            false => fs::File::create(filename),
         }
-        if pos == 0 {
-            return String::new();
+        .map_err(|e| format!("{}: cannot create: {}", filename, e))?;
+        file.write_all(text.as_bytes()).map_err(|e| e.to_string())?;
+
+        self.history_state.session_lines = 0;
+        if append {
+            self.history_state.file_lines += num;
         }
+        Ok(())
+    }
 
-        let mut file_line = pos - 1;
-        if let Ok(n) = self
+    /* HISTFILESIZE: the oldest entries in the file are removed */
+    fn truncate_history_file(&mut self, filename: &str) {
+        let size = match self
             .db
             .get_param("HISTFILESIZE")
             .unwrap_or_default()
-            .parse::<usize>()
+            .parse::<i64>()
         {
-            file_line %= n;
-        }
+            Ok(n) if n >= 0 => n as usize,
+            _ => return,
+        };
+        let entries = match read_history_file(filename) {
+            Ok(e) if e.len() > size => e,
+            _ => return,
+        };
 
-        if let Ok(hist_file) = File::open(self.db.get_param("HISTFILE").unwrap_or_default()) {
-            let mut rev_lines = RevLines::new(BufReader::new(hist_file));
-            if let Some(Ok(s)) = rev_lines.nth(file_line) {
-                return s;
+        let stamp = !self
+            .db
+            .get_param("HISTTIMEFORMAT")
+            .unwrap_or_default()
+            .is_empty();
+        let mut text = String::new();
+        for (time, entry) in &entries[entries.len() - size..] {
+            if stamp && *time > 0 {
+                text += &format!("#{}\n", time);
             }
+            text += entry;
+            text += "\n";
         }
+        let _ = fs::write(filename, text);
+    }
 
-        String::new()
+    /* at the start of an interactive shell */
+    pub fn load_history(&mut self) {
+        let filename = self.db.get_param("HISTFILE").unwrap_or_default();
+        if !filename.is_empty() {
+            let _ = self.read_history(&filename, 0);
+        }
     }
 
-    pub fn write_history_to_file(&mut self) {
+    /* at the exit. The file is overwritten unless histappend is set. */
+    pub fn save_history(&mut self) {
         if !self.db.flags.contains('i') || self.is_subshell {
             return;
         }
         let filename = self.db.get_param("HISTFILE").unwrap_or_default();
         if filename.is_empty() {
-            eprintln!("sush: HISTFILE is not set");
             return;
         }
 
-        let file = match OpenOptions::new().create(true).append(true).open(&filename) {
-            Ok(f) => f,
-            _ => {
-                eprintln!("sush: invalid history file");
-                return;
-            }
-        };
-
-        let mut f = BufWriter::new(file);
-        for h in self.history.iter().rev() {
-            if h.is_empty() {
-                continue;
-            }
-            let _ = f.write(h.as_bytes());
-            let _ = f.write(&[0x0A]);
+        let append = self.shopts.query("histappend");
+        match self.write_history(&filename, append) {
+            Ok(()) => self.truncate_history_file(&filename),
+            Err(e) => eprintln!("sush: {}", e),
         }
-        let _ = f.flush();
     }
 }
@@ -0,0 +1,135 @@
+//SPDXFileCopyrightText: 2025 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDXLicense-Identifier: BSD-3-Clause
+
+const NEWLINE: &str = "↵ \0";
+
+/* words after which a newline is replaced with a space, not with "; " */
+const NO_SEMI_SUCCESSORS: [&str; 16] = [
+    "{", "!", "&&", "||", "|&", ";;", ";&", ";;&", "case", "do", "elif", "else", "if", "in",
+    "then", "until",
+];
+
+#[derive(Debug, Default)]
+struct LineScanner {
+    quote: Option<char>,
+    /* delimiters of here documents and whether leading tabs are removed */
+    heredocs: Vec<(String, bool)>,
+    in_heredoc: bool,
+}
+
+impl LineScanner {
+    fn heredoc_word(chars: &[char], pos: &mut usize) -> (String, bool) {
+        let strip = chars.get(*pos) == Some(&'-');
+        if strip {
+            *pos += 1;
+        }
+        while *pos < chars.len() && (chars[*pos] == ' ' || chars[*pos] == '\t') {
+            *pos += 1;
+        }
+
+        let mut word = String::new();
+        while *pos < chars.len() && !" \t;|&<>()".contains(chars[*pos]) {
+            if !"'\"\\".contains(chars[*pos]) {
+                word.push(chars[*pos]);
+            }
+            *pos += 1;
+        }
+        (word, strip)
+    }
+
+    /* returns false if the rest of the line is a comment */
+    fn scan(&mut self, line: &str) -> bool {
+        let chars: Vec<char> = line.chars().collect();
+        let mut pos = 0;
+        let mut escaped = false;
+
+        while pos < chars.len() {
+            let c = chars[pos];
+            pos += 1;
+            if escaped {
+                escaped = false;
+                continue;
+            }
+
+            match (self.quote, c) {
+                (Some('\''), '\'') => self.quote = None,
+                (Some('\''), _) => {}
+                (_, '\\') => escaped = true,
+                (Some(q), c) if q == c => self.quote = None,
+                (Some(_), _) => {}
+                (None, '\'' | '"' | '`') => self.quote = Some(c),
+                (None, '#') if pos == 1 || " \t;|&(".contains(chars[pos - 2]) => return false,
+                (None, '<') if chars.get(pos) == Some(&'<') && chars.get(pos + 1) != Some(&'<') => {
+                    pos += 1;
+                    let word = Self::heredoc_word(&chars, &mut pos);
+                    self.heredocs.push(word);
+                }
+                _ => {}
+            }
+        }
+        true
+    }
+
+    /* the chars put between the line and the next one */
+    fn delimiter(&mut self, line: &str) -> &'static str {
+        if self.in_heredoc {
+            let (word, strip) = &self.heredocs[0];
+            let body = match strip {
+                true => line.trim_start_matches('\t'),
+                false => line,
+            };
+            if body == word {
+                self.heredocs.remove(0);
+                self.in_heredoc = !self.heredocs.is_empty();
+            }
+            return NEWLINE;
+        }
+
+        if !self.scan(line) || self.quote.is_some() {
+            return NEWLINE;
+        }
+        if !self.heredocs.is_empty() {
+            self.in_heredoc = true;
+            return NEWLINE;
+        }
+
+        let last = line.split_whitespace().last().unwrap_or("");
+        if last.is_empty()
+            || last.ends_with([';', '&', '|', '('])
+            || last.ends_with("()")
+            || NO_SEMI_SUCCESSORS.contains(&last)
+        {
+            return " ";
+        }
+        "; "
+    }
+}
+
+fn ends_with_backslash(line: &str) -> bool {
+    line.chars().rev().take_while(|c| *c == '\\').count() % 2 == 1
+}
+
+/* joins the lines of a multi-line command into an entry like bash does with cmdhist */
+pub fn join_lines(lines: &[&str]) -> String {
+    let mut scanner = LineScanner::default();
+    let mut ans = String::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        if i == lines.len() - 1 {
+            ans += line;
+            break;
+        }
+
+        if !scanner.in_heredoc && scanner.quote.is_none() && ends_with_backslash(line) {
+            let mut line = line.to_string();
+            line.pop();
+            scanner.scan(&line);
+            ans += &line;
+            continue;
+        }
+
+        ans += line;
+        ans += scanner.delimiter(line);
+    }
+    ans
+}
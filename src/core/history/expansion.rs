@@ -0,0 +1,422 @@
+//SPDXFileCopyrightText: 2025 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDXLicense-Identifier: BSD-3-Clause
+
+use crate::core::xtrace::{self, QuoteStyle};
+use crate::ShellCore;
+
+#[derive(Debug, Default)]
+struct Expander {
+    chars: Vec<char>,
+    pos: usize,
+    history: Vec<String>,
+    ans: String,
+    search_word: String,
+    subst: Option<(String, String)>,
+    print_only: bool,
+}
+
+/* words of a history entry. Quoted parts and operators are kept together. */
+pub fn split_words(line: &str) -> Vec<String> {
+    let mut ans = vec![];
+    let mut word = String::new();
+    let mut quote = None;
+    let mut escaped = false;
+
+    for c in line.chars() {
+        if escaped {
+            word.push(c);
+            escaped = false;
+            continue;
+        }
+
+        match (quote, c) {
+            (_, '\\') if quote != Some('\'') => {
+                word.push(c);
+                escaped = true;
+            }
+            (Some(q), c) if q == c => {
+                word.push(c);
+                quote = None;
+            }
+            (Some(_), c) => word.push(c),
+            (None, '\'' | '"' | '`') => {
+                word.push(c);
+                quote = Some(c);
+            }
+            (None, ' ' | '\t' | '\n') => {
+                if !word.is_empty() {
+                    ans.push(std::mem::take(&mut word));
+                }
+            }
+            (None, ';' | '&' | '|' | '<' | '>' | '(' | ')') => {
+                let is_op = |w: &str| w.chars().all(|c| ";&|<>()".contains(c));
+                if !word.is_empty() && !is_op(&word) {
+                    ans.push(std::mem::take(&mut word));
+                }
+                word.push(c);
+            }
+            (None, c) => {
+                if !word.is_empty() && word.chars().all(|c| ";&|<>()".contains(c)) {
+                    ans.push(std::mem::take(&mut word));
+                }
+                word.push(c);
+            }
+        }
+    }
+
+    if !word.is_empty() {
+        ans.push(word);
+    }
+    ans
+}
+
+impl Expander {
+    fn peek(&self, n: usize) -> Option<char> {
+        self.chars.get(self.pos + n).copied()
+    }
+
+    fn event_error(&self, from: usize) -> String {
+        let text: String = self.chars[from..self.pos].iter().collect();
+        format!("{}: event not found", text)
+    }
+
+    fn take_while(&mut self, judge: impl Fn(char) -> bool) -> String {
+        let mut ans = String::new();
+        while let Some(c) = self.peek(0) {
+            if !judge(c) {
+                break;
+            }
+            ans.push(c);
+            self.pos += 1;
+        }
+        ans
+    }
+
+    fn event(&mut self, hist_char: char) -> Result<String, String> {
+        let from = self.pos;
+        self.pos += 1; // hist_char
+        let last = self.history.last().cloned();
+
+        match self.peek(0) {
+            Some(c) if c == hist_char => {
+                self.pos += 1;
+                last.ok_or_else(|| self.event_error(from))
+            }
+            Some('#') => {
+                self.pos += 1;
+                Ok(self.ans.clone())
+            }
+            Some(c) if c.is_ascii_digit() || c == '-' => {
+                let minus = c == '-';
+                if minus {
+                    self.pos += 1;
+                }
+                let num = self.take_while(|c| c.is_ascii_digit());
+                let n = num.parse::<usize>().unwrap_or(0);
+                let len = self.history.len() + 1; // the number of the current line
+                let index = match minus {
+                    true => len.checked_sub(n).filter(|_| n > 0),
+                    false => Some(n).filter(|n| *n > 0 && *n < len),
+                };
+                match index.filter(|i| *i > 0 && *i < len) {
+                    Some(i) => Ok(self.history[i - 1].clone()),
+                    None => Err(self.event_error(from)),
+                }
+            }
+            Some('?') => {
+                self.pos += 1;
+                let s = self.take_while(|c| c != '?' && c != '\n');
+                if self.peek(0) == Some('?') {
+                    self.pos += 1;
+                }
+                self.search_word = s.clone();
+                let found = self.history.iter().rev().find(|h| h.contains(&s)).cloned();
+                found.ok_or_else(|| self.event_error(from))
+            }
+            Some('$' | '^' | '*' | ':' | '%') if last.is_none() => {
+                self.take_while(|c| !" \t\n;&|<>()\"'`".contains(c)); // the word designator
+                Err(self.event_error(from))
+            }
+            Some('$' | '^' | '*' | ':' | '%') => Ok(last.unwrap()),
+            _ => {
+                let s = self.take_while(|c| !" \t\n:;&|<>()\"'`".contains(c));
+                let found = self
+                    .history
+                    .iter()
+                    .rev()
+                    .find(|h| h.starts_with(&s))
+                    .cloned();
+                found.ok_or_else(|| self.event_error(from))
+            }
+        }
+    }
+
+    fn word_index(&mut self, spec: char, words: &[String]) -> Option<usize> {
+        match spec {
+            '^' => Some(1),
+            '$' => Some(words.len().saturating_sub(1)),
+            '%' => words.iter().position(|w| w.contains(&self.search_word)),
+            c if c.is_ascii_digit() => self.take_while(|c| c.is_ascii_digit()).parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn word_designator(&mut self, line: &str) -> Result<Option<String>, String> {
+        let start = self.pos;
+        let has_colon = self.peek(0) == Some(':');
+        let spec_pos = self.pos + if has_colon { 1 } else { 0 };
+        let spec = match self.chars.get(spec_pos) {
+            Some(c) if "^$*%-".contains(*c) || (has_colon && c.is_ascii_digit()) => *c,
+            _ => return Ok(None),
+        };
+        self.pos = spec_pos;
+
+        let words = split_words(line);
+        let bad = |e: &Self| {
+            let text: String = e.chars[start..e.pos].iter().collect();
+            format!("{}: bad word specifier", text)
+        };
+
+        if spec == '*' {
+            self.pos += 1;
+            return Ok(Some(
+                words.get(1..).map(|w| w.join(" ")).unwrap_or_default(),
+            ));
+        }
+
+        let first = match spec {
+            '-' => 0,
+            c if c.is_ascii_digit() => self.word_index(c, &words).unwrap_or(usize::MAX),
+            c => {
+                self.pos += 1;
+                self.word_index(c, &words).unwrap_or(usize::MAX)
+            }
+        };
+
+        let last = match self.peek(0) {
+            Some('*') => {
+                self.pos += 1;
+                if first > words.len() {
+                    return Err(bad(self));
+                }
+                return Ok(Some(words[first.min(words.len())..].join(" ")));
+            }
+            Some('-') => {
+                self.pos += 1;
+                match self.peek(0) {
+                    Some(c) if c.is_ascii_digit() || c == '$' || c == '^' || c == '%' => {
+                        if !c.is_ascii_digit() {
+                            self.pos += 1;
+                        }
+                        self.word_index(c, &words).unwrap_or(usize::MAX)
+                    }
+                    _ => words.len().saturating_sub(2),
+                }
+            }
+            _ => first,
+        };
+
+        if first > last || last >= words.len() {
+            return Err(bad(self));
+        }
+        Ok(Some(words[first..=last].join(" ")))
+    }
+
+    fn read_until(&mut self, delim: char) -> String {
+        let mut ans = String::new();
+        while let Some(c) = self.peek(0) {
+            if c == '\n' {
+                break;
+            }
+            self.pos += 1;
+            if c == delim {
+                break;
+            }
+            match (c, self.peek(0)) {
+                ('\\', Some(d)) if d == delim => {
+                    ans.push(d);
+                    self.pos += 1;
+                }
+                _ => ans.push(c),
+            }
+        }
+        ans
+    }
+
+    fn substitute(&mut self, text: &str, global: bool, from: usize) -> Result<String, String> {
+        let failed = |e: &Self| {
+            let text: String = e.chars[from..e.pos].iter().collect();
+            format!("{}: substitution failed", text)
+        };
+
+        let (old, new) = match &self.subst {
+            Some(s) if !s.0.is_empty() => s.clone(),
+            _ => return Err(failed(self)),
+        };
+
+        if !text.contains(&old) {
+            return Err(failed(self));
+        }
+        Ok(match global {
+            true => text.replace(&old, &new),
+            false => text.replacen(&old, &new, 1),
+        })
+    }
+
+    fn modifiers(&mut self, mut text: String) -> Result<String, String> {
+        while self.peek(0) == Some(':') {
+            let from = self.pos;
+            let mut global = false;
+            let mut m = match self.peek(1) {
+                Some(c) => c,
+                None => break,
+            };
+            if m == 'g' || m == 'a' || m == 'G' {
+                global = true;
+                m = self.peek(2).unwrap_or(' ');
+                self.pos += 1;
+            }
+            self.pos += 2;
+
+            text = match m {
+                'h' => match text.rfind('/') {
+                    Some(0) => "/".to_string(),
+                    Some(p) => text[..p].to_string(),
+                    None => text,
+                },
+                't' => match text.rfind('/') {
+                    Some(p) => text[p + 1..].to_string(),
+                    None => text,
+                },
+                'r' => match text.rfind('.') {
+                    Some(p) if !text[p..].contains('/') => text[..p].to_string(),
+                    _ => text,
+                },
+                'e' => match text.rfind('.') {
+                    Some(p) if !text[p..].contains('/') => text[p..].to_string(),
+                    _ => String::new(),
+                },
+                'p' => {
+                    self.print_only = true;
+                    text
+                }
+                'q' => xtrace::quote(&text, QuoteStyle::Always),
+                'x' => split_words(&text)
+                    .iter()
+                    .map(|w| xtrace::quote(w, QuoteStyle::Always))
+                    .collect::<Vec<String>>()
+                    .join(" "),
+                's' => {
+                    let delim = match self.peek(0) {
+                        Some(c) => c,
+                        None => return Err(format!("{}: bad word specifier", ":s")),
+                    };
+                    self.pos += 1;
+                    let old = self.read_until(delim);
+                    let new = self.read_until(delim);
+                    let old = match (old.is_empty(), &self.subst) {
+                        (true, Some(s)) => s.0.clone(),
+                        _ => old,
+                    };
+                    self.subst = Some((old.clone(), new.replace('&', &old)));
+                    self.substitute(&text, global, from)?
+                }
+                '&' => self.substitute(&text, global, from)?,
+                _ => {
+                    self.pos -= 1;
+                    let text: String = self.chars[from..self.pos + 1].iter().collect();
+                    return Err(format!("{}: unrecognized history modifier", text));
+                }
+            };
+        }
+        Ok(text)
+    }
+
+    fn expand(&mut self, hist_char: char, comment_char: char) -> Result<bool, String> {
+        let mut expanded = false;
+        let mut quote = None;
+
+        while let Some(c) = self.peek(0) {
+            let next = self.peek(1);
+            let at_word_head = self.ans.is_empty() || self.ans.ends_with([' ', '\t', '\n']);
+
+            if quote.is_none() && c == comment_char && at_word_head {
+                self.ans.extend(&self.chars[self.pos..]);
+                break;
+            }
+
+            match (quote, c) {
+                (Some('\''), '\'') | (Some('"'), '"') => quote = None,
+                (None, '\'' | '"') => quote = Some(c),
+                (q, '\\') if q != Some('\'') => {
+                    self.ans.push(c);
+                    if let Some(n) = next {
+                        self.ans.push(n);
+                    }
+                    self.pos += 2;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let literal = next.is_none()
+                || next.is_some_and(|n| " \t\n=(".contains(n))
+                || (quote == Some('"') && next == Some('"'));
+            if c != hist_char || quote == Some('\'') || literal {
+                self.ans.push(c);
+                self.pos += 1;
+                continue;
+            }
+
+            let line = self.event(hist_char)?;
+            let text = self.word_designator(&line)?.unwrap_or(line);
+            let text = self.modifiers(text)?;
+            self.ans += &text;
+            expanded = true;
+        }
+        Ok(expanded)
+    }
+}
+
+impl ShellCore {
+    /* returns the expanded line and whether it is only printed (:p) */
+    pub fn expand_history(&mut self, line: &str) -> Result<Option<(String, bool)>, String> {
+        self.expand_history_with(line, true)
+    }
+
+    /* the current line is excluded from the list of the history or not */
+    pub fn expand_history_with(
+        &mut self,
+        line: &str,
+        exclude_current: bool,
+    ) -> Result<Option<(String, bool)>, String> {
+        let histchars = self.db.get_param("histchars").unwrap_or_default();
+        let mut chs = histchars.chars();
+        let hist_char = chs.next().unwrap_or('!');
+        let quick_char = chs.next().unwrap_or('^');
+        let comment_char = chs.next().unwrap_or('#');
+
+        let mut line = line.to_string();
+        if line.starts_with(quick_char) {
+            line = format!("{}{}:s{}", hist_char, hist_char, line);
+        } else if !line.contains(hist_char) {
+            return Ok(None);
+        }
+
+        let mut history = self.history_list();
+        if exclude_current {
+            history.pop();
+        }
+
+        let mut expander = Expander {
+            chars: line.chars().collect(),
+            history,
+            ..Default::default()
+        };
+
+        match expander.expand(hist_char, comment_char)? {
+            true => Ok(Some((expander.ans, expander.print_only))),
+            false => Ok(None),
+        }
+    }
+}
@@ -8,6 +8,7 @@ use nix::sys::wait;
 use nix::sys::wait::{WaitPidFlag, WaitStatus};
 use nix::unistd;
 use nix::unistd::Pid;
+use std::os::fd::RawFd;
 
 #[derive(Debug, Default)]
 pub struct JobEntry {
@@ -18,6 +19,7 @@ pub struct JobEntry {
     pub text: String,
     change: bool,
     pub no_control: bool,
+    pub coproc: Option<(String, Vec<RawFd>)>,
 }
 
 fn wait_nonblock(pid: &Pid, status: &mut WaitStatus) -> Result<(), ExecError> {
@@ -246,9 +248,35 @@ impl ShellCore {
             self.job_table_priority.insert(0, job_id);
         }
 
+        self.reap_coprocs();
         Ok(())
     }
 
+    /* the fds and the variables of a finished coproc are removed as bash does */
+    pub fn reap_coprocs(&mut self) {
+        let mut finished = vec![];
+        for e in self.job_table.iter_mut() {
+            if still(&e.proc_statuses[0]) {
+                continue;
+            }
+            if let Some((name, fds)) = e.coproc.take() {
+                finished.push((name, fds, e.pids[0]));
+            }
+        }
+
+        for (name, fds, pid) in finished {
+            for fd in fds {
+                let _ = unistd::close(fd);
+            }
+
+            let pid_name = name.clone() + "_PID";
+            if self.db.get_param(&pid_name).unwrap_or_default() == pid.to_string() {
+                self.db.unset_var(&name);
+                self.db.unset_var(&pid_name);
+            }
+        }
+    }
+
     pub fn jobtable_print_status_change(&mut self) {
         if self.is_subshell {
             return;
@@ -0,0 +1,827 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::ShellCore;
+use nix::errno::Errno;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Binding {
+    Function(String),
+    Macro(Vec<u8>),
+    Command(String),
+}
+
+pub const FUNCTIONS: [&str; 173] = [
+    "abort",
+    "accept-line",
+    "alias-expand-line",
+    "arrow-key-prefix",
+    "backward-byte",
+    "backward-char",
+    "backward-delete-char",
+    "backward-kill-line",
+    "backward-kill-word",
+    "backward-word",
+    "beginning-of-history",
+    "beginning-of-line",
+    "bracketed-paste-begin",
+    "call-last-kbd-macro",
+    "capitalize-word",
+    "character-search",
+    "character-search-backward",
+    "clear-display",
+    "clear-screen",
+    "complete",
+    "complete-command",
+    "complete-filename",
+    "complete-hostname",
+    "complete-into-braces",
+    "complete-username",
+    "complete-variable",
+    "copy-backward-word",
+    "copy-forward-word",
+    "copy-region-as-kill",
+    "dabbrev-expand",
+    "delete-char",
+    "delete-char-or-list",
+    "delete-horizontal-space",
+    "digit-argument",
+    "display-shell-version",
+    "do-lowercase-version",
+    "downcase-word",
+    "dump-functions",
+    "dump-macros",
+    "dump-variables",
+    "dynamic-complete-history",
+    "edit-and-execute-command",
+    "emacs-editing-mode",
+    "end-kbd-macro",
+    "end-of-history",
+    "end-of-line",
+    "exchange-point-and-mark",
+    "fetch-history",
+    "forward-backward-delete-char",
+    "forward-byte",
+    "forward-char",
+    "forward-search-history",
+    "forward-word",
+    "glob-complete-word",
+    "glob-expand-word",
+    "glob-list-expansions",
+    "history-and-alias-expand-line",
+    "history-expand-line",
+    "history-search-backward",
+    "history-search-forward",
+    "history-substring-search-backward",
+    "history-substring-search-forward",
+    "insert-comment",
+    "insert-completions",
+    "insert-last-argument",
+    "kill-line",
+    "kill-region",
+    "kill-whole-line",
+    "kill-word",
+    "magic-space",
+    "menu-complete",
+    "menu-complete-backward",
+    "next-history",
+    "next-screen-line",
+    "non-incremental-forward-search-history",
+    "non-incremental-forward-search-history-again",
+    "non-incremental-reverse-search-history",
+    "non-incremental-reverse-search-history-again",
+    "old-menu-complete",
+    "operate-and-get-next",
+    "overwrite-mode",
+    "possible-command-completions",
+    "possible-completions",
+    "possible-filename-completions",
+    "possible-hostname-completions",
+    "possible-username-completions",
+    "possible-variable-completions",
+    "previous-history",
+    "previous-screen-line",
+    "print-last-kbd-macro",
+    "quoted-insert",
+    "re-read-init-file",
+    "redraw-current-line",
+    "reverse-search-history",
+    "revert-line",
+    "self-insert",
+    "set-mark",
+    "shell-backward-kill-word",
+    "shell-backward-word",
+    "shell-expand-line",
+    "shell-forward-word",
+    "shell-kill-word",
+    "shell-transpose-words",
+    "skip-csi-sequence",
+    "spell-correct-word",
+    "start-kbd-macro",
+    "tab-insert",
+    "tilde-expand",
+    "transpose-chars",
+    "transpose-words",
+    "tty-status",
+    "undo",
+    "universal-argument",
+    "unix-filename-rubout",
+    "unix-line-discard",
+    "unix-word-rubout",
+    "upcase-word",
+    "vi-append-eol",
+    "vi-append-mode",
+    "vi-arg-digit",
+    "vi-bWord",
+    "vi-back-to-indent",
+    "vi-backward-bigword",
+    "vi-backward-word",
+    "vi-bword",
+    "vi-change-case",
+    "vi-change-char",
+    "vi-change-to",
+    "vi-char-search",
+    "vi-column",
+    "vi-complete",
+    "vi-delete",
+    "vi-delete-to",
+    "vi-eWord",
+    "vi-edit-and-execute-command",
+    "vi-editing-mode",
+    "vi-end-bigword",
+    "vi-end-word",
+    "vi-eof-maybe",
+    "vi-eword",
+    "vi-fWord",
+    "vi-fetch-history",
+    "vi-first-print",
+    "vi-forward-bigword",
+    "vi-forward-word",
+    "vi-fword",
+    "vi-goto-mark",
+    "vi-insert-beg",
+    "vi-insertion-mode",
+    "vi-match",
+    "vi-movement-mode",
+    "vi-next-word",
+    "vi-overstrike",
+    "vi-overstrike-delete",
+    "vi-prev-word",
+    "vi-put",
+    "vi-redo",
+    "vi-replace",
+    "vi-rubout",
+    "vi-search",
+    "vi-search-again",
+    "vi-set-mark",
+    "vi-subst",
+    "vi-tilde-expand",
+    "vi-undo",
+    "vi-unix-word-rubout",
+    "vi-yank-arg",
+    "vi-yank-pop",
+    "vi-yank-to",
+    "yank",
+    "yank-last-arg",
+    "yank-nth-arg",
+    "yank-pop",
+];
+
+const EMACS_BINDINGS: [(&str, &str); 55] = [
+    ("\\C-g", "abort"),
+    ("\\C-x\\C-g", "abort"),
+    ("\\M-\\C-g", "abort"),
+    ("\\C-j", "accept-line"),
+    ("\\C-m", "accept-line"),
+    ("\\C-b", "backward-char"),
+    ("\\M-[D", "backward-char"),
+    ("\\C-h", "backward-delete-char"),
+    ("\\C-?", "backward-delete-char"),
+    ("\\C-x\\C-?", "backward-kill-line"),
+    ("\\M-\\C-h", "backward-kill-word"),
+    ("\\M-\\C-?", "backward-kill-word"),
+    ("\\M-[1;3D", "backward-word"),
+    ("\\M-[1;5D", "backward-word"),
+    ("\\M-b", "backward-word"),
+    ("\\M-<", "beginning-of-history"),
+    ("\\C-a", "beginning-of-line"),
+    ("\\M-[H", "beginning-of-line"),
+    ("\\M-c", "capitalize-word"),
+    ("\\C-l", "clear-screen"),
+    ("\\C-i", "complete"),
+    ("\\C-d", "delete-char"),
+    ("\\M-[3~", "delete-char"),
+    ("\\M-l", "downcase-word"),
+    ("\\C-x\\C-e", "edit-and-execute-command"),
+    ("\\M->", "end-of-history"),
+    ("\\C-e", "end-of-line"),
+    ("\\M-[F", "end-of-line"),
+    ("\\C-f", "forward-char"),
+    ("\\M-[C", "forward-char"),
+    ("\\C-s", "forward-search-history"),
+    ("\\M-[1;3C", "forward-word"),
+    ("\\M-[1;5C", "forward-word"),
+    ("\\M-f", "forward-word"),
+    ("\\M-.", "insert-last-argument"),
+    ("\\M-_", "insert-last-argument"),
+    ("\\C-k", "kill-line"),
+    ("\\M-d", "kill-word"),
+    ("\\C-n", "next-history"),
+    ("\\M-[B", "next-history"),
+    ("\\C-p", "previous-history"),
+    ("\\M-[A", "previous-history"),
+    ("\\C-r", "reverse-search-history"),
+    ("\\M-\\C-i", "tab-insert"),
+    ("\\C-t", "transpose-chars"),
+    ("\\M-t", "transpose-words"),
+    ("\\C-_", "undo"),
+    ("\\C-x\\C-u", "undo"),
+    ("\\C-u", "unix-line-discard"),
+    ("\\C-w", "unix-word-rubout"),
+    ("\\M-u", "upcase-word"),
+    ("\\M-\\C-j", "vi-editing-mode"),
+    ("\\C-y", "yank"),
+    ("\\M-y", "yank-pop"),
+    ("\\C-x\\C-r", "re-read-init-file"),
+];
+
+/* except for the characters, which are handled as vi commands in vi-command */
+const VI_INSERT_BINDINGS: [(&str, &str); 29] = [
+    ("\\C-j", "accept-line"),
+    ("\\C-m", "accept-line"),
+    ("\\M-[D", "backward-char"),
+    ("\\C-h", "backward-delete-char"),
+    ("\\C-?", "backward-delete-char"),
+    ("\\M-[1;3D", "backward-word"),
+    ("\\M-[1;5D", "backward-word"),
+    ("\\M-[H", "beginning-of-line"),
+    ("\\C-i", "complete"),
+    ("\\M-[3~", "delete-char"),
+    ("\\M-[F", "end-of-line"),
+    ("\\M-[C", "forward-char"),
+    ("\\C-s", "forward-search-history"),
+    ("\\M-[1;3C", "forward-word"),
+    ("\\M-[1;5C", "forward-word"),
+    ("\\M-[5~", "history-search-backward"),
+    ("\\M-[6~", "history-search-forward"),
+    ("\\C-n", "menu-complete"),
+    ("\\C-p", "menu-complete-backward"),
+    ("\\M-[B", "next-history"),
+    ("\\M-[A", "previous-history"),
+    ("\\C-v", "quoted-insert"),
+    ("\\C-r", "reverse-search-history"),
+    ("\\C-t", "transpose-chars"),
+    ("\\C-u", "unix-line-discard"),
+    ("\\C-d", "vi-eof-maybe"),
+    ("\\C-_", "vi-undo"),
+    ("\\C-w", "vi-unix-word-rubout"),
+    ("\\C-y", "yank"),
+];
+
+const VI_COMMAND_BINDINGS: [(&str, &str); 31] = [
+    ("\\C-g", "abort"),
+    ("\\C-j", "accept-line"),
+    ("\\C-m", "accept-line"),
+    ("\\C-h", "backward-char"),
+    ("\\M-[D", "backward-char"),
+    ("\\M-[1;3D", "backward-word"),
+    ("\\M-[1;5D", "backward-word"),
+    ("\\M-[H", "beginning-of-line"),
+    ("\\C-l", "clear-screen"),
+    ("\\M-[3~", "delete-char"),
+    ("\\M-[F", "end-of-line"),
+    ("\\M-[C", "forward-char"),
+    ("\\C-s", "forward-search-history"),
+    ("\\M-[1;3C", "forward-word"),
+    ("\\M-[1;5C", "forward-word"),
+    ("\\M-[5~", "history-search-backward"),
+    ("\\M-[6~", "history-search-forward"),
+    ("\\C-k", "kill-line"),
+    ("\\C-n", "next-history"),
+    ("\\M-[B", "next-history"),
+    ("\\C-p", "previous-history"),
+    ("\\M-[A", "previous-history"),
+    ("\\C-q", "quoted-insert"),
+    ("\\C-v", "quoted-insert"),
+    ("\\C-r", "reverse-search-history"),
+    ("\\C-t", "transpose-chars"),
+    ("\\C-u", "unix-line-discard"),
+    ("\\C-d", "vi-eof-maybe"),
+    ("\\C-_", "vi-undo"),
+    ("\\C-w", "vi-unix-word-rubout"),
+    ("\\C-y", "yank"),
+];
+
+const BOOL_VARIABLES: [(&str, &str); 34] = [
+    ("bind-tty-special-chars", "on"),
+    ("blink-matching-paren", "off"),
+    ("byte-oriented", "off"),
+    ("colored-completion-prefix", "off"),
+    ("colored-stats", "off"),
+    ("completion-ignore-case", "off"),
+    ("completion-map-case", "off"),
+    ("convert-meta", "on"),
+    ("disable-completion", "off"),
+    ("echo-control-characters", "on"),
+    ("enable-active-region", "on"),
+    ("enable-bracketed-paste", "on"),
+    ("enable-keypad", "off"),
+    ("enable-meta-key", "on"),
+    ("expand-tilde", "off"),
+    ("history-preserve-point", "off"),
+    ("horizontal-scroll-mode", "off"),
+    ("input-meta", "on"),
+    ("mark-directories", "on"),
+    ("mark-modified-lines", "off"),
+    ("mark-symlinked-directories", "off"),
+    ("match-hidden-files", "on"),
+    ("menu-complete-display-prefix", "off"),
+    ("meta-flag", "on"),
+    ("output-meta", "on"),
+    ("page-completions", "on"),
+    ("prefer-visible-bell", "on"),
+    ("print-completions-horizontally", "off"),
+    ("revert-all-at-newline", "off"),
+    ("show-all-if-ambiguous", "off"),
+    ("show-all-if-unmodified", "off"),
+    ("show-mode-in-prompt", "off"),
+    ("skip-completed-text", "off"),
+    ("visible-stats", "off"),
+];
+
+const STRING_VARIABLES: [(&str, &str); 12] = [
+    ("bell-style", "audible"),
+    ("comment-begin", "#"),
+    ("completion-display-width", "-1"),
+    ("completion-prefix-display-length", "0"),
+    ("completion-query-items", "100"),
+    ("editing-mode", "emacs"),
+    ("emacs-mode-string", "@"),
+    ("history-size", "0"),
+    ("keymap", "emacs"),
+    ("keyseq-timeout", "500"),
+    ("vi-cmd-mode-string", "(cmd)"),
+    ("vi-ins-mode-string", "(ins)"),
+];
+
+const KEYMAP_NAMES: [&str; 8] = [
+    "emacs",
+    "emacs-standard",
+    "emacs-meta",
+    "emacs-ctlx",
+    "vi",
+    "vi-move",
+    "vi-command",
+    "vi-insert",
+];
+
+#[derive(Debug)]
+pub struct KeyMap {
+    /* emacs, vi-insert and vi-command */
+    pub maps: BTreeMap<String, BTreeMap<Vec<u8>, Binding>>,
+    pub variables: BTreeMap<String, String>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        let mut maps = BTreeMap::new();
+        for (name, table) in [
+            ("emacs", &EMACS_BINDINGS[..]),
+            ("vi-insert", &VI_INSERT_BINDINGS[..]),
+            ("vi-command", &VI_COMMAND_BINDINGS[..]),
+        ] {
+            let bindings = table
+                .iter()
+                .map(|(keys, f)| (translate(keys), Binding::Function(f.to_string())))
+                .collect();
+            maps.insert(name.to_string(), bindings);
+        }
+
+        let variables = BOOL_VARIABLES
+            .iter()
+            .chain(STRING_VARIABLES.iter())
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+
+        Self { maps, variables }
+    }
+}
+
+fn ctrl(b: u8) -> u8 {
+    match b {
+        b'?' => 0x7f,
+        b => b.to_ascii_uppercase() & 0x1f,
+    }
+}
+
+fn utf8(c: char) -> Vec<u8> {
+    c.to_string().into_bytes()
+}
+
+fn take_digits(chars: &[char], pos: &mut usize, radix: u32, max: usize) -> Option<u8> {
+    let mut s = String::new();
+    while s.len() < max && *pos < chars.len() && chars[*pos].is_digit(radix) {
+        s.push(chars[*pos]);
+        *pos += 1;
+    }
+    u32::from_str_radix(&s, radix).ok().map(|n| n as u8)
+}
+
+/* one key of a key sequence with the escapes of readline */
+fn translate_one(chars: &[char], pos: &mut usize) -> Vec<u8> {
+    let rest = &chars[*pos..];
+    if rest.len() > 3 && rest[0] == '\\' && rest[2] == '-' && (rest[1] == 'C' || rest[1] == 'M') {
+        *pos += 3;
+        let mut ans = translate_one(chars, pos);
+        match rest[1] {
+            'C' => {
+                if let Some(last) = ans.last_mut() {
+                    *last = ctrl(*last);
+                }
+            }
+            _ => ans.insert(0, 0x1b),
+        }
+        return ans;
+    }
+
+    *pos += 1;
+    if rest[0] != '\\' || rest.len() == 1 {
+        return utf8(rest[0]);
+    }
+
+    *pos += 1;
+    let b = match rest[1] {
+        'a' => 0x07,
+        'b' => 0x08,
+        'd' => 0x7f,
+        'e' => 0x1b,
+        'f' => 0x0c,
+        'n' => 0x0a,
+        'r' => 0x0d,
+        't' => 0x09,
+        'v' => 0x0b,
+        '0'..='7' => {
+            *pos -= 1;
+            take_digits(chars, pos, 8, 3).unwrap_or(0)
+        }
+        'x' => take_digits(chars, pos, 16, 2).unwrap_or(b'x'),
+        c => return utf8(c),
+    };
+    vec![b]
+}
+
+/* "\C-x\C-e" -> [0x18, 0x05] */
+pub fn translate(keyseq: &str) -> Vec<u8> {
+    let chars: Vec<char> = keyseq.chars().collect();
+    let mut pos = 0;
+    let mut ans = vec![];
+    while pos < chars.len() {
+        ans.extend(translate_one(&chars, &mut pos));
+    }
+    ans
+}
+
+/* [0x18, 0x05] -> "\C-x\C-e". A prefix of ESC is written as \M- */
+pub fn untranslate(keys: &[u8], meta: bool) -> String {
+    let mut ans: Vec<u8> = vec![];
+    for (i, b) in keys.iter().enumerate() {
+        match b {
+            0x1b if meta && i + 1 < keys.len() => ans.extend(b"\\M-"),
+            0x1b => ans.extend(b"\\e"),
+            0x7f => ans.extend(b"\\C-?"),
+            b'\\' | b'"' => ans.extend([b'\\', *b]),
+            0..=0x1f => {
+                ans.extend(b"\\C-");
+                ans.push((b + 0x40).to_ascii_lowercase());
+            }
+            b => ans.push(*b),
+        }
+    }
+    String::from_utf8_lossy(&ans).to_string()
+}
+
+/* Control-u, Meta-Rubout, C-x, ... */
+fn translate_keyname(name: &str) -> Vec<u8> {
+    let lower = name.to_ascii_lowercase();
+    for (prefix, is_ctrl) in [
+        ("control-", true),
+        ("c-", true),
+        ("meta-", false),
+        ("m-", false),
+    ] {
+        if lower.starts_with(prefix) && name.len() > prefix.len() {
+            let mut ans = translate_keyname(&name[prefix.len()..]);
+            match is_ctrl {
+                true => {
+                    if let Some(last) = ans.last_mut() {
+                        *last = ctrl(*last);
+                    }
+                }
+                false => ans.insert(0, 0x1b),
+            }
+            return ans;
+        }
+    }
+
+    match lower.as_str() {
+        "rubout" | "del" => vec![0x7f],
+        "escape" | "esc" => vec![0x1b],
+        "lfd" | "newline" => vec![b'\n'],
+        "ret" | "return" => vec![b'\r'],
+        "space" | "spc" => vec![b' '],
+        "tab" => vec![b'\t'],
+        _ => translate(name),
+    }
+}
+
+/* the position of the closing quote */
+fn closing_quote(chars: &[char], quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in chars.iter().enumerate().skip(1) {
+        match (escaped, *c) {
+            (false, '\\') => escaped = true,
+            (false, c) if c == quote => return Some(i),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+/* splits `"keyseq": value` or `keyname: value` */
+pub fn split_binding(line: &str) -> Result<(Vec<u8>, String), String> {
+    let chars: Vec<char> = line.chars().collect();
+    if chars.first() == Some(&'"') {
+        let end = match closing_quote(&chars, '"') {
+            Some(e) => e,
+            None => return Err(format!("{}: no closing `\"' in key binding", line)),
+        };
+        let keyseq: String = chars[1..end].iter().collect();
+        let rest: String = chars[end + 1..].iter().collect();
+        return match rest.trim_start().strip_prefix(':') {
+            Some(value) => Ok((translate(&keyseq), value.trim().to_string())),
+            None => Err(format!("\"{}\": no key sequence terminator", keyseq)),
+        };
+    }
+
+    match line.split_once(':') {
+        Some((name, value)) => Ok((translate_keyname(name.trim()), value.trim().to_string())),
+        None => Err(format!("{}: no key sequence terminator", line)),
+    }
+}
+
+/* a function name or a quoted macro */
+pub fn parse_value(value: &str) -> Binding {
+    let chars: Vec<char> = value.chars().collect();
+    match chars.first() {
+        Some(q) if *q == '"' || *q == '\'' => {
+            let end = closing_quote(&chars, *q).unwrap_or(chars.len());
+            let text: String = chars[1..end].iter().collect();
+            Binding::Macro(translate(&text))
+        }
+        _ => {
+            let name = value.split_whitespace().next().unwrap_or("");
+            Binding::Function(name.to_string())
+        }
+    }
+}
+
+pub fn is_keymap_name(name: &str) -> bool {
+    KEYMAP_NAMES.contains(&name)
+}
+
+/* emacs-meta and emacs-ctlx are the parts of emacs after ESC and C-x */
+pub fn resolve(name: &str) -> (&'static str, Vec<u8>) {
+    match name {
+        "emacs-meta" => ("emacs", vec![0x1b]),
+        "emacs-ctlx" => ("emacs", vec![0x18]),
+        "vi" | "vi-move" | "vi-command" => ("vi-command", vec![]),
+        "vi-insert" => ("vi-insert", vec![]),
+        _ => ("emacs", vec![]),
+    }
+}
+
+impl KeyMap {
+    pub fn is_bool_variable(name: &str) -> bool {
+        BOOL_VARIABLES.iter().any(|v| v.0 == name)
+    }
+
+    pub fn bool_variables(&self) -> Vec<(&String, &String)> {
+        self.variables
+            .iter()
+            .filter(|v| Self::is_bool_variable(v.0))
+            .collect()
+    }
+
+    pub fn string_variables(&self) -> Vec<(&String, &String)> {
+        self.variables
+            .iter()
+            .filter(|v| !Self::is_bool_variable(v.0))
+            .collect()
+    }
+
+    pub fn bindings(&self, map: &str) -> &BTreeMap<Vec<u8>, Binding> {
+        &self.maps[map]
+    }
+
+    pub fn bindings_mut(&mut self, map: &str) -> &mut BTreeMap<Vec<u8>, Binding> {
+        self.maps.entry(map.to_string()).or_default()
+    }
+
+    /* the map and the key prefix for the keymap variable */
+    pub fn target(&self) -> (&'static str, Vec<u8>) {
+        let name = self.variables.get("keymap").map(|s| s.as_str());
+        resolve(name.unwrap_or("emacs"))
+    }
+
+    /* whether more keys are needed to determine the binding */
+    pub fn is_prefix(&self, map: &str, keys: &[u8]) -> bool {
+        self.bindings(map)
+            .range(keys.to_vec()..)
+            .find(|(k, _)| k.as_slice() != keys)
+            .is_some_and(|(k, _)| k.starts_with(keys))
+    }
+
+    pub fn is_bound(&self, map: &str, keys: &[u8]) -> bool {
+        self.bindings(map).contains_key(keys) || self.is_prefix(map, keys)
+    }
+
+    pub fn keys_of(&self, map: &str, function: &str) -> Vec<String> {
+        let f = Binding::Function(function.to_string());
+        self.bindings(map)
+            .iter()
+            .filter(|(_, b)| **b == f)
+            .map(|(k, _)| format!("\"{}\"", untranslate(k, true)))
+            .collect()
+    }
+
+    pub fn unbind_function(&mut self, map: &str, function: &str) {
+        let f = Binding::Function(function.to_string());
+        self.bindings_mut(map).retain(|_, b| *b != f);
+    }
+
+    pub fn set_variable(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let value = match (
+            Self::is_bool_variable(name),
+            self.variables.contains_key(name),
+        ) {
+            (_, false) => return Err(format!("{}: unknown variable name", name)),
+            (true, _) => match value.is_empty() || value.eq_ignore_ascii_case("on") || value == "1"
+            {
+                true => "on",
+                false => "off",
+            },
+            (false, _) if name == "bell-style" && value.is_empty() => "audible",
+            (false, _) => value,
+        };
+        self.variables.insert(name.to_string(), value.to_string());
+        Ok(())
+    }
+}
+
+impl ShellCore {
+    /* a line of inputrc: a variable setting or a key binding */
+    pub fn parse_and_bind(&mut self, line: &str) -> Result<(), String> {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("set") {
+            if rest.starts_with([' ', '\t']) {
+                let mut words = rest.split_whitespace();
+                let name = words.next().unwrap_or("");
+                let value = words.next().unwrap_or("");
+                if name == "keymap" && !is_keymap_name(value) {
+                    return Ok(());
+                }
+                self.keymap.set_variable(name, value)?;
+                if name == "editing-mode" && (value == "vi" || value == "emacs") {
+                    let _ = self.options.set(value, true);
+                    self.set_keymap(None);
+                }
+                return Ok(());
+            }
+        }
+
+        let (keys, value) = split_binding(line)?;
+        let (map, prefix) = self.keymap.target();
+        let keys = [prefix, keys].concat();
+        match parse_value(&value) {
+            Binding::Function(f) if !FUNCTIONS.contains(&f.as_str()) => {
+                self.keymap.bindings_mut(map).remove(&keys);
+            }
+            b => {
+                self.keymap.bindings_mut(map).insert(keys, b);
+            }
+        }
+        Ok(())
+    }
+
+    /* the keymap of the editing mode unless a name is given */
+    pub fn set_keymap(&mut self, name: Option<&str>) {
+        let name = name.unwrap_or(match self.options.query("vi") {
+            true => "vi-insert",
+            false => "emacs",
+        });
+        self.keymap
+            .variables
+            .insert("keymap".to_string(), name.to_string());
+    }
+
+    fn inputrc_condition(&mut self, cond: &str) -> bool {
+        let cond = cond.trim();
+        match cond.split_once('=') {
+            Some(("mode", m)) => self.editing_mode() == m.trim(),
+            Some(("term", t)) => {
+                let term = self.db.get_param("TERM").unwrap_or_default();
+                let t = t.trim();
+                term == t || term.split('-').next() == Some(t)
+            }
+            Some((name, value)) => {
+                self.keymap.variables.get(name.trim()).map(|s| s.as_str()) == Some(value.trim())
+            }
+            None => cond == "Bash",
+        }
+    }
+
+    pub fn read_inputrc(&mut self, path: &str) -> Result<(), String> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(t) => t,
+            Err(e) => {
+                let errno = Errno::from_raw(e.raw_os_error().unwrap_or(0));
+                return Err(format!("{}: cannot read: {}", path, errno.desc()));
+            }
+        };
+
+        let mut skips: Vec<bool> = vec![];
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let skip = skips.iter().any(|s| *s);
+            if let Some(directive) = line.strip_prefix('$') {
+                let (name, arg) = directive.split_once([' ', '\t']).unwrap_or((directive, ""));
+                match name {
+                    "if" => skips.push(!self.inputrc_condition(arg)),
+                    "else" => {
+                        if let Some(s) = skips.last_mut() {
+                            *s = !*s;
+                        }
+                    }
+                    "endif" => {
+                        skips.pop();
+                    }
+                    "include" if !skip => {
+                        let file = arg.trim();
+                        let file = match file.strip_prefix("~/") {
+                            Some(f) => {
+                                format!("{}/{}", self.db.get_param("HOME").unwrap_or_default(), f)
+                            }
+                            None => file.to_string(),
+                        };
+                        if let Err(e) = self.read_inputrc(&file) {
+                            eprintln!("readline: {}", e);
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            if skip {
+                continue;
+            }
+            if let Err(e) = self.parse_and_bind(line) {
+                eprintln!("readline: {}: line {}: {}", path, n + 1, e);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn editing_mode(&self) -> &str {
+        match self.options.query("vi") {
+            true => "vi",
+            false => "emacs",
+        }
+    }
+
+    pub fn inputrc_path(&mut self) -> String {
+        if let Ok(path) = self.db.get_param("INPUTRC") {
+            if !path.is_empty() {
+                return path;
+            }
+        }
+        let home = format!("{}/.inputrc", self.db.get_param("HOME").unwrap_or_default());
+        match std::path::Path::new(&home).is_file() {
+            true => home,
+            false => "/etc/inputrc".to_string(),
+        }
+    }
+
+    pub fn load_inputrc(&mut self) {
+        let path = self.inputrc_path();
+        if std::path::Path::new(&path).is_file() {
+            self.set_keymap(None);
+            let _ = self.read_inputrc(&path);
+        }
+    }
+}
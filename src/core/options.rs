@@ -13,12 +13,18 @@ pub struct Options {
 impl Options {
     pub fn new_as_basic_opts() -> Options {
         let mut options = Options::default();
+        options.opts.insert("emacs".to_string(), false);
+        options.opts.insert("errexit".to_string(), false);
+        options.opts.insert("errtrace".to_string(), false);
+        options.opts.insert("functrace".to_string(), false);
+        options.opts.insert("histexpand".to_string(), false);
         options.opts.insert("pipefail".to_string(), false);
         options.opts.insert("monitor".to_string(), true);
         options.opts.insert("noclobber".to_string(), false);
         options.opts.insert("noglob".to_string(), false);
         options.opts.insert("onecmd".to_string(), false);
         options.opts.insert("posix".to_string(), false);
+        options.opts.insert("vi".to_string(), false);
         options.opts.insert("history".to_string(), false); //TODO: still dummy
         options
     }
@@ -54,6 +60,7 @@ impl Options {
             "histverify",
             "hostcomplete",
             "huponexit",
+            "inherit_errexit",
             "interactive_comments",
             "lastpipe",
             "lithist",
@@ -75,7 +82,13 @@ impl Options {
             options.opts.insert(opt.to_string(), false);
         }
 
-        let true_list = ["extglob", "progcomp", "globskipdots"];
+        let true_list = [
+            "extglob",
+            "progcomp",
+            "globskipdots",
+            "promptvars",
+            "cmdhist",
+        ];
         for opt in true_list {
             options.opts.insert(opt.to_string(), true);
         }
@@ -93,6 +106,17 @@ impl Options {
             "lastpipe",
             "execfail",
             "assoc_expand_once",
+            "inherit_errexit",
+            "extdebug",
+            "histverify",
+            "promptvars",
+            "cmdhist",
+            "lithist",
+            "histappend",
+            "autocd",
+            "cdable_vars",
+            "cdspell",
+            "dirspell",
         ]
         .iter()
         .map(|s| s.to_string())
@@ -182,6 +206,10 @@ impl Options {
         }
 
         self.opts.insert(opt.to_string(), onoff);
+        if onoff && (opt == "vi" || opt == "emacs") {
+            let other = if opt == "vi" { "emacs" } else { "vi" };
+            self.opts.insert(other.to_string(), false); // editing modes are exclusive
+        }
 
         Ok(())
     }
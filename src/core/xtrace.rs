@@ -0,0 +1,154 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::elements::subword;
+use crate::elements::subword::filler::FillerSubword;
+use crate::elements::word::{Word, WordMode};
+use crate::{Feeder, ShellCore};
+use nix::fcntl;
+use std::fs::File;
+use std::io::Write;
+use std::mem::ManuallyDrop;
+use std::os::fd::{FromRawFd, RawFd};
+
+fn needs_single_quote(s: &str) -> bool {
+    let mut prev = ' ';
+    for (i, c) in s.chars().enumerate() {
+        if " \t\n'\"\\|&;()<>!{}*[?]^$`".contains(c)
+            || (c == '~' && (i == 0 || prev == '=' || prev == ':'))
+            || (c == '#' && i == 0)
+        {
+            return true;
+        }
+        prev = c;
+    }
+    false
+}
+
+pub fn ansi_c_quote(s: &str) -> String {
+    let mut ans = "$'".to_string();
+    for c in s.chars() {
+        match c {
+            '\x07' => ans += "\\a",
+            '\x08' => ans += "\\b",
+            '\x1b' => ans += "\\E",
+            '\x0c' => ans += "\\f",
+            '\n' => ans += "\\n",
+            '\r' => ans += "\\r",
+            '\t' => ans += "\\t",
+            '\x0b' => ans += "\\v",
+            '\\' | '\'' => {
+                ans.push('\\');
+                ans.push(c);
+            }
+            c if c.is_ascii_control() => ans += &format!("\\{:03o}", c as u8),
+            c => ans.push(c),
+        }
+    }
+    ans + "'"
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuoteStyle {
+    IfNeeded,  // the xtrace output
+    Always,    // in single quotes
+    Backslash, // printf %q
+}
+
+fn backslash_quote(s: &str) -> String {
+    let mut ans = String::new();
+    let mut prev = None;
+    for (i, c) in s.chars().enumerate() {
+        if " \t\n!\"$&'()*,;<>?[\\]^`{|}".contains(c)
+            || (c == '#' && i == 0)
+            || (c == '~' && (i == 0 || prev == Some('=') || prev == Some(':')))
+        {
+            ans.push('\\');
+        }
+        ans.push(c);
+        prev = Some(c);
+    }
+    ans
+}
+
+/* quotes a word so that the parser reads it as the original string */
+pub fn quote(s: &str, style: QuoteStyle) -> String {
+    let single_quote = |s: &str| format!("'{}'", s.replace('\'', "'\\''"));
+    match style {
+        QuoteStyle::Always => single_quote(s),
+        _ if s.is_empty() => "''".to_string(),
+        QuoteStyle::IfNeeded
+            if s.chars()
+                .any(|c| c.is_ascii_control() && c != '\t' && c != '\n') =>
+        {
+            ansi_c_quote(s)
+        }
+        QuoteStyle::IfNeeded if needs_single_quote(s) => single_quote(s),
+        QuoteStyle::IfNeeded => s.to_string(),
+        QuoteStyle::Backslash if s.chars().any(|c| c.is_control()) => ansi_c_quote(s),
+        QuoteStyle::Backslash => backslash_quote(s),
+    }
+}
+
+impl ShellCore {
+    pub fn expand_prompt(&mut self, ps4: &str) -> String {
+        let mut feeder = Feeder::new(ps4);
+        let mut word = Word::default();
+        let mode = Some(WordMode::Heredoc);
+
+        while !feeder.is_empty() {
+            match subword::parse(&mut feeder, self, &mode) {
+                Ok(Some(mut sw)) => {
+                    sw.set_heredoc_flag();
+                    word.text += sw.get_text();
+                    word.subwords.push(sw);
+                }
+                Ok(None) => {
+                    let len = feeder.scanner_char();
+                    let text = feeder.consume(len);
+                    word.text += &text;
+                    word.subwords.push(Box::new(FillerSubword { text }));
+                }
+                Err(_) => return ps4.to_string(),
+            }
+        }
+
+        let flags = self.db.flags.clone();
+        self.db.flags.retain(|f| f != 'x');
+        let ans = word.eval_as_value(self).unwrap_or(ps4.to_string());
+        self.db.flags = flags;
+        ans
+    }
+
+    pub fn get_ps4(&mut self) -> String {
+        let ps4 = self.db.get_param("PS4").unwrap_or_default();
+        let ps4 = self.expand_prompt(&ps4);
+
+        let first = match ps4.chars().next() {
+            Some(c) => c,
+            None => return ps4,
+        };
+
+        let level = self.source_files.len() + self.eval_level as usize;
+        first.to_string().repeat(level) + &ps4
+    }
+
+    fn xtrace_fd(&mut self) -> RawFd {
+        let fd = match self.db.get_param("BASH_XTRACEFD") {
+            Ok(s) => s.parse::<RawFd>().unwrap_or(2),
+            Err(_) => 2,
+        };
+
+        match fd >= 0 && fcntl::fcntl(fd, fcntl::F_GETFD).is_ok() {
+            true => fd,
+            false => 2,
+        }
+    }
+
+    pub fn print_xtrace(&mut self, line: &str) {
+        let ps4 = self.get_ps4();
+        let fd = self.xtrace_fd();
+        let mut f = ManuallyDrop::new(unsafe { File::from_raw_fd(fd) });
+        let _ = f.write_all(format!("{}{}\n", ps4, line).as_bytes());
+    }
+}
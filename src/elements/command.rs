@@ -4,11 +4,13 @@
 pub mod arithmetic;
 pub mod brace;
 pub mod case;
+pub mod coproc;
 pub mod r#for;
 pub mod function_def;
 pub mod r#if;
 pub mod paren;
 pub mod repeat;
+pub mod select;
 pub mod simple;
 pub mod test;
 pub mod r#while;
@@ -16,15 +18,18 @@ pub mod r#while;
 use self::arithmetic::ArithmeticCommand;
 use self::brace::BraceCommand;
 use self::case::CaseCommand;
+use self::coproc::CoprocCommand;
 use self::function_def::FunctionDefinition;
 use self::paren::ParenCommand;
 use self::r#for::ForCommand;
 use self::r#if::IfCommand;
 use self::r#while::WhileCommand;
 use self::repeat::RepeatCommand;
+use self::select::SelectCommand;
 use self::simple::SimpleCommand;
 use self::test::TestCommand;
 use super::io::redirect::Redirect;
+use super::word::Word;
 use super::{io, Pipe};
 use crate::error::exec::ExecError;
 use crate::error::parse::ParseError;
@@ -113,12 +118,23 @@ pub trait Command {
         self.get_text().replace("\n", " ")
     }
     fn get_redirects(&mut self) -> &mut Vec<Redirect>;
+    /* the words and the inner scripts for syntax highlighting */
+    fn get_words(&self) -> Vec<&Word> {
+        vec![]
+    }
+    fn get_scripts(&mut self) -> Vec<&mut Script> {
+        vec![]
+    }
     fn get_lineno(&mut self) -> usize {
         panic!("IMPLEMENT!!")
     }
     fn set_force_fork(&mut self);
     fn boxed_clone(&self) -> Box<dyn Command>;
     fn force_fork(&self) -> bool;
+    /* true if failures are checked with the commands inside (set -e, ERR trap) */
+    fn is_compound(&self) -> bool {
+        false
+    }
 
     fn read_heredoc(
         &mut self,
@@ -258,12 +274,16 @@ pub fn parse(
         Ok(Some(Box::new(a)))
     } else if let Some(a) = WhileCommand::parse(feeder, core)? {
         Ok(Some(Box::new(a)))
+    } else if let Some(a) = SelectCommand::parse(feeder, core)? {
+        Ok(Some(Box::new(a)))
     } else if let Some(a) = RepeatCommand::parse(feeder, core)? {
         Ok(Some(Box::new(a)))
     } else if let Some(a) = CaseCommand::parse(feeder, core)? {
         Ok(Some(Box::new(a)))
     } else if let Some(a) = TestCommand::parse(feeder, core)? {
         Ok(Some(Box::new(a)))
+    } else if let Some(a) = CoprocCommand::parse(feeder, core)? {
+        Ok(Some(Box::new(a)))
     } else {
         Ok(None)
     }
@@ -5,7 +5,7 @@ use super::{Command, Redirect};
 use crate::elements::expr::arithmetic::ArithmeticExpr;
 use crate::error::exec::ExecError;
 use crate::error::parse::ParseError;
-use crate::{Feeder, ShellCore};
+use crate::{signal, Feeder, ShellCore};
 
 #[derive(Debug, Clone, Default)]
 pub struct ArithmeticCommand {
@@ -18,9 +18,16 @@ pub struct ArithmeticCommand {
 
 impl Command for ArithmeticCommand {
     fn run(&mut self, core: &mut ShellCore, _: bool) -> Result<(), ExecError> {
+        if signal::skip_by_debug_trap(core, &self.text) {
+            if !core.return_flag {
+                core.db.exit_status = 0;
+            }
+            return Ok(());
+        }
+
         let mut err = None;
 
-        let exit_status = match self.eval(core) {
+        let exit_status = match self.eval_as_command(core) {
             Ok(n) => {
                 if n == "0" {
                     1
@@ -85,6 +92,15 @@ impl ArithmeticCommand {
         Ok(ans)
     }
 
+    fn eval_as_command(&mut self, core: &mut ShellCore) -> Result<String, ExecError> {
+        let mut ans = String::new();
+        for a in &mut self.expressions {
+            ans = a.eval_as_command(core)?;
+        }
+
+        Ok(ans)
+    }
+
     pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Result<Option<Self>, ParseError> {
         if !feeder.starts_with("((") {
             return Ok(None);
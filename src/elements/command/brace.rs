@@ -32,6 +32,10 @@ impl Command for BraceCommand {
     fn get_redirects(&mut self) -> &mut Vec<Redirect> {
         &mut self.redirects
     }
+
+    fn get_scripts(&mut self) -> Vec<&mut Script> {
+        self.script.iter_mut().collect()
+    }
     fn get_lineno(&mut self) -> usize {
         self.lineno
     }
@@ -44,6 +48,9 @@ impl Command for BraceCommand {
     fn force_fork(&self) -> bool {
         self.force_fork
     }
+    fn is_compound(&self) -> bool {
+        true
+    }
 
     fn pretty_print(&mut self, indent_num: usize) {
         println!("{{ ");
@@ -7,7 +7,7 @@ use crate::elements::word::Word;
 use crate::error::exec::ExecError;
 use crate::error::parse::ParseError;
 use crate::utils::glob;
-use crate::{Feeder, Script, ShellCore};
+use crate::{signal, Feeder, Script, ShellCore};
 
 #[derive(Debug, Clone, Default)]
 pub struct CaseCommand {
@@ -27,8 +27,13 @@ impl Command for CaseCommand {
         let word = self.word.clone().unwrap();
 
         if core.db.flags.contains('x') {
-            let ps4 = core.get_ps4();
-            eprintln!("\r{} case {} in\r", ps4, word.text);
+            core.print_xtrace(&format!("case {} in", word.text));
+        }
+        if signal::skip_by_debug_trap(core, &format!("case {} in ", word.text)) {
+            if !core.return_flag {
+                core.db.exit_status = 0;
+            }
+            return Ok(());
         }
 
         let w = match word.eval_for_case_word(core) {
@@ -72,6 +77,13 @@ impl Command for CaseCommand {
     fn get_redirects(&mut self) -> &mut Vec<Redirect> {
         &mut self.redirects
     }
+
+    fn get_scripts(&mut self) -> Vec<&mut Script> {
+        self.patterns_script_end
+            .iter_mut()
+            .map(|p| &mut p.1)
+            .collect()
+    }
     fn get_lineno(&mut self) -> usize {
         self.lineno
     }
@@ -84,6 +96,9 @@ impl Command for CaseCommand {
     fn force_fork(&self) -> bool {
         self.force_fork
     }
+    fn is_compound(&self) -> bool {
+        true
+    }
 }
 
 impl CaseCommand {
@@ -0,0 +1,221 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use super::{Command, Pipe, Redirect};
+use crate::core::jobtable::JobEntry;
+use crate::elements::{command, io};
+use crate::error::exec::ExecError;
+use crate::error::parse::ParseError;
+use crate::utils::exit;
+use crate::{proc_ctrl, utils, Feeder, Script, ShellCore};
+use nix::sys::wait::WaitStatus;
+use nix::unistd::{ForkResult, Pid};
+use nix::{fcntl, unistd};
+use std::os::fd::{IntoRawFd, RawFd};
+
+#[derive(Debug, Clone, Default)]
+pub struct CoprocCommand {
+    text: String,
+    name: String,
+    command: Option<Box<dyn Command>>,
+    lineno: usize,
+    _dummy: Vec<Redirect>,
+}
+
+impl Command for CoprocCommand {
+    fn exec(&mut self, core: &mut ShellCore, pipe: &mut Pipe) -> Result<Option<Pid>, ExecError> {
+        core.db
+            .set_param("LINENO", &self.lineno.to_string(), None)?;
+        if !utils::is_name(&self.name, core) {
+            core.db.exit_status = 1;
+            return Err(ExecError::InvalidName(self.name.clone()));
+        }
+
+        let (child_in, parent_out) = unistd::pipe()?;
+        let (parent_in, child_out) = unistd::pipe()?;
+        let child_in = child_in.into_raw_fd();
+        let parent_out = parent_out.into_raw_fd();
+        let parent_in = parent_in.into_raw_fd();
+        let child_out = child_out.into_raw_fd();
+
+        let backup = core.tty_fd.as_ref().map(|fd| fd.try_clone().unwrap());
+        core.tty_fd = None;
+
+        let result = match unsafe { unistd::fork() } {
+            Ok(ForkResult::Child) => {
+                core.initialize_as_subshell(Pid::from_raw(0), pipe.pgid);
+                io::close(parent_in, "Cannot close coproc pipe");
+                io::close(parent_out, "Cannot close coproc pipe");
+                io::replace(child_in, 0);
+                io::replace(child_out, 1);
+
+                if let Err(e) = self.run(core, true) {
+                    e.print(core);
+                    core.db.exit_status = 1;
+                }
+                exit::normal(core)
+            }
+            Ok(ForkResult::Parent { child }) => {
+                proc_ctrl::set_pgid(core, child, pipe.pgid);
+                io::close(child_in, "Cannot close coproc pipe");
+                io::close(child_out, "Cannot close coproc pipe");
+                self.set_parent(core, child, parent_in, parent_out)
+            }
+            Err(e) => Err(ExecError::from(e)),
+        };
+
+        core.tty_fd = backup;
+        result?;
+        core.db.exit_status = 0;
+        Ok(None)
+    }
+
+    fn run(&mut self, core: &mut ShellCore, _: bool) -> Result<(), ExecError> {
+        let command = match self.command.as_mut() {
+            Some(c) => c,
+            None => exit::internal(" (CoprocCommand::run)"),
+        };
+
+        let mut pipe = Pipe::end(-1, unistd::getpgrp(), false);
+        let pid = command.exec(core, &mut pipe)?;
+        proc_ctrl::wait_pipeline(core, vec![pid], false, false);
+        Ok(())
+    }
+
+    fn get_text(&self) -> String {
+        self.text.clone()
+    }
+    fn get_redirects(&mut self) -> &mut Vec<Redirect> {
+        &mut self._dummy
+    }
+
+    fn get_scripts(&mut self) -> Vec<&mut Script> {
+        match self.command.as_mut() {
+            Some(c) => c.get_scripts(),
+            None => vec![],
+        }
+    }
+    fn get_lineno(&mut self) -> usize {
+        self.lineno
+    }
+    fn set_force_fork(&mut self) {}
+    fn boxed_clone(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+    fn force_fork(&self) -> bool {
+        false
+    }
+}
+
+fn move_to_high_fd(fd: RawFd) -> Result<RawFd, ExecError> {
+    let ans = fcntl::fcntl(fd, fcntl::F_DUPFD_CLOEXEC(60))?;
+    io::close(fd, "Cannot close coproc pipe");
+    Ok(ans)
+}
+
+impl CoprocCommand {
+    fn set_parent(
+        &mut self,
+        core: &mut ShellCore,
+        child: Pid,
+        parent_in: RawFd,
+        parent_out: RawFd,
+    ) -> Result<(), ExecError> {
+        let fds = vec![move_to_high_fd(parent_in)?, move_to_high_fd(parent_out)?];
+        let values = fds.iter().map(|fd| fd.to_string()).collect();
+
+        core.db.set_array(&self.name, Some(values), None)?;
+        core.db
+            .set_param(&(self.name.clone() + "_PID"), &child.to_string(), None)?;
+        core.db.set_param("!", &child.to_string(), None)?;
+
+        let new_job_id = core.generate_new_job_id();
+        core.job_table_priority.insert(0, new_job_id);
+        let text = format!("coproc {} {}", &self.name, &self.get_command_text());
+        let mut entry = JobEntry::new(
+            vec![Some(child)],
+            &[WaitStatus::StillAlive],
+            &text,
+            "Running",
+            new_job_id,
+        );
+
+        if !core.options.query("monitor") {
+            entry.no_control = true;
+        }
+        entry.coproc = Some((self.name.clone(), fds));
+        core.job_table.push(entry);
+
+        if core.db.flags.contains('i') {
+            eprintln!("[{}] {}", new_job_id, child);
+        }
+        Ok(())
+    }
+
+    fn get_command_text(&self) -> String {
+        match &self.command {
+            Some(c) => c.get_one_line_text().trim_end().to_string(),
+            None => String::new(),
+        }
+    }
+
+    fn starts_with_compound(feeder: &mut Feeder, core: &mut ShellCore) -> bool {
+        if feeder.starts_withs(&["{", "(", "[["]) {
+            return true;
+        }
+
+        let len = feeder.scanner_name(core);
+        len > 0 && utils::reserved(feeder.refer(len))
+    }
+
+    fn eat_name(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) {
+        ans.name = "COPROC".to_string();
+
+        let len = feeder.scanner_plain_word();
+        if len == 0 || utils::reserved(feeder.refer(len)) {
+            return;
+        }
+
+        feeder.set_backup();
+        let name = feeder.consume(len);
+        let mut blank = String::new();
+        command::eat_blank_with_comment(feeder, core, &mut blank);
+
+        if blank.is_empty() || !Self::starts_with_compound(feeder, core) {
+            feeder.rewind();
+            return;
+        }
+
+        feeder.pop_backup();
+        ans.text += &name;
+        ans.text += &blank;
+        ans.name = name;
+    }
+
+    pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Result<Option<Self>, ParseError> {
+        if !feeder.starts_with("coproc") {
+            return Ok(None);
+        }
+
+        let mut ans = Self {
+            lineno: feeder.lineno,
+            text: feeder.consume(6),
+            ..Default::default()
+        };
+
+        if !command::eat_blank_with_comment(feeder, core, &mut ans.text) {
+            return Err(ParseError::UnexpectedSymbol("coproc".to_string()));
+        }
+
+        Self::eat_name(feeder, &mut ans, core);
+
+        match command::parse(feeder, core)? {
+            Some(c) => {
+                ans.text += &c.get_text();
+                ans.command = Some(c);
+                Ok(Some(ans))
+            }
+            None => Err(ParseError::UnexpectedSymbol("coproc".to_string())),
+        }
+    }
+}
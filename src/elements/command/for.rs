@@ -1,7 +1,7 @@
 //SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::{Feeder, Script, ShellCore};
+use crate::{signal, Feeder, Script, ShellCore};
 
 use super::{Command, Redirect};
 use crate::elements::command;
@@ -51,6 +51,10 @@ impl Command for ForCommand {
     fn get_redirects(&mut self) -> &mut Vec<Redirect> {
         &mut self.redirects
     }
+
+    fn get_scripts(&mut self) -> Vec<&mut Script> {
+        self.do_script.iter_mut().collect()
+    }
     fn get_lineno(&mut self) -> usize {
         self.lineno
     }
@@ -63,9 +67,22 @@ impl Command for ForCommand {
     fn force_fork(&self) -> bool {
         self.force_fork
     }
+    fn is_compound(&self) -> bool {
+        true
+    }
 }
 
 impl ForCommand {
+    fn header_text(&self) -> String {
+        match self.has_in {
+            true => {
+                let values: Vec<&str> = self.values.iter().map(|w| w.text.as_str()).collect();
+                format!("for {} in {}", &self.name, values.join(" "))
+            }
+            false => format!("for {} in \"$@\"", &self.name),
+        }
+    }
+
     fn eval_values(&mut self, core: &mut ShellCore) -> Option<Vec<String>> {
         let mut ans = vec![];
         for w in &mut self.values {
@@ -98,7 +115,18 @@ impl ForCommand {
                 return false;
             }
 
-            if let Err(e) = core.db.set_param(&self.name, &p, None) {
+            if core.db.flags.contains('x') {
+                core.print_xtrace(&self.header_text());
+            }
+            if signal::skip_by_debug_trap(core, &self.header_text()) {
+                continue;
+            }
+
+            let res = match core.db.is_nameref(&self.name) {
+                true => core.db.set_nameref(&self.name, &p, None),
+                false => core.db.set_param(&self.name, &p, None),
+            };
+            if let Err(e) = res {
                 core.db.exit_status = 1;
                 e.print(core);
                 //                let msg = format!("{:?}", &e);
@@ -122,10 +150,20 @@ impl ForCommand {
         true
     }
 
+    /* an empty expression is 1, and a skipped one by the DEBUG trap is 0 */
     fn eval_arithmetic(a: &mut Option<ArithmeticExpr>, core: &mut ShellCore) -> (bool, String) {
-        match a {
+        let arith = a.as_mut().filter(|arith| !arith.text.trim().is_empty());
+        let text = match &arith {
+            Some(arith) => format!("(({}))", arith.text.trim_start()),
+            None => "((1))".to_string(),
+        };
+        if signal::skip_by_debug_trap(core, &text) {
+            return (true, "0".to_string());
+        }
+
+        match arith {
             None => (true, "1".to_string()),
-            Some(ref mut arith) => match arith.eval(core) {
+            Some(arith) => match arith.eval_as_command(core) {
                 Ok(n) => (true, n),
                 _ => (false, "0".to_string()),
             },
@@ -7,7 +7,7 @@ use crate::elements::command::{BraceCommand, IfCommand, ParenCommand, WhileComma
 use crate::error::exec::ExecError;
 use crate::error::parse::ParseError;
 use crate::utils;
-use crate::{Feeder, ShellCore};
+use crate::{signal, Feeder, Script, ShellCore};
 use nix::unistd::Pid;
 
 #[derive(Debug, Clone, Default)]
@@ -26,6 +26,7 @@ impl Command for FunctionDefinition {
         core.db
             .functions
             .insert(self.name.to_string(), self.clone());
+        core.db.exit_status = 0;
         Ok(None)
     }
 
@@ -38,6 +39,13 @@ impl Command for FunctionDefinition {
     fn get_redirects(&mut self) -> &mut Vec<Redirect> {
         &mut self._dummy
     }
+
+    fn get_scripts(&mut self) -> Vec<&mut Script> {
+        match self.command.as_mut() {
+            Some(c) => c.get_scripts(),
+            None => vec![],
+        }
+    }
     fn get_lineno(&mut self) -> usize {
         self.lineno
     }
@@ -65,31 +73,46 @@ impl FunctionDefinition {
     }
 
     pub fn run_as_command(&mut self, args: &mut [String], core: &mut ShellCore) {
-        let mut array = core.db.get_vec("FUNCNAME", false).unwrap();
-        array.insert(0, args[0].clone()); //TODO: We must put the name not only in 0 but also 1..
-        let _ = core.db.set_array("FUNCNAME", Some(array.clone()), None);
-        let mut source = core.db.get_vec("BASH_SOURCE", false).unwrap();
-        source.insert(0, self.file.clone());
-        let _ = core.db.set_array("BASH_SOURCE", Some(source.clone()), None);
+        core.push_call_frame(&args[0], &self.file, true);
 
         args[0] = core.db.position_parameters[0][0].clone();
         core.db.position_parameters.push(args.to_vec());
 
         let mut dummy = Pipe::new("|".to_string());
 
+        let lineno = core.db.get_param("LINENO").unwrap_or_default();
+        let mut err_script = String::new();
+        if !core.inherits_err_trap() {
+            err_script = std::mem::take(&mut core.err_script);
+        }
+        let (mut debug_script, mut return_script) = (String::new(), String::new());
+        if !core.inherits_debug_return_traps() {
+            debug_script = std::mem::take(&mut core.debug_script);
+            return_script = std::mem::take(&mut core.return_script);
+        }
+
         core.source_function_level += 1;
+        signal::run_debug_trap(core); // at the entry of the function
         if let Err(e) = self.command.as_mut().unwrap().exec(core, &mut dummy) {
             e.print(core);
         }
         core.return_flag = false;
+        signal::run_return_trap(core);
         core.source_function_level -= 1;
 
-        core.db.position_parameters.pop();
+        for (trap, bkup) in [
+            (&mut core.err_script, err_script),
+            (&mut core.debug_script, debug_script),
+            (&mut core.return_script, return_script),
+        ] {
+            if trap.is_empty() {
+                *trap = bkup;
+            }
+        }
+        let _ = core.db.set_param("LINENO", &lineno, None);
 
-        array.remove(0);
-        source.remove(0);
-        let _ = core.db.set_array("FUNCNAME", Some(array), None);
-        let _ = core.db.set_array("BASH_SOURCE", Some(source), None);
+        core.db.position_parameters.pop();
+        core.pop_call_frame();
     }
 
     fn eat_header(&mut self, feeder: &mut Feeder, core: &mut ShellCore) -> bool {
@@ -154,9 +177,7 @@ impl FunctionDefinition {
 
         if ans.command.is_some() {
             feeder.pop_backup();
-            if let Some(f) = core.source_files.last() {
-                ans.file = f.clone();
-            }
+            ans.file = core.current_source_name();
             Ok(Some(ans))
         } else {
             feeder.rewind();
@@ -21,8 +21,13 @@ pub struct IfCommand {
 
 impl Command for IfCommand {
     fn run(&mut self, core: &mut ShellCore, _: bool) -> Result<(), ExecError> {
+        let suspend_e_option = core.suspend_e_option;
         for i in 0..self.if_elif_scripts.len() {
-            self.if_elif_scripts[i].exec(core)?;
+            core.suspend_e_option = true;
+            let result = self.if_elif_scripts[i].exec(core);
+            core.suspend_e_option = suspend_e_option;
+            result?;
+
             if core.db.exit_status == 0 {
                 let _ = self.then_scripts[i].exec(core);
                 return Ok(());
@@ -43,6 +48,17 @@ impl Command for IfCommand {
     fn get_redirects(&mut self) -> &mut Vec<Redirect> {
         &mut self.redirects
     }
+
+    fn get_scripts(&mut self) -> Vec<&mut Script> {
+        let mut ans = vec![];
+        let mut thens = self.then_scripts.iter_mut();
+        for s in self.if_elif_scripts.iter_mut() {
+            ans.push(s);
+            ans.extend(thens.next());
+        }
+        ans.extend(self.else_script.iter_mut());
+        ans
+    }
     fn get_lineno(&mut self) -> usize {
         self.lineno
     }
@@ -55,6 +71,9 @@ impl Command for IfCommand {
     fn force_fork(&self) -> bool {
         self.force_fork
     }
+    fn is_compound(&self) -> bool {
+        true
+    }
 }
 
 impl IfCommand {
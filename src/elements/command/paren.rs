@@ -19,6 +19,8 @@ pub struct ParenCommand {
 
 impl Command for ParenCommand {
     fn exec(&mut self, core: &mut ShellCore, pipe: &mut Pipe) -> Result<Option<Pid>, ExecError> {
+        core.db
+            .set_param("LINENO", &self.lineno.to_string(), None)?;
         self.fork_exec(core, pipe)
     }
 
@@ -41,6 +43,10 @@ impl Command for ParenCommand {
     fn get_redirects(&mut self) -> &mut Vec<Redirect> {
         &mut self.redirects
     }
+
+    fn get_scripts(&mut self) -> Vec<&mut Script> {
+        self.script.iter_mut().collect()
+    }
     fn get_lineno(&mut self) -> usize {
         self.lineno
     }
@@ -55,6 +55,9 @@ impl Command for RepeatCommand {
     fn force_fork(&self) -> bool {
         self.force_fork
     }
+    fn is_compound(&self) -> bool {
+        true
+    }
 }
 
 impl RepeatCommand {
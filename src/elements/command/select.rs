@@ -0,0 +1,348 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{signal, Feeder, Script, ShellCore};
+
+use super::{Command, Redirect};
+use crate::elements::command;
+use crate::elements::word::Word;
+use crate::error::exec::ExecError;
+use crate::error::parse::ParseError;
+use std::io::Write;
+use std::sync::atomic::Ordering::Relaxed;
+use unicode_width::UnicodeWidthStr;
+
+#[derive(Debug, Clone, Default)]
+pub struct SelectCommand {
+    text: String,
+    name: String,
+    has_in: bool,
+    values: Vec<Word>,
+    do_script: Option<Script>,
+    redirects: Vec<Redirect>,
+    force_fork: bool,
+    lineno: usize,
+}
+
+impl Command for SelectCommand {
+    fn run(&mut self, core: &mut ShellCore, _: bool) -> Result<(), ExecError> {
+        let values = match self.has_in {
+            true => match self.eval_values(core) {
+                Some(vs) => vs,
+                None => {
+                    core.db.exit_status = 1;
+                    return Ok(());
+                }
+            },
+            false => core.db.get_position_params(),
+        };
+
+        core.db.exit_status = 0;
+        if values.is_empty() {
+            return Ok(());
+        }
+
+        core.loop_level += 1;
+        self.run_with_values(core, &values);
+        core.loop_level -= 1;
+        if core.loop_level == 0 {
+            core.break_counter = 0;
+        }
+        Ok(())
+    }
+
+    fn get_text(&self) -> String {
+        self.text.clone()
+    }
+    fn get_redirects(&mut self) -> &mut Vec<Redirect> {
+        &mut self.redirects
+    }
+
+    fn get_scripts(&mut self) -> Vec<&mut Script> {
+        self.do_script.iter_mut().collect()
+    }
+    fn get_lineno(&mut self) -> usize {
+        self.lineno
+    }
+    fn set_force_fork(&mut self) {
+        self.force_fork = true;
+    }
+    fn boxed_clone(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+    fn force_fork(&self) -> bool {
+        self.force_fork
+    }
+    fn is_compound(&self) -> bool {
+        true
+    }
+}
+
+fn number_len(n: usize) -> usize {
+    n.to_string().len()
+}
+
+fn indent(from: usize, to: usize) -> String {
+    let mut ans = String::new();
+    let mut from = from;
+    while from < to {
+        if to / 8 > from / 8 {
+            ans.push('\t');
+            from += 8 - from % 8;
+        } else {
+            ans.push(' ');
+            from += 1;
+        }
+    }
+    ans
+}
+
+impl SelectCommand {
+    fn header_text(&self) -> String {
+        match self.has_in {
+            true => {
+                let values: Vec<&str> = self.values.iter().map(|w| w.text.as_str()).collect();
+                format!("select {} in {}", &self.name, values.join(" "))
+            }
+            false => format!("select {} in \"$@\"", &self.name),
+        }
+    }
+
+    fn eval_values(&mut self, core: &mut ShellCore) -> Option<Vec<String>> {
+        let mut ans = vec![];
+        for w in &mut self.values {
+            match w.eval(core) {
+                Ok(mut ws) => ans.append(&mut ws),
+                Err(e) => {
+                    e.print(core);
+                    return None;
+                }
+            }
+        }
+
+        Some(ans)
+    }
+
+    fn get_columns(core: &mut ShellCore) -> usize {
+        match core
+            .db
+            .get_param("COLUMNS")
+            .unwrap_or_default()
+            .parse::<usize>()
+        {
+            Ok(n) if n > 0 => n,
+            _ => 80,
+        }
+    }
+
+    /* the same layout as print_select_list in bash */
+    fn menu(values: &[String], columns: usize) -> String {
+        let indices_len = number_len(values.len());
+        let max_elem_len = values.iter().map(|v| v.width()).max().unwrap_or(0) + indices_len + 4;
+
+        let cols = std::cmp::max(columns / max_elem_len, 1);
+        let rows = values.len().div_ceil(cols);
+        let cols = values.len().div_ceil(rows);
+        let (rows, cols) = match rows {
+            1 => (cols, 1),
+            _ => (rows, cols),
+        };
+
+        let first_indices_len = number_len(rows);
+        let mut ans = String::new();
+        for row in 0..rows {
+            let mut pos = 0;
+            for col in 0..cols {
+                let ind = row + col * rows;
+                if ind >= values.len() {
+                    break;
+                }
+
+                let len = if col == 0 {
+                    first_indices_len
+                } else {
+                    indices_len
+                };
+                ans += &format!("{:>len$}) {}", ind + 1, &values[ind]);
+
+                if ind + rows >= values.len() {
+                    break;
+                }
+                let elem_len = values[ind].width() + len + 2;
+                ans += &indent(pos + elem_len, pos + max_elem_len);
+                pos += max_elem_len;
+            }
+            ans.push('\n');
+        }
+        ans
+    }
+
+    fn query(core: &mut ShellCore, values: &[String], show_menu: bool) -> Option<String> {
+        let mut show_menu = show_menu;
+        loop {
+            let prompt = match core.db.exist("PS3") {
+                true => core.db.get_param("PS3").unwrap_or_default(),
+                false => "#? ".to_string(),
+            };
+
+            if show_menu {
+                let columns = Self::get_columns(core);
+                eprint!("{}", Self::menu(values, columns));
+            }
+            eprint!("{}", &prompt);
+            let _ = std::io::stderr().flush();
+
+            let read = *core.builtins.get("read")?;
+            if read(core, &["read".to_string()]) != 0 {
+                println!();
+                return None;
+            }
+
+            let reply = core.db.get_param("REPLY").unwrap_or_default();
+            if reply.is_empty() {
+                show_menu = true;
+                continue;
+            }
+
+            return match reply.trim().parse::<usize>() {
+                Ok(n) if n >= 1 && n <= values.len() => Some(values[n - 1].clone()),
+                _ => Some("".to_string()),
+            };
+        }
+    }
+
+    fn run_with_values(&mut self, core: &mut ShellCore, values: &[String]) {
+        let mut show_menu = true;
+        loop {
+            if core.sigint.load(Relaxed) || core.return_flag {
+                return;
+            }
+
+            if core.db.flags.contains('x') {
+                core.print_xtrace(&self.header_text());
+            }
+            if signal::skip_by_debug_trap(core, &self.header_text()) {
+                continue;
+            }
+
+            let selection = match Self::query(core, values, show_menu) {
+                Some(s) => s,
+                None => {
+                    core.db.exit_status = 1;
+                    return;
+                }
+            };
+
+            let res = match core.db.is_nameref(&self.name) {
+                true => core.db.set_nameref(&self.name, &selection, None),
+                false => core.db.set_param(&self.name, &selection, None),
+            };
+            if let Err(e) = res {
+                core.db.exit_status = 1;
+                e.print(core);
+            }
+
+            if let Some(mut s) = self.do_script.clone() {
+                let _ = s.exec(core);
+            }
+
+            if core.break_counter > 0 {
+                core.break_counter -= 1;
+                return;
+            }
+
+            if core.continue_counter > 0 {
+                core.continue_counter -= 1;
+                if core.continue_counter > 0 {
+                    return;
+                }
+            }
+
+            show_menu = core.db.get_param("REPLY").unwrap_or_default().is_empty();
+        }
+    }
+
+    fn eat_name(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        command::eat_blank_with_comment(feeder, core, &mut ans.text);
+
+        let len = feeder.scanner_name(core);
+        if len == 0 {
+            return false;
+        }
+
+        ans.name = feeder.consume(len);
+        ans.text += &ans.name.clone();
+        command::eat_blank_with_comment(feeder, core, &mut ans.text);
+        true
+    }
+
+    fn eat_in_part(
+        feeder: &mut Feeder,
+        ans: &mut Self,
+        core: &mut ShellCore,
+    ) -> Result<(), ParseError> {
+        if !feeder.starts_with("in") {
+            return Ok(());
+        }
+
+        ans.text += &feeder.consume(2);
+        ans.has_in = true;
+
+        loop {
+            command::eat_blank_with_comment(feeder, core, &mut ans.text);
+            match Word::parse(feeder, core, None)? {
+                Some(w) => {
+                    ans.text += &w.text.clone();
+                    ans.values.push(w);
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn eat_end(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        command::eat_blank_with_comment(feeder, core, &mut ans.text);
+        if feeder.starts_with(";") || feeder.starts_with("\n") {
+            ans.text += &feeder.consume(1);
+            command::eat_blank_with_comment(feeder, core, &mut ans.text);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Result<Option<Self>, ParseError> {
+        if !feeder.starts_with("select") {
+            return Ok(None);
+        }
+        let mut ans = Self {
+            lineno: feeder.lineno,
+            text: feeder.consume(6),
+            ..Default::default()
+        };
+
+        if !Self::eat_name(feeder, &mut ans, core) {
+            return Ok(None);
+        }
+        Self::eat_in_part(feeder, &mut ans, core)?;
+
+        if !Self::eat_end(feeder, &mut ans, core) {
+            return Ok(None);
+        }
+
+        command::eat_blank_lines(feeder, core, &mut ans.text)?;
+
+        if command::eat_inner_script(feeder, core, "do", vec!["done"], &mut ans.do_script, false)? {
+            ans.text.push_str("do");
+            if let Some(ref mut s) = ans.do_script {
+                ans.text.push_str(&s.get_text());
+            }
+            ans.text.push_str(&feeder.consume(4)); //done
+
+            command::eat_redirects(feeder, core, &mut ans.redirects, &mut ans.text)?;
+            Ok(Some(ans))
+        } else {
+            Ok(None)
+        }
+    }
+}
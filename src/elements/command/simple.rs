@@ -6,14 +6,15 @@ pub mod hash;
 pub mod parser;
 pub mod run_internal;
 
-use crate::{proc_ctrl, ShellCore};
+use crate::core::xtrace::{self, QuoteStyle};
+use crate::{proc_ctrl, signal, ShellCore};
 
 use super::{Command, Pipe, Redirect};
 use crate::elements::substitution::Substitution;
 use crate::elements::word::Word;
 use crate::env;
 use crate::error::exec::ExecError;
-use crate::utils::exit;
+use crate::utils::{exit, file_check};
 use nix::unistd::Pid;
 use std::sync::atomic::Ordering::Relaxed;
 
@@ -47,7 +48,9 @@ impl Command for SimpleCommand {
             return Ok(None);
         }
 
-        core.db.set_param("BASH_COMMAND", &self.text, None)?;
+        if signal::skip_by_debug_trap(core, self.text.trim_end()) {
+            return Ok(None);
+        }
 
         self.args.clear();
         let mut words = self.words.to_vec();
@@ -60,6 +63,10 @@ impl Command for SimpleCommand {
             self.redirects.clear();
             self.args.insert(0, "fg".to_string());
         }
+        if !self.args.is_empty() && self.is_autocd(core) {
+            self.args.splice(0..0, ["cd".to_string(), "--".to_string()]);
+            eprintln!("{}", self.args.join(" "));
+        }
 
         match self.args.len() {
             0 => self.exec_set_param(core),
@@ -91,6 +98,10 @@ impl Command for SimpleCommand {
     fn get_redirects(&mut self) -> &mut Vec<Redirect> {
         &mut self.redirects
     }
+
+    fn get_words(&self) -> Vec<&Word> {
+        self.words.iter().collect()
+    }
     fn set_force_fork(&mut self) {
         self.force_fork = true;
     }
@@ -107,6 +118,17 @@ impl SimpleCommand {
         core.break_counter > 0 || core.continue_counter > 0
     }
 
+    /* autocd: a directory name given as a command is the argument of cd */
+    fn is_autocd(&self, core: &mut ShellCore) -> bool {
+        let name = &self.args[0];
+        core.shopts.query("autocd")
+            && core.db.flags.contains('i')
+            && !core.builtins.contains_key(name)
+            && !core.substitution_builtins.contains_key(name)
+            && !core.db.functions.contains_key(name)
+            && file_check::is_dir(name)
+    }
+
     pub fn exec_command(
         &mut self,
         core: &mut ShellCore,
@@ -162,10 +184,16 @@ impl SimpleCommand {
 
     fn exec_set_param(&mut self, core: &mut ShellCore) -> Result<Option<Pid>, ExecError> {
         core.db.last_arg = String::new();
-        self.option_x_output(core);
 
         for s in self.substitutions.iter_mut() {
-            if let Err(e) = s.eval(core, None, false) {
+            let result = s.eval_right_hand(core, false).and_then(|_| {
+                if core.db.flags.contains('x') {
+                    core.print_xtrace(&s.get_xtrace_text());
+                }
+                s.set_to_shell(core, None)
+            });
+
+            if let Err(e) = result {
                 core.db.exit_status = 1;
                 if !core.db.flags.contains('i') {
                     if let ExecError::SyntaxError(_) = e {
@@ -221,23 +249,24 @@ impl SimpleCommand {
             return;
         }
 
-        let ps4 = core.get_ps4();
         for s in &self.substitutions {
-            eprintln!("\r{} {}\r", &ps4, &s.text);
+            core.print_xtrace(&s.get_compact_text());
         }
 
-        if self.args.is_empty() {
-            return;
-        }
-
-        eprint!("{}", &ps4);
-        for a in &self.args {
-            match a.contains(" ") {
-                false => eprint!(" {}", &a),
-                true => eprint!(" '{}'", &a),
+        let mut words: Vec<String> = self
+            .args
+            .iter()
+            .map(|a| xtrace::quote(a, QuoteStyle::IfNeeded))
+            .collect();
+        if core.substitution_builtins.contains_key(&self.args[0]) {
+            for sub in &self.substitutions_as_args {
+                match sub {
+                    SubsArgType::Subs(s) => words.push(s.get_compact_text()),
+                    SubsArgType::Other(w) => words.push(w.text.clone()),
+                }
             }
         }
 
-        eprintln!();
+        core.print_xtrace(&words.join(" "));
     }
 }
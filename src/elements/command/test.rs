@@ -7,7 +7,7 @@ use crate::elements::expr::conditional::elem::CondElem;
 use crate::elements::expr::conditional::ConditionalExpr;
 use crate::error::exec::ExecError;
 use crate::error::parse::ParseError;
-use crate::{Feeder, ShellCore};
+use crate::{signal, Feeder, ShellCore};
 
 #[derive(Debug, Clone, Default)]
 pub struct TestCommand {
@@ -20,9 +20,11 @@ pub struct TestCommand {
 
 impl Command for TestCommand {
     fn run(&mut self, core: &mut ShellCore, _: bool) -> Result<(), ExecError> {
-        if core.db.flags.contains('x') {
-            let ps4 = core.get_ps4();
-            eprint!("\r{} [[ ", &ps4);
+        if signal::skip_by_debug_trap(core, &self.text) {
+            if !core.return_flag {
+                core.db.exit_status = 0;
+            }
+            return Ok(());
         }
 
         match self.cond.clone().unwrap().eval(core) {
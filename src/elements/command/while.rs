@@ -23,11 +23,13 @@ impl Command for WhileCommand {
             return Ok(());
         }
         core.loop_level += 1;
+        let suspend_e_option = core.suspend_e_option;
         while !core.return_flag {
             core.suspend_e_option = true;
-            self.while_script.clone().as_mut().unwrap().exec(core)?;
+            let result = self.while_script.clone().as_mut().unwrap().exec(core);
+            core.suspend_e_option = suspend_e_option;
+            result?;
 
-            core.suspend_e_option = false;
             if core.db.exit_status != 0 {
                 core.db.exit_status = 0;
                 break;
@@ -58,6 +60,13 @@ impl Command for WhileCommand {
     fn get_redirects(&mut self) -> &mut Vec<Redirect> {
         &mut self.redirects
     }
+
+    fn get_scripts(&mut self) -> Vec<&mut Script> {
+        self.while_script
+            .iter_mut()
+            .chain(self.do_script.iter_mut())
+            .collect()
+    }
     fn get_lineno(&mut self) -> usize {
         self.lineno
     }
@@ -70,6 +79,9 @@ impl Command for WhileCommand {
     fn force_fork(&self) -> bool {
         self.force_fork
     }
+    fn is_compound(&self) -> bool {
+        true
+    }
 }
 
 impl WhileCommand {
@@ -47,17 +47,29 @@ impl ArithmeticExpr {
             self.elements = a.elements;
         }
 
-        if core.db.flags.contains('x') {
-            let ps4 = core.get_ps4();
-            eprintln!("\r{} (( {} ))\r", ps4, &self.text);
-        }
-
         Ok(())
     }
 
     pub fn eval(&mut self, core: &mut ShellCore) -> Result<String, ExecError> {
+        self.eval_with_xtrace(core, false)
+    }
+
+    /* for (( )) and the header of for (( ; ; )), which are traced by set -x */
+    pub fn eval_as_command(&mut self, core: &mut ShellCore) -> Result<String, ExecError> {
+        let xtrace = core.db.flags.contains('x');
+        self.eval_with_xtrace(core, xtrace)
+    }
+
+    fn eval_with_xtrace(
+        &mut self,
+        core: &mut ShellCore,
+        xtrace: bool,
+    ) -> Result<String, ExecError> {
         let mut cp = self.clone();
         cp.eval_doller(core)?;
+        if xtrace {
+            core.print_xtrace(&format!("(( {} ))", &cp.text));
+        }
 
         let ans = match cp.eval_elems(core, true) {
             Ok(a) => a,
@@ -6,6 +6,7 @@ mod parser;
 
 use self::elem::CondElem;
 use super::arithmetic::elem::ArithElem;
+use crate::core::xtrace::{self, QuoteStyle};
 use crate::elements::expr::arithmetic::elem::{float, int};
 use crate::elements::expr::arithmetic::ArithmeticExpr;
 use crate::elements::substitution::variable::Variable;
@@ -61,21 +62,11 @@ impl ConditionalExpr {
             e.eval(core)?;
         }
 
-        if core.db.flags.contains('x') {
-            let mut elems = cp
-                .elements
-                .clone()
-                .into_iter()
-                .map(|e| e.to_string())
-                .collect::<Vec<String>>();
-            elems.pop();
-            eprintln!("{} ]]\r", &elems.join(" "));
-        }
-
         for i in 0..cp.elements.len() {
             match cp.elements[i] {
                 CondElem::And | CondElem::Or => {
                     if next {
+                        Self::xtrace(&cp.elements[from..i], core);
                         last = Self::calculate(&cp.elements[from..i], core)?;
                     }
                     from = i + 1;
@@ -97,6 +88,59 @@ impl ConditionalExpr {
         Ok(last)
     }
 
+    fn xtrace(elems: &[CondElem], core: &mut ShellCore) {
+        if !core.db.flags.contains('x') {
+            return;
+        }
+
+        let line = match elems {
+            [CondElem::InParen(_)] => return,
+            [_] => format!("-n {}", Self::xtrace_text(elems, 0)),
+            _ => (0..elems.len())
+                .map(|i| Self::xtrace_text(elems, i))
+                .collect::<Vec<String>>()
+                .join(" "),
+        };
+        core.print_xtrace(&format!("[[ {} ]]", line));
+    }
+
+    /* blanks in a pattern are escaped so as not to be read as separators */
+    fn xtrace_pattern(pattern: &str) -> String {
+        if pattern.is_empty() {
+            return "''".to_string();
+        }
+
+        let mut ans = String::new();
+        let mut escaped = false;
+        for c in pattern.chars() {
+            if !escaped && " \t\n".contains(c) {
+                ans.push('\\');
+            }
+            escaped = !escaped && c == '\\';
+            ans.push(c);
+        }
+        ans
+    }
+
+    /* operands are quoted. patterns are given with backslashes on quoted chars */
+    fn xtrace_text(elems: &[CondElem], i: usize) -> String {
+        let is_pattern = i > 0
+            && matches!(&elems[i - 1], CondElem::BinaryOp(op)
+                if ["==", "!=", "=", "=~"].contains(&op.as_str()));
+
+        match &elems[i] {
+            CondElem::Word(w) | CondElem::Regex(w) if is_pattern => {
+                Self::xtrace_pattern(&w.clone().make_glob_string())
+            }
+            CondElem::Word(w) => xtrace::quote(
+                &w.clone().make_unquoted_word().unwrap_or_default(),
+                QuoteStyle::IfNeeded,
+            ),
+            CondElem::Operand(s) => xtrace::quote(s, QuoteStyle::IfNeeded),
+            e => e.to_string(),
+        }
+    }
+
     fn calculate(elems: &[CondElem], core: &mut ShellCore) -> Result<CondElem, ExecError> {
         let rev_pol = Self::rev_polish(elems)?;
         let mut stack = Self::reduce(&rev_pol, core)?;
@@ -200,31 +244,36 @@ impl ConditionalExpr {
             Err(e) => return Err(e),
         };
 
-        if op == "-o" || op == "-v" || op == "-z" || op == "-n" {
-            let ans = match op {
-                "-o" => core.options.query(&operand),
-                "-v" => {
-                    if env::var(&operand).is_ok() {
-                        true
+        let ans = Self::unary_test(op, &operand, core)?;
+        stack.push(CondElem::Ans(ans));
+        Ok(())
+    }
+
+    /* also used by the test builtin */
+    pub fn unary_test(op: &str, operand: &str, core: &mut ShellCore) -> Result<bool, ExecError> {
+        let ans = match op {
+            "-o" => core.options.query(operand),
+            "-v" => {
+                if env::var(operand).is_ok() {
+                    true
+                } else {
+                    let mut f = Feeder::new(operand);
+                    if let Some(v) = Variable::parse(&mut f, core)? {
+                        v.exist(core)?
                     } else {
-                        let mut f = Feeder::new(&operand);
-                        if let Some(v) = Variable::parse(&mut f, core)? {
-                            v.exist(core)?
-                        } else {
-                            false
-                        }
+                        false
                     }
                 }
-                "-z" => operand.is_empty(),
-                "-n" => !operand.is_empty(),
-                _ => false,
-            };
-
-            stack.push(CondElem::Ans(ans));
-            return Ok(());
-        }
+            }
+            "-z" => operand.is_empty(),
+            "-n" => !operand.is_empty(),
+            _ => match file_check::unary(op, operand) {
+                Some(ans) => ans,
+                None => return Err(ExecError::Other("unsupported option".to_string())),
+            },
+        };
 
-        Self::unary_file_check(op, &operand, stack)
+        Ok(ans)
     }
 
     fn regex_operation(stack: &mut Vec<CondElem>, core: &mut ShellCore) -> Result<(), ExecError> {
@@ -365,26 +414,6 @@ impl ConditionalExpr {
         Ok(())
     }
 
-    fn unary_file_check(op: &str, s: &str, stack: &mut Vec<CondElem>) -> Result<(), ExecError> {
-        let result = match op {
-            "-a" | "-e" => file_check::exists(s),
-            "-d" => file_check::is_dir(s),
-            "-f" => file_check::is_regular_file(s),
-            "-h" | "-L" => file_check::is_symlink(s),
-            "-r" => file_check::is_readable(s),
-            "-t" => file_check::is_tty(s),
-            "-w" => file_check::is_writable(s),
-            "-x" => file_check::is_executable(s),
-            "-b" | "-c" | "-g" | "-k" | "-p" | "-s" | "-u" | "-G" | "-N" | "-O" | "-S" => {
-                file_check::metadata_check(s, op)
-            }
-            _ => return Err(ExecError::Other("unsupported option".to_string())),
-        };
-
-        stack.push(CondElem::Ans(result));
-        Ok(())
-    }
-
     fn rev_polish_op(elem: &CondElem, stack: &mut Vec<CondElem>, ans: &mut Vec<CondElem>) -> bool {
         loop {
             match stack.last() {
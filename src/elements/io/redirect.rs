@@ -19,6 +19,41 @@ use std::os::fd::FromRawFd;
 use std::os::fd::{IntoRawFd, RawFd};
 use std::process;
 
+/* a pipe can hold at least a page without a reader */
+const PIPE_CAPACITY: usize = 4096;
+
+/* connects the text to the stdin. A long text is written by a child process
+ * so as not to block on the full pipe. */
+fn pipe_here_data(text: &str) -> Result<(), ExecError> {
+    let (r, s) = unistd::pipe().expect("Cannot open pipe");
+    let recv = r.into_raw_fd();
+    let send = s.into_raw_fd();
+
+    if text.len() <= PIPE_CAPACITY {
+        let mut f = unsafe { File::from_raw_fd(send) };
+        let _ = write!(&mut f, "{}", text);
+        drop(f);
+        io::replace(recv, 0);
+        return Ok(());
+    }
+
+    match unsafe { unistd::fork()? } {
+        ForkResult::Child => {
+            io::close(recv, "here_data close error (child recv)");
+            let mut f = unsafe { File::from_raw_fd(send) };
+            let _ = write!(&mut f, "{}", text);
+            f.flush().unwrap();
+            io::close(send, "here_data close error (child send)");
+            process::exit(0);
+        }
+        ForkResult::Parent { child: _ } => {
+            io::close(send, "here_data close error (parent send)");
+            io::replace(recv, 0);
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct Redirect {
     pub text: String,
@@ -190,10 +225,6 @@ impl Redirect {
         restore: bool,
     ) -> Result<(), ExecError> {
         self.left_fd = 0;
-        let (r, s) = unistd::pipe().expect("Cannot open pipe");
-        let recv = r.into_raw_fd();
-        let send = s.into_raw_fd();
-
         if restore {
             self.left_backup = io::backup(0);
         }
@@ -206,21 +237,7 @@ impl Redirect {
             true => self.here_data.text.clone(),
         };
 
-        match unsafe { unistd::fork()? } {
-            ForkResult::Child => {
-                io::close(recv, "here_data close error (child recv)");
-                let mut f = unsafe { File::from_raw_fd(send) };
-                let _ = write!(&mut f, "{}", &text);
-                f.flush().unwrap();
-                io::close(send, "here_data close error (child send)");
-                process::exit(0);
-            }
-            ForkResult::Parent { child: _ } => {
-                io::close(send, "here_data close error (parent send)");
-                io::replace(recv, 0);
-            }
-        }
-        Ok(())
+        pipe_here_data(&text)
     }
 
     fn redirect_herestring(
@@ -229,31 +246,12 @@ impl Redirect {
         restore: bool,
     ) -> Result<(), ExecError> {
         self.left_fd = 0;
-        let (r, s) = unistd::pipe().expect("Cannot open pipe");
-        let recv = r.into_raw_fd();
-        let send = s.into_raw_fd();
-
         if restore {
             self.left_backup = io::backup(0);
         }
 
-        let text = self.right.eval_as_herestring(core)?;
-
-        match unsafe { unistd::fork()? } {
-            ForkResult::Child => {
-                io::close(recv, "here_data close error (child recv)");
-                let mut f = unsafe { File::from_raw_fd(send) };
-                let _ = writeln!(&mut f, "{}", &text);
-                f.flush().unwrap();
-                io::close(send, "here_data close error (child send)");
-                process::exit(0);
-            }
-            ForkResult::Parent { child: _ } => {
-                io::close(send, "here_data close error (parent send)");
-                io::replace(recv, 0);
-            }
-        }
-        Ok(())
+        let text = self.right.eval_as_herestring(core)? + "\n";
+        pipe_here_data(&text)
     }
 
     pub fn restore(&mut self) {
@@ -298,7 +296,10 @@ impl Redirect {
         loop {
             if feeder.is_empty() {
                 feeder.feed_additional_line(core)?;
+            }
 
+            let text = &self.here_data.text;
+            if text.is_empty() || text.ends_with('\n') {
                 if remove_tab {
                     let len = feeder.scanner_tabs();
                     feeder.consume(len);
@@ -54,6 +54,9 @@ impl Job {
                 core.jobtable_check_status()?;
                 let (pids, exclamation, time, err) = pipeline.exec(core, pgid);
                 let waitstatuses = proc_ctrl::wait_pipeline(core, pids.clone(), exclamation, time);
+                if !exclamation && !pipeline.is_checked_inside(&pids) {
+                    exit::check_e_option(core);
+                }
 
                 Self::check_stop(core, &pipeline.get_one_line_text(), &pids, &waitstatuses);
 
@@ -8,6 +8,7 @@ use crate::error::exec::ExecError;
 use crate::error::parse::ParseError;
 use crate::{Feeder, ShellCore};
 use nix::sys::resource;
+use nix::sys::time::TimeVal;
 use nix::time;
 use nix::time::ClockId;
 use nix::unistd::Pid;
@@ -19,6 +20,7 @@ pub struct Pipeline {
     pub text: String,
     exclamation: bool,
     pub time: bool,
+    time_posix: bool,
 }
 
 impl Pipeline {
@@ -26,6 +28,26 @@ impl Pipeline {
         &mut self,
         core: &mut ShellCore,
         pgid: Pid,
+    ) -> (Vec<Option<Pid>>, bool, bool, Option<ExecError>) {
+        let suspend_e_option = core.suspend_e_option;
+        core.suspend_e_option |= self.exclamation;
+        let ans = self.exec_commands(core, pgid);
+        core.suspend_e_option = suspend_e_option;
+        ans
+    }
+
+    /* failures in compound commands run in this process are checked inside them */
+    pub fn is_checked_inside(&self, pids: &[Option<Pid>]) -> bool {
+        match self.commands.last() {
+            Some(c) => c.is_compound() && pids.last() == Some(&None),
+            None => true,
+        }
+    }
+
+    fn exec_commands(
+        &mut self,
+        core: &mut ShellCore,
+        pgid: Pid,
     ) -> (Vec<Option<Pid>>, bool, bool, Option<ExecError>) {
         if self.commands.is_empty() {
             // the case of only '!'
@@ -74,6 +96,15 @@ impl Pipeline {
             return;
         }
 
+        core.measured_time.posix = self.time_posix;
+        if self.commands.is_empty() && core.options.query("posix") {
+            // times of the shell itself
+            core.measured_time.user = TimeVal::new(0, 0);
+            core.measured_time.sys = TimeVal::new(0, 0);
+            core.measured_time.real = core.measured_time.start;
+            return;
+        }
+
         let self_usage = resource::getrusage(resource::UsageWho::RUSAGE_SELF).unwrap();
         let children_usage = resource::getrusage(resource::UsageWho::RUSAGE_CHILDREN).unwrap();
 
@@ -121,15 +152,42 @@ impl Pipeline {
         true
     }
 
+    fn starts_with_word(feeder: &mut Feeder, word: &str) -> bool {
+        if !feeder.starts_with(word) {
+            return false;
+        }
+        feeder.len() == word.len()
+            || [" ", "\t", "\n", ";", "&"]
+                .iter()
+                .any(|end| feeder.starts_with(&(word.to_owned() + end)))
+    }
+
     fn eat_time(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
-        match feeder.starts_with("time ") || feeder.starts_with("time\t") {
-            true => ans.text += &feeder.consume(4),
-            false => return false,
+        if !Self::starts_with_word(feeder, "time") {
+            return false;
         }
 
-        ans.time = true;
+        feeder.set_backup();
+        let mut text = feeder.consume(4);
         let blank_len = feeder.scanner_blank(core);
-        ans.text += &feeder.consume(blank_len);
+        text += &feeder.consume(blank_len);
+
+        if core.options.query("posix") && feeder.starts_with("-") {
+            feeder.rewind(); // not the reserved word in POSIX mode
+            return false;
+        }
+        feeder.pop_backup();
+        ans.text += &text;
+        ans.time = true;
+
+        for opt in ["-p", "--"] {
+            if Self::starts_with_word(feeder, opt) {
+                ans.text += &feeder.consume(2);
+                ans.time_posix = true; // bash also takes -- as -p
+                let blank_len = feeder.scanner_blank(core);
+                ans.text += &feeder.consume(blank_len);
+            }
+        }
         true
     }
 
@@ -167,7 +167,15 @@ impl Script {
     ) -> Result<Option<Script>, ParseError> {
         let mut ans = Self::default();
         loop {
-            while Self::eat_job(feeder, core, &mut ans)? && Self::eat_job_end(feeder, &mut ans) {}
+            while Self::eat_job(feeder, core, &mut ans)? {
+                /* the lines of here documents can follow in the same text */
+                if feeder.starts_with("\n") && feeder.len() > 1 {
+                    ans.read_heredoc(feeder, core)?;
+                }
+                if !Self::eat_job_end(feeder, &mut ans) {
+                    break;
+                }
+            }
 
             match ans.check_nest(feeder, permit_empty) {
                 Status::NormalEnd => {
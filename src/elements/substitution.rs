@@ -6,8 +6,9 @@ pub mod subscript;
 pub mod value;
 pub mod variable;
 
-use self::value::Value;
+use self::value::{ParsedDataType, Value};
 use self::variable::Variable;
+use crate::core::xtrace::{self, QuoteStyle};
 use crate::error::exec::ExecError;
 use crate::error::parse::ParseError;
 use crate::{Feeder, ShellCore};
@@ -29,6 +30,15 @@ impl Substitution {
         core: &mut ShellCore,
         layer: Option<usize>,
         declare: bool,
+    ) -> Result<(), ExecError> {
+        self.eval_right_hand(core, declare)?;
+        self.set_to_shell(core, layer)
+    }
+
+    pub fn eval_right_hand(
+        &mut self,
+        core: &mut ShellCore,
+        declare: bool,
     ) -> Result<(), ExecError> {
         core.db
             .set_param("LINENO", &self.lineno.to_string(), None)?;
@@ -38,8 +48,36 @@ impl Substitution {
         if declare && self.right_hand.evaluated_array.is_some() {
             self.left_hand.index = None;
         }
+        Ok(())
+    }
 
-        self.set_to_shell(core, layer)
+    /* the elements of an array are separated by single spaces as bash does */
+    pub fn get_compact_text(&self) -> String {
+        let op = if self.append { "+=" } else { "=" };
+        match &self.right_hand.value {
+            ParsedDataType::Array(a) => {
+                format!("{}{}{}", &self.left_hand.text, op, a.get_compact_text())
+            }
+            _ => self.text.clone(),
+        }
+    }
+
+    pub fn get_xtrace_text(&self) -> String {
+        if self.right_hand.evaluated_array.is_some() {
+            return self.get_compact_text();
+        }
+
+        let value = self.right_hand.evaluated_string.clone().unwrap_or_default();
+        let op = if self.append { "+=" } else { "=" };
+        match value.is_empty() {
+            true => format!("{}{}", &self.left_hand.text, op),
+            false => format!(
+                "{}{}{}",
+                &self.left_hand.text,
+                op,
+                xtrace::quote(&value, QuoteStyle::IfNeeded)
+            ),
+        }
     }
 
     pub fn reparse(&mut self, core: &mut ShellCore) -> Result<(), ExecError> {
@@ -134,7 +172,7 @@ impl Substitution {
         }
     }
 
-    fn set_to_shell(
+    pub fn set_to_shell(
         &mut self,
         core: &mut ShellCore,
         layer: Option<usize>,
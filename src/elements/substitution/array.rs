@@ -46,6 +46,18 @@ impl Array {
         Ok(ans)
     }
 
+    pub fn get_compact_text(&self) -> String {
+        let elems: Vec<String> = self
+            .words
+            .iter()
+            .map(|(sub, append, w)| match sub {
+                Some(s) => format!("{}{}{}", &s.text, if *append { "+=" } else { "=" }, &w.text),
+                None => w.text.clone(),
+            })
+            .collect();
+        format!("({})", elems.join(" "))
+    }
+
     fn eat_word(
         feeder: &mut Feeder,
         ans: &mut Self,
@@ -132,32 +132,6 @@ pub trait Subword {
     fn set_pipe(&mut self) {}
 }
 
-fn replace_history_expansion(feeder: &mut Feeder, core: &mut ShellCore) -> bool {
-    let len = feeder.scanner_history_expansion(core);
-    if len == 0 {
-        return false;
-    }
-
-    let history_len = core.history.len();
-    if history_len < 2 {
-        feeder.replace(len, "");
-        return true;
-    }
-
-    let mut his = String::new();
-    for h in &core.history[1..] {
-        let last = h.split(" ").last().unwrap();
-
-        if !last.starts_with("!$") {
-            his = last.to_string();
-            break;
-        }
-    }
-
-    feeder.replace(len, &his);
-    true
-}
-
 pub fn parse_special_subword(
     feeder: &mut Feeder,
     core: &mut ShellCore,
@@ -216,10 +190,6 @@ pub fn parse(
     core: &mut ShellCore,
     mode: &Option<WordMode>,
 ) -> Result<Option<Box<dyn Subword>>, ParseError> {
-    if replace_history_expansion(feeder, core) {
-        return parse(feeder, core, mode);
-    }
-
     if let Some(a) = BracedParam::parse(feeder, core)? {
         Ok(Some(Box::new(a)))
     } else if let Some(a) = AnsiCQuoted::parse(feeder, core)? {
@@ -234,7 +204,7 @@ pub fn parse(
     //else if let Some(a) = CommandSubstitutionOld::parse(feeder, core)?{ Ok(Some(Box::new(a))) }
     else if let Some(a) = ProcessSubstitution::parse(feeder, core, mode)? {
         Ok(Some(Box::new(a)))
-    } else if let Some(a) = SingleQuoted::parse(feeder, core) {
+    } else if let Some(a) = SingleQuoted::parse(feeder, core)? {
         Ok(Some(Box::new(a)))
     } else if let Some(a) = DoubleQuoted::parse(feeder, core, mode)? {
         Ok(Some(Box::new(a)))
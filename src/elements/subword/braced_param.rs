@@ -41,6 +41,13 @@ impl Subword for BracedParam {
             return Ok(());
         }
 
+        if self.indirect && self.param.index.is_none() && core.db.is_nameref(&self.param.name) {
+            // ${!ref} gives the name of the target
+            let target = core.db.get_nameref(&self.param.name).unwrap_or_default();
+            self.text = self.optional_operation(target, core)?;
+            return Ok(());
+        }
+
         if self.indirect {
             self.indirect_replace(core)?;
             self.check()?;
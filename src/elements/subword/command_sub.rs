@@ -32,7 +32,16 @@ impl Subword for CommandSubstitution {
     fn substitute(&mut self, core: &mut ShellCore) -> Result<(), ExecError> {
         let mut pipe = Pipe::new("|".to_string());
         pipe.set(-1, unistd::getpgrp());
-        let pid = self.command.exec(core, &mut pipe)?;
+        let flags = core.db.flags.clone();
+        if !core.shopts.query("inherit_errexit") && !core.options.query("posix") {
+            core.db.flags.retain(|f| f != 'e');
+        }
+
+        core.eval_level += 1; // for the nest level of PS4
+        let pid = self.command.exec(core, &mut pipe);
+        core.eval_level -= 1;
+        core.db.flags = flags;
+        let pid = pid?;
         let result = self.read(pipe.recv, core);
         proc_ctrl::wait_pipeline(core, vec![pid], false, false);
         result?;
@@ -2,6 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use super::Subword;
+use crate::error::parse::ParseError;
 use crate::{Feeder, ShellCore};
 
 #[derive(Debug, Clone)]
@@ -36,12 +37,12 @@ impl Subword for SingleQuoted {
 }
 
 impl SingleQuoted {
-    pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Self> {
-        match feeder.scanner_single_quoted_subword(core) {
-            0 => None,
+    pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Result<Option<Self>, ParseError> {
+        match feeder.scanner_single_quoted_subword(core)? {
+            0 => Ok(None),
             n => {
                 let s = feeder.consume(n);
-                Some(SingleQuoted { text: s })
+                Ok(Some(SingleQuoted { text: s }))
             }
         }
     }
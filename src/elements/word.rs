@@ -215,7 +215,7 @@ impl Word {
         Some(sw.into_iter().map(|s| s.unwrap()).collect::<String>())
     }
 
-    fn make_glob_string(&mut self) -> String {
+    pub fn make_glob_string(&mut self) -> String {
         self.subwords
             .iter_mut()
             .map(|s| s.make_glob_string())
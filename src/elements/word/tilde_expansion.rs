@@ -1,6 +1,7 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
+use crate::core::builtins::dir_stack;
 use crate::elements::word::Word;
 use crate::error::exec::ExecError;
 use crate::ShellCore;
@@ -96,7 +97,12 @@ fn get_value(text: &str, core: &mut ShellCore) -> Result<String, ExecError> {
         "" => "HOME",
         "+" => "PWD",
         "-" => "OLDPWD",
-        _ => return Ok(get_home_dir(text)),
+        _ => {
+            if let Some(dir) = dir_stack::get_entry(core, text) {
+                return Ok(dir);
+            }
+            return Ok(get_home_dir(text));
+        }
     };
 
     core.db.get_param(key)
@@ -2,7 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 mod scanner;
-mod terminal;
+pub mod terminal;
 
 use crate::error::input::InputError;
 use crate::error::parse::ParseError;
@@ -137,6 +137,7 @@ impl Feeder {
         };
 
         line.map(|ln| {
+            let ln = self.history_expansion(ln, core);
             self.add_line(ln.clone(), core);
             self.add_backup(&ln);
         })
@@ -157,12 +158,68 @@ impl Feeder {
     }
 
     pub fn feed_line(&mut self, core: &mut ShellCore) -> Result<(), InputError> {
-        let line = match core.db.flags.contains('i') && self.script_lines.is_none() {
+        let interactive = core.db.flags.contains('i') && self.script_lines.is_none();
+        let line = match interactive {
             true => terminal::read_line(core, "PS1"),
             false => self.read_script(),
         };
 
-        line.map(|ln| self.add_line(ln, core))
+        line.map(|ln| {
+            let record = !interactive && core.options.query("history");
+            if record {
+                core.history.insert(0, ln.trim_end().to_string());
+            }
+            let ln = self.history_expansion(ln, core);
+            if interactive {
+                core.arrange_multiline_history();
+            }
+            if interactive || record {
+                core.record_history();
+            }
+            self.add_line(ln, core)
+        })
+    }
+
+    /* only the lines saved to the history are expanded */
+    fn history_expansion(&mut self, line: String, core: &mut ShellCore) -> String {
+        let interactive = core.db.flags.contains('i') && self.script_lines.is_none();
+        if !core.db.flags.contains('H') || !(interactive || core.options.query("history")) {
+            return line;
+        }
+
+        let (expanded, print_only) = match core.expand_history(&line) {
+            Ok(Some(ans)) => ans,
+            Ok(None) => return line,
+            Err(msg) => {
+                if let Some(h) = core.history.first_mut() {
+                    h.clear(); // not recorded
+                }
+                let name = core.db.get_param("0").unwrap_or_default();
+                match core.db.flags.contains('i') {
+                    true => eprintln!("{}: {}", &name, &msg),
+                    false => eprintln!("{}: line {}: {}", &name, self.lineno, &msg),
+                }
+                return "\n".to_string();
+            }
+        };
+
+        let interactive = core.db.flags.contains('i') && self.script_lines.is_none();
+        if interactive && core.shopts.query("histverify") && !print_only {
+            core.history.remove(0);
+            return match terminal::read_line_with(core, "PS1", expanded.trim_end()) {
+                Ok(ln) => self.history_expansion(ln, core),
+                Err(_) => "\n".to_string(),
+            };
+        }
+
+        if let Some(h) = core.history.first_mut() {
+            *h = expanded.trim_end().replace('\n', "↵ \0");
+        }
+        eprintln!("{}", expanded.trim_end());
+        match print_only {
+            true => "\n".to_string(),
+            false => expanded,
+        }
     }
 
     pub fn add_line(&mut self, line: String, core: &mut ShellCore) {
@@ -2,21 +2,25 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use super::Feeder;
+use crate::error::parse::ParseError;
 use crate::ShellCore;
 
 impl Feeder {
-    fn feed_and_connect(&mut self, core: &mut ShellCore) {
-        self.remaining.pop();
-        self.remaining.pop();
+    /* removes the backslash and the newline at the position. The next line
+     * is read if it is not in the remaining text. */
+    fn feed_and_connect(&mut self, pos: usize, core: &mut ShellCore) {
+        self.remaining.replace_range(pos..pos + 2, "");
         self.lineno += 1;
         let _ = core.db.set_param("LINENO", &self.lineno.to_string(), None);
-        let _ = self.feed_additional_line_core(core);
+        if self.remaining.len() == pos {
+            let _ = self.feed_additional_line_core(core);
+        }
     }
 
     fn backslash_check_and_feed(&mut self, starts: Vec<&str>, core: &mut ShellCore) {
         let check = |s: &str| self.remaining.starts_with(&(s.to_owned() + "\\\n"));
-        if starts.iter().any(|s| check(s)) {
-            self.feed_and_connect(core);
+        if let Some(s) = starts.iter().find(|s| check(s)) {
+            self.feed_and_connect(s.len(), core);
         }
     }
 
@@ -42,8 +46,8 @@ impl Feeder {
                 }
             }
 
-            match &self.remaining[skip_bytes + ans..] == "\\\n" {
-                true => self.feed_and_connect(core),
+            match self.remaining[skip_bytes + ans..].starts_with("\\\n") {
+                true => self.feed_and_connect(skip_bytes + ans, core),
                 false => return ans,
             }
         }
@@ -113,7 +117,7 @@ impl Feeder {
 
     pub fn scanner_escaped_char(&mut self, core: &mut ShellCore) -> usize {
         if self.starts_with("\\\n") {
-            self.feed_and_connect(core);
+            self.feed_and_connect(0, core);
         }
 
         if !self.starts_with("\\") {
@@ -177,13 +181,6 @@ impl Feeder {
         self.scanner_chars(judge, core, 2) + 2
     }
 
-    pub fn scanner_history_expansion(&mut self, _: &mut ShellCore) -> usize {
-        match self.starts_with("!$") {
-            true => 2,
-            false => 0,
-        }
-    }
-
     pub fn scanner_dollar_special_and_positional_param(&mut self, core: &mut ShellCore) -> usize {
         if !self.starts_with("$") {
             return 0;
@@ -226,6 +223,18 @@ impl Feeder {
         ans
     }
 
+    /* a word without quotes and metacharacters */
+    pub fn scanner_plain_word(&self) -> usize {
+        let mut ans = 0;
+        for ch in self.remaining.chars() {
+            if " \t\n;&|()<>\\'\"$`".find(ch).is_some() {
+                break;
+            }
+            ans += ch.len_utf8();
+        }
+        ans
+    }
+
     pub fn scanner_double_quoted_subword(&mut self, core: &mut ShellCore) -> usize {
         let judge = |ch| "`\"\\$".find(ch).is_none();
         self.scanner_chars(judge, core, 0)
@@ -236,17 +245,19 @@ impl Feeder {
         self.scanner_chars(judge, core, 0)
     }
 
-    pub fn scanner_single_quoted_subword(&mut self, core: &mut ShellCore) -> usize {
+    pub fn scanner_single_quoted_subword(
+        &mut self,
+        core: &mut ShellCore,
+    ) -> Result<usize, ParseError> {
         if !self.starts_with("'") {
-            return 0;
+            return Ok(0);
         }
 
         loop {
             if let Some(n) = self.remaining[1..].find("'") {
-                return n + 2;
-            } else if self.feed_additional_line(core).is_err() {
-                return 0;
+                return Ok(n + 2);
             }
+            self.feed_additional_line(core)?;
         }
     }
 
@@ -2,17 +2,21 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 mod completion;
+mod edit;
+mod exec;
+mod highlight;
 mod key;
+mod multiline;
+mod prompt;
+mod search;
+mod suggest;
+mod vi;
 
 use crate::error::input::InputError;
-use crate::utils::{arg, file};
-use crate::{file_check, ShellCore};
-use nix::unistd;
-use nix::unistd::User;
-use std::fs::File;
+use crate::utils::arg;
+use crate::ShellCore;
 use std::io;
 use std::io::{Stdout, Write};
-use std::path::Path;
 use std::sync::atomic::Ordering::Relaxed;
 use termion::cursor::DetectCursorPos;
 use termion::event;
@@ -29,6 +33,8 @@ struct Terminal {
     head: usize,
     hist_ptr: usize,
     prompt_width_map: Vec<usize>,
+    /* PS2 and its width for multi-line editing */
+    ps2: Option<(String, usize)>,
     size: (usize, usize),
     tab_num: usize,
     prev_key: Key,
@@ -37,61 +43,33 @@ struct Terminal {
     tab_row: i32,
     tab_col: i32,
     escape_at_completion: bool,
-}
-
-fn oct_string(s: &str) -> bool {
-    if !s.starts_with('\\') {
-        return false;
-    }
-
-    for i in 1..4 {
-        match s.chars().nth(i) {
-            Some(c) => {
-                if !c.is_ascii_digit() {
-                    return false;
-                }
-            }
-            _ => return false,
-        }
-    }
-
-    true
-}
-
-fn oct_to_hex_in_str(from: &str) -> String {
-    let mut i = 0;
-    let mut pos = vec![];
-
-    for ch in from.chars() {
-        if oct_string(&from[i..]) {
-            pos.push(i);
-        }
-        i += ch.len_utf8();
-    }
-
-    let mut prev = 0;
-    let mut ans = String::new();
-    for p in pos {
-        ans += &from[prev..p];
-        if let Ok(n) = u32::from_str_radix(&from[p + 1..p + 4], 8) {
-            ans += &char::from_u32(n).unwrap().to_string();
-        }
-        prev = p + 4;
-    }
-    ans += &from[prev..];
-    ans
+    /* for editing */
+    undo_stack: Vec<(Vec<char>, usize)>,
+    yank_len: usize,
+    yank_last_arg_nth: usize,
+    search: Option<search::Search>,
+    vi: vi::ViState,
+    /* for key bindings */
+    pending_keys: Vec<u8>,
+    last_function: String,
+    macro_level: usize,
+    bell: bool,
+    /* for highlighting and autosuggestion */
+    styles: Vec<highlight::Style>,
+    suggestion: String,
+    /* the buffer is not a command line (read -e) */
+    plain: bool,
 }
 
 impl Terminal {
     pub fn new(core: &mut ShellCore, ps: &str) -> Self {
         let raw_prompt = core.db.get_param(ps).unwrap_or_default();
-        let ansi_on_prompt = oct_to_hex_in_str(&raw_prompt);
+        let replaced_prompt = Self::make_prompt_string(&raw_prompt, core);
+        Self::with_prompt(core, &replaced_prompt)
+    }
 
-        let replaced_prompt = Self::make_prompt_string(&ansi_on_prompt);
-        let prompt = replaced_prompt
-            .replace("\\[", "")
-            .replace("\\]", "")
-            .to_string();
+    fn with_prompt(core: &mut ShellCore, replaced_prompt: &str) -> Self {
+        let prompt = replaced_prompt.replace(['\x01', '\x02'], "");
         print!("{prompt}");
         io::stdout().flush().unwrap();
 
@@ -106,91 +84,29 @@ impl Terminal {
             head: prompt.chars().count(),
             hist_ptr: 0,
             size: Terminal::size(),
-            prompt_width_map: Self::make_width_map(&replaced_prompt),
+            prompt_width_map: Self::make_width_map(replaced_prompt),
+            ps2: None,
             prev_key: event::Key::Char('a'),
             tab_num: 0,
             completion_candidate: String::new(),
             tab_row: -1,
             tab_col: -1,
             escape_at_completion: true,
+            undo_stack: vec![],
+            yank_len: 0,
+            yank_last_arg_nth: 0,
+            search: None,
+            vi: vi::ViState::default(),
+            pending_keys: vec![],
+            last_function: String::new(),
+            macro_level: 0,
+            bell: core.keymap.variables.get("bell-style").map(|s| s.as_str()) == Some("audible"),
+            styles: vec![],
+            suggestion: String::new(),
+            plain: false,
         }
     }
 
-    fn get_branch(cwd: &str) -> String {
-        let mut dirs: Vec<String> = cwd.split("/").map(|s| s.to_string()).collect();
-        while !dirs.is_empty() {
-            let path = dirs.join("/") + "/.git/HEAD";
-            dirs.pop();
-
-            if !file_check::is_regular_file(&path) {
-                continue;
-            }
-
-            if let Ok(mut f) = File::open(Path::new(&path)) {
-                return match f.read_line() {
-                    Ok(Some(s)) => s.replace("ref: refs/heads/", "") + "🌵",
-                    _ => "".to_string(),
-                };
-            }
-        }
-
-        "".to_string()
-    }
-
-    fn make_prompt_string(raw: &str) -> String {
-        let uid = unistd::getuid();
-        let user = match User::from_uid(uid) {
-            Ok(Some(u)) => u.name,
-            _ => "".to_string(),
-        };
-        let hostname = match unistd::gethostname() {
-            Ok(h) => file::oss_to_name(&h),
-            _ => "".to_string(),
-        };
-
-        let homedir = match User::from_uid(uid) {
-            Ok(Some(u)) => file::buf_to_name(&u.dir),
-            _ => "".to_string(),
-        };
-        let mut cwd = match unistd::getcwd() {
-            Ok(p) => file::buf_to_name(&p),
-            _ => "".to_string(),
-        };
-        let branch = Self::get_branch(&cwd);
-
-        if cwd.starts_with(&homedir) {
-            cwd = cwd.replacen(&homedir, "~", 1);
-        }
-
-        raw.replace("\\u", &user)
-            .replace("\\h", &hostname)
-            .replace("\\w", &cwd)
-            .replace("\\b", &branch)
-            .to_string()
-    }
-
-    fn make_width_map(prompt: &str) -> Vec<usize> {
-        let tmp = prompt
-            .replace("\\[", "\x01")
-            .replace("\\]", "\x02")
-            .to_string();
-        let mut in_escape = false;
-        let mut ans = vec![];
-        for c in tmp.chars() {
-            if c == '\x01' || c == '\x02' {
-                in_escape = c == '\x01';
-                continue;
-            }
-
-            let wid = match in_escape {
-                true => 0,
-                false => UnicodeWidthChar::width(c).unwrap_or(0),
-            };
-            ans.push(wid);
-        }
-        ans
-    }
-
     fn write(&mut self, s: &str) {
         write!(self.stdout, "{s}").unwrap();
     }
@@ -229,7 +145,10 @@ impl Terminal {
         for (i, c) in self.chars[..head].iter().enumerate() {
             if *c == '\n' {
                 y += 1;
-                x = 0;
+                x = match i < self.origin() {
+                    true => 0,
+                    false => self.ps2.as_ref().map(|p| p.1).unwrap_or(0),
+                };
                 continue;
             }
 
@@ -259,7 +178,8 @@ impl Terminal {
         if erase {
             self.write(termion::clear::AfterCursor.as_ref());
         }
-        self.write(&self.get_string(0).replace("\n", "\n\r"));
+        let text = self.prompt.replace("\n", "\n\r") + &self.decorated_text();
+        self.write(&text);
         self.goto(self.head);
         self.flush();
     }
@@ -346,9 +266,15 @@ impl Terminal {
     }
 
     pub fn call_history(&mut self, inc: i32, core: &mut ShellCore) {
+        let mut pos = self.hist_ptr;
+        Self::shift_in_range(&mut pos, inc, 0, isize::MAX as usize);
+        self.set_history_pos(pos, core);
+    }
+
+    pub fn set_history_pos(&mut self, pos: usize, core: &mut ShellCore) {
         let prev = self.hist_ptr;
         let prev_str = self.get_string(self.prompt.chars().count());
-        Self::shift_in_range(&mut self.hist_ptr, inc, 0, isize::MAX as usize);
+        self.hist_ptr = pos;
 
         self.chars = self.prompt.chars().collect();
         self.chars.extend(
@@ -357,6 +283,9 @@ impl Terminal {
                 .chars(),
         );
         self.head = self.chars.len();
+        if self.chars.contains(&'\n') {
+            self.set_ps2(core);
+        }
         self.rewrite(true);
     }
 
@@ -371,17 +300,17 @@ impl Terminal {
     }
 
     pub fn cloop(&mut self) {
+        if !self.bell {
+            return;
+        }
         print!("\x07");
         self.flush();
     }
 
     fn completion_finish_check(&mut self) {
         match self.prev_key {
-            event::Key::Char('\t')
-            | event::Key::Left
-            | event::Key::Down
-            | event::Key::Right
-            | event::Key::Up => (),
+            event::Key::Left | event::Key::Down | event::Key::Right | event::Key::Up => (),
+            _ if self.last_function == "complete" => (),
             _ => {
                 self.tab_num = 0;
                 self.completion_candidate = String::new();
@@ -398,10 +327,48 @@ fn signal_check(core: &mut ShellCore, term: &mut Terminal) -> Result<bool, Input
     Ok(true)
 }
 
+/* the value of a prompt variable after the decoding and the expansion */
+pub fn prompt_string(core: &mut ShellCore, ps: &str) -> String {
+    let raw_prompt = core.db.get_param(ps).unwrap_or_default();
+    Terminal::make_prompt_string(&raw_prompt, core).replace(['\x01', '\x02'], "")
+}
+
 pub fn read_line(core: &mut ShellCore, prompt: &str) -> Result<String, InputError> {
-    let mut term = Terminal::new(core, prompt);
+    read_line_with(core, prompt, "")
+}
+
+/* starts editing with the given text in the buffer */
+pub fn read_line_with(
+    core: &mut ShellCore,
+    prompt: &str,
+    text: &str,
+) -> Result<String, InputError> {
+    let term = Terminal::new(core, prompt);
+    edit(core, term, text)
+}
+
+/* for read -e: the prompt is used as it is and the line is not recorded in the history */
+pub fn read_line_plain(
+    core: &mut ShellCore,
+    prompt: &str,
+    text: &str,
+) -> Result<String, InputError> {
+    let mut term = Terminal::with_prompt(core, prompt);
+    term.plain = true;
+    let ans = edit(core, term, text)?;
+    core.history.remove(0);
+    Ok(ans)
+}
+
+fn edit(core: &mut ShellCore, mut term: Terminal, text: &str) -> Result<String, InputError> {
+    text.chars().for_each(|c| term.insert(c));
     signal_check(core, &mut term)?;
 
+    let vi_mode = core.options.query("vi");
+    if vi_mode {
+        term.vi_start();
+    }
+
     core.history.insert(0, String::new());
 
     for c in io::stdin().keys() {
@@ -415,9 +382,15 @@ pub fn read_line(core: &mut ShellCore, prompt: &str) -> Result<String, InputErro
         term.check_terminal_size();
         match key::action(core, &mut term, &c) {
             Ok(true) => break,
-            Ok(false) => term.prev_key = c,
+            Ok(false) => {
+                term.prev_key = c;
+                term.decorate(core);
+            }
             Err(e) => {
                 core.history.remove(0);
+                if vi_mode {
+                    term.vi_finish();
+                }
                 return Err(e);
             }
         }
@@ -426,8 +399,11 @@ pub fn read_line(core: &mut ShellCore, prompt: &str) -> Result<String, InputErro
         term.check_scroll();
     }
 
+    if vi_mode {
+        term.vi_finish();
+    }
     let ans = term.get_string(term.prompt.chars().count());
-    core.history[0] = ans.trim_end().to_string();
+    core.history[0] = ans.trim_end().replace('\n', "↵ \0");
     Ok(ans)
 }
 
@@ -460,7 +436,10 @@ pub fn read_line(core: &mut ShellCore, prompt: &str) -> Result<String, InputErro
         term.check_terminal_size();
         match key::action(core, &mut term, &c) {
             Ok(true) => break,
-            Ok(false) => term.prev_key = c,
+            Ok(false) => {
+                term.prev_key = c;
+                term.decorate(core);
+            }
             Err(e) => {
                 core.history.remove(0);
                 return Err(e)
@@ -1,7 +1,7 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::core::builtins::compgen;
+use crate::core::builtins::{compgen, dir_stack};
 use crate::core::completion::CompletionEntry;
 use crate::elements::command::simple::SimpleCommand;
 use crate::elements::command::Command;
@@ -381,10 +381,19 @@ impl Terminal {
         let tilde_path;
         let last_tilde_expanded;
 
+        let dir_entry = match (last.starts_with('~'), last.find('/')) {
+            (true, Some(n)) if n > 1 => dir_stack::get_entry(core, &last[1..n]),
+            _ => None,
+        };
+
         if last.starts_with("~/") {
             tilde_prefix = "~/".to_string();
             tilde_path = core.db.get_param("HOME").unwrap_or_default() + "/";
             last_tilde_expanded = last.replacen(&tilde_prefix, &tilde_path, 1);
+        } else if let Some(dir) = dir_entry {
+            tilde_prefix = last[..last.find('/').unwrap() + 1].to_string();
+            tilde_path = dir + "/";
+            last_tilde_expanded = last.replacen(&tilde_prefix, &tilde_path, 1);
         } else {
             tilde_prefix = String::new();
             tilde_path = String::new();
@@ -0,0 +1,271 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::feeder::terminal::Terminal;
+use crate::ShellCore;
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+impl Terminal {
+    pub fn origin(&self) -> usize {
+        self.prompt.chars().count()
+    }
+
+    /* the head of the word at or before the position (backward-word) */
+    fn word_start(&self, from: usize) -> usize {
+        let mut pos = from;
+        while pos > self.origin() && !is_word_char(self.chars[pos - 1]) {
+            pos -= 1;
+        }
+        while pos > self.origin() && is_word_char(self.chars[pos - 1]) {
+            pos -= 1;
+        }
+        pos
+    }
+
+    /* the end of the word at or after the position (forward-word) */
+    fn word_end(&self, from: usize) -> usize {
+        let mut pos = from;
+        while pos < self.chars.len() && !is_word_char(self.chars[pos]) {
+            pos += 1;
+        }
+        while pos < self.chars.len() && is_word_char(self.chars[pos]) {
+            pos += 1;
+        }
+        pos
+    }
+
+    /* the head of the whitespace-delimited word before the position (unix-word-rubout) */
+    fn blank_word_start(&self, from: usize) -> usize {
+        let mut pos = from;
+        while pos > self.origin() && self.chars[pos - 1].is_whitespace() {
+            pos -= 1;
+        }
+        while pos > self.origin() && !self.chars[pos - 1].is_whitespace() {
+            pos -= 1;
+        }
+        pos
+    }
+
+    pub fn save_undo(&mut self) {
+        let state = (self.chars.clone(), self.head);
+        if self.undo_stack.last() != Some(&state) {
+            self.undo_stack.push(state);
+        }
+    }
+
+    pub fn undo(&mut self) {
+        match self.undo_stack.pop() {
+            Some((chars, head)) => {
+                self.chars = chars;
+                self.head = head;
+                self.rewrite(true);
+            }
+            None => self.cloop(),
+        }
+    }
+
+    fn is_kill_function(name: &str) -> bool {
+        matches!(
+            name,
+            "kill-line"
+                | "backward-kill-line"
+                | "kill-whole-line"
+                | "unix-line-discard"
+                | "unix-word-rubout"
+                | "kill-word"
+                | "backward-kill-word"
+        )
+    }
+
+    /* removes chars[from..to] and stores them in the kill ring.
+     * Successive kills are joined into one entry. */
+    fn kill(&mut self, from: usize, to: usize, core: &mut ShellCore) {
+        if from >= to {
+            return;
+        }
+        self.save_undo();
+
+        let killed: String = self.chars.drain(from..to).collect();
+        let forward = from == self.head;
+        self.head = from;
+
+        match (
+            Self::is_kill_function(&self.last_function),
+            core.kill_ring.last_mut(),
+        ) {
+            (true, Some(last)) if forward => last.push_str(&killed),
+            (true, Some(last)) => last.insert_str(0, &killed),
+            _ => core.kill_ring.push(killed),
+        }
+        self.rewrite(true);
+    }
+
+    pub fn kill_line(&mut self, core: &mut ShellCore) {
+        self.kill(self.head, self.chars.len(), core);
+    }
+
+    pub fn kill_whole_line(&mut self, core: &mut ShellCore) {
+        self.kill(self.origin(), self.chars.len(), core);
+    }
+
+    pub fn unix_line_discard(&mut self, core: &mut ShellCore) {
+        self.kill(self.origin(), self.head, core);
+    }
+
+    pub fn unix_word_rubout(&mut self, core: &mut ShellCore) {
+        self.kill(self.blank_word_start(self.head), self.head, core);
+    }
+
+    pub fn kill_word(&mut self, core: &mut ShellCore) {
+        self.kill(self.head, self.word_end(self.head), core);
+    }
+
+    pub fn backward_kill_word(&mut self, core: &mut ShellCore) {
+        self.kill(self.word_start(self.head), self.head, core);
+    }
+
+    fn insert_str(&mut self, s: &str) {
+        for c in s.chars() {
+            self.chars.insert(self.head, c);
+            self.head += 1;
+        }
+        self.rewrite(true);
+    }
+
+    pub fn yank(&mut self, core: &mut ShellCore) {
+        let text = match core.kill_ring.last() {
+            Some(t) => t.clone(),
+            None => return self.cloop(),
+        };
+        self.save_undo();
+        self.insert_str(&text);
+        self.yank_len = text.chars().count();
+    }
+
+    /* replaces the text just yanked with the previous entry of the kill ring */
+    pub fn yank_pop(&mut self, core: &mut ShellCore) {
+        let after_yank = self.last_function == "yank" || self.last_function == "yank-pop";
+        if !after_yank || core.kill_ring.len() < 2 {
+            return self.cloop();
+        }
+
+        self.head -= self.yank_len;
+        self.chars.drain(self.head..self.head + self.yank_len);
+        core.kill_ring.rotate_right(1);
+
+        let text = core.kill_ring.last().unwrap().clone();
+        self.insert_str(&text);
+        self.yank_len = text.chars().count();
+    }
+
+    /* inserts the last word of a previous command. Repeating goes further back. */
+    pub fn yank_last_arg(&mut self, core: &mut ShellCore) {
+        let nth = match self.last_function.as_str() {
+            "insert-last-argument" | "yank-last-arg" => {
+                self.head -= self.yank_len;
+                self.chars.drain(self.head..self.head + self.yank_len);
+                self.yank_last_arg_nth + 1
+            }
+            _ => {
+                self.save_undo();
+                1
+            }
+        };
+
+        let word = match core.history_last_arg(nth) {
+            Some(w) => w,
+            None => {
+                self.yank_len = 0;
+                self.rewrite(true);
+                return self.cloop();
+            }
+        };
+
+        self.yank_last_arg_nth = nth;
+        self.insert_str(&word);
+        self.yank_len = word.chars().count();
+    }
+
+    pub fn forward_word(&mut self) {
+        self.head = self.word_end(self.head);
+        self.goto(self.head);
+        self.flush();
+    }
+
+    pub fn backward_word(&mut self) {
+        self.head = self.word_start(self.head);
+        self.goto(self.head);
+        self.flush();
+    }
+
+    /* capitalize-word, downcase-word, and upcase-word */
+    pub fn change_word_case(&mut self, function: &str) {
+        let end = self.word_end(self.head);
+        if end == self.head {
+            return self.cloop();
+        }
+        self.save_undo();
+
+        let mut head_of_word = true;
+        for c in self.chars[self.head..end].iter_mut() {
+            let upper = match function {
+                "upcase-word" => true,
+                "capitalize-word" => head_of_word && is_word_char(*c),
+                _ => false,
+            };
+            if is_word_char(*c) {
+                head_of_word = false;
+            }
+            *c = match upper {
+                true => c.to_uppercase().next().unwrap_or(*c),
+                false => c.to_lowercase().next().unwrap_or(*c),
+            };
+        }
+        self.head = end;
+        self.rewrite(true);
+    }
+
+    pub fn transpose_chars(&mut self) {
+        let len = self.chars.len();
+        if self.head == self.origin() || len < self.origin() + 2 {
+            return self.cloop();
+        }
+        self.save_undo();
+
+        if self.head == len {
+            self.chars.swap(len - 2, len - 1);
+        } else {
+            self.chars.swap(self.head - 1, self.head);
+            self.head += 1;
+        }
+        self.rewrite(true);
+    }
+
+    pub fn transpose_words(&mut self) {
+        let end2 = self.word_end(self.head);
+        let start2 = self.word_start(end2);
+        let start1 = self.word_start(start2);
+        let end1 = self.word_end(start1);
+
+        if start2 <= self.origin() || end1 > start2 || start1 == start2 {
+            return self.cloop();
+        }
+        self.save_undo();
+
+        let word1: Vec<char> = self.chars[start1..end1].to_vec();
+        let word2: Vec<char> = self.chars[start2..end2].to_vec();
+        self.chars.splice(start2..end2, word1);
+        self.chars.splice(start1..end1, word2);
+        self.head = end2;
+        self.rewrite(true);
+    }
+
+    pub fn clear_screen(&mut self) {
+        self.write(termion::clear::All.as_ref());
+        self.prompt_row = 1;
+        self.rewrite(true);
+    }
+}
@@ -0,0 +1,92 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use super::Terminal;
+use crate::core::builtins;
+use crate::error::input::InputError;
+use crate::ShellCore;
+use termion::cursor::DetectCursorPos;
+
+impl Terminal {
+    /* runs a command with the terminal out of the raw mode.
+     * The prompt is drawn again after the output of the command. */
+    fn run_outside(&mut self, core: &mut ShellCore, command: &str) -> i32 {
+        self.goto(0);
+        self.write(termion::clear::AfterCursor.as_ref());
+        self.flush();
+
+        let _ = self.stdout.suspend_raw_mode();
+        let status = builtins::eval(core, &["eval".to_string(), command.to_string()]);
+        let _ = self.stdout.activate_raw_mode();
+
+        self.prompt_row = match self.stdout.cursor_pos() {
+            Ok((_, row)) => row as usize,
+            Err(_) => 1,
+        };
+        status
+    }
+
+    fn set_line(&mut self, line: &str, point: usize) {
+        self.chars = self.prompt.chars().collect();
+        self.chars.extend(line.chars());
+        self.head = std::cmp::min(self.origin().saturating_add(point), self.chars.len());
+    }
+
+    /* for bind -x. The command can edit the line through READLINE_LINE and READLINE_POINT. */
+    pub fn execute_command(&mut self, core: &mut ShellCore, command: &str) {
+        let line = self.get_string(self.origin());
+        let point = (self.head - self.origin()).to_string();
+        let _ = core.db.set_param("READLINE_LINE", &line, None);
+        let _ = core.db.set_param("READLINE_POINT", &point, None);
+
+        self.run_outside(core, command);
+
+        let line = core.db.get_param("READLINE_LINE").unwrap_or_default();
+        let point = core.db.get_param("READLINE_POINT").unwrap_or_default();
+        let point = point.parse::<usize>().unwrap_or(usize::MAX);
+        core.db.unset("READLINE_LINE");
+        core.db.unset("READLINE_POINT");
+
+        self.save_undo();
+        self.set_line(&line, point);
+        self.rewrite(true);
+    }
+
+    /* edit-and-execute-command (C-x C-e) */
+    pub fn edit_and_execute(&mut self, core: &mut ShellCore) -> Result<bool, InputError> {
+        let default = match core.options.query("vi") {
+            true => "vi",
+            false => "emacs",
+        };
+        let editor = ["VISUAL", "EDITOR"]
+            .iter()
+            .map(|v| core.db.get_param(v).unwrap_or_default())
+            .find(|e| !e.is_empty())
+            .unwrap_or(default.to_string());
+
+        let path = std::env::temp_dir().join(format!("sush-edit-{}", std::process::id()));
+        let line = self.get_string(self.origin());
+        if std::fs::write(&path, line.clone() + "\n").is_err() {
+            self.cloop();
+            return Ok(false);
+        }
+
+        let command = format!("{} {}", editor, path.display());
+        let status = self.run_outside(core, &command);
+        let text = std::fs::read_to_string(&path).unwrap_or_default();
+        let _ = std::fs::remove_file(&path);
+
+        if status != 0 {
+            let point = self.head - self.origin();
+            self.set_line(&line, point);
+            self.rewrite(true);
+            return Ok(false);
+        }
+
+        self.set_line(text.trim_end_matches('\n'), usize::MAX);
+        self.rewrite(true);
+        self.write("\r\n");
+        self.chars.push('\n');
+        Ok(true)
+    }
+}
@@ -0,0 +1,275 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use super::Terminal;
+use crate::elements::command::Command;
+use crate::elements::script::Script;
+use crate::elements::word::Word;
+use crate::feeder::Feeder;
+use crate::utils::{file, file_check};
+use crate::ShellCore;
+use termion::{color, style};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Style {
+    #[default]
+    Plain,
+    Alias,
+    Builtin,
+    Function,
+    Command,
+    Unknown,
+    Quoted,
+    Variable,
+    Redirect,
+    Unbalanced,
+    Suggestion,
+}
+
+impl Style {
+    pub fn escape(&self) -> String {
+        let reset = style::Reset;
+        match self {
+            Style::Plain => reset.to_string(),
+            Style::Alias => format!("{}{}", reset, color::Fg(color::Cyan)),
+            Style::Builtin => format!("{}{}{}", reset, style::Bold, color::Fg(color::Cyan)),
+            Style::Function => format!("{}{}", reset, color::Fg(color::Magenta)),
+            Style::Command => format!("{}{}", reset, color::Fg(color::Green)),
+            Style::Unknown => format!("{}{}", reset, color::Fg(color::Red)),
+            Style::Quoted => format!("{}{}", reset, color::Fg(color::Yellow)),
+            Style::Variable => format!("{}{}", reset, color::Fg(color::LightBlue)),
+            Style::Redirect => format!("{}{}", reset, style::Bold),
+            Style::Unbalanced => format!("{}{}{}", reset, style::Underline, color::Fg(color::Red)),
+            Style::Suggestion => format!("{}{}", reset, color::Fg(color::LightBlack)),
+        }
+    }
+}
+
+fn command_style(name: &str, core: &mut ShellCore) -> Style {
+    if core.db.has_array_value("BASH_ALIASES", name) {
+        Style::Alias
+    } else if core.db.functions.contains_key(name) {
+        Style::Function
+    } else if core.builtins.contains_key(name) || core.substitution_builtins.contains_key(name) {
+        Style::Builtin
+    } else if name.contains('/') {
+        match file_check::is_executable(name) && !file_check::is_dir(name) {
+            true => Style::Command,
+            false => Style::Unknown,
+        }
+    } else {
+        match file::search_command(name) {
+            Some(_) => Style::Command,
+            None => Style::Unknown,
+        }
+    }
+}
+
+/* the position of the quote which is not closed */
+fn unbalanced_quote(chars: &[char]) -> Option<usize> {
+    let mut quote: Option<(char, usize)> = None;
+    let mut escaped = false;
+
+    for (i, c) in chars.iter().enumerate() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match (quote, c) {
+            (Some(('\'', _)), '\'') => quote = None,
+            (Some(('\'', _)), _) => {}
+            (_, '\\') => escaped = true,
+            (Some((q, _)), c) if q == *c => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"' | '`') => quote = Some((*c, i)),
+            _ => {}
+        }
+    }
+    quote.map(|q| q.1)
+}
+
+/* paints the chars of the buffer following the elements given by the parser */
+struct Painter<'a> {
+    chars: &'a [char],
+    styles: Vec<Style>,
+    pos: usize,
+}
+
+impl Painter<'_> {
+    fn find(&self, text: &str, from: usize) -> Option<usize> {
+        let target: Vec<char> = text.chars().collect();
+        if target.is_empty() || from + target.len() > self.chars.len() {
+            return None;
+        }
+        (from..=self.chars.len() - target.len()).find(|i| {
+            self.chars[*i..*i + target.len()] == target[..]
+                && self.styles[*i..*i + target.len()]
+                    .iter()
+                    .all(|s| *s == Style::Plain)
+        })
+    }
+
+    fn paint(&mut self, from: usize, len: usize, style: Style) {
+        let end = std::cmp::min(from + len, self.styles.len());
+        self.styles[from..end].iter_mut().for_each(|s| *s = style);
+    }
+
+    fn paint_word(&mut self, word: &Word, pos: usize) {
+        let mut pos = pos;
+        for sw in &word.subwords {
+            let text = sw.get_text();
+            let style = if text.starts_with(['\'', '"']) || text.starts_with("$'") {
+                Style::Quoted
+            } else if text.starts_with(['$', '`']) && text.len() > 1 {
+                Style::Variable
+            } else {
+                Style::Plain
+            };
+            self.paint(pos, text.chars().count(), style);
+            pos += text.chars().count();
+        }
+    }
+
+    fn paint_command(&mut self, com: &mut Box<dyn Command>, core: &mut ShellCore) {
+        let start = self.pos;
+        for script in com.get_scripts() {
+            self.paint_script(script, core);
+        }
+
+        for (i, word) in com.get_words().into_iter().enumerate() {
+            let pos = match self.find(&word.text, self.pos) {
+                Some(p) => p,
+                None => continue,
+            };
+            let literal = !word.text.contains(['\'', '"', '$', '`', '\\']);
+            match i == 0 && literal {
+                true => self.paint(
+                    pos,
+                    word.text.chars().count(),
+                    command_style(&word.text, core),
+                ),
+                false => self.paint_word(word, pos),
+            }
+            self.pos = pos + word.text.chars().count();
+        }
+
+        for r in com.get_redirects().iter() {
+            if let Some(pos) = self.find(r.text.trim(), start) {
+                let len = r.text.trim().chars().count();
+                self.paint(pos, len, Style::Redirect);
+                self.pos = std::cmp::max(self.pos, pos + len);
+            }
+        }
+    }
+
+    fn paint_script(&mut self, script: &mut Script, core: &mut ShellCore) {
+        for job in script.jobs.iter_mut() {
+            for pipeline in job.pipelines.iter_mut() {
+                for com in pipeline.commands.iter_mut() {
+                    self.paint_command(com, core);
+                }
+            }
+        }
+    }
+}
+
+impl Terminal {
+    /* parses the buffer without the alias expansion */
+    fn parse_buffer(core: &mut ShellCore, text: &str) -> Option<Script> {
+        let exit_status = core.db.exit_status;
+        let flags = core.db.flags.clone();
+        let expand_aliases = core.shopts.query("expand_aliases");
+        core.db.flags.retain(|f| f != 'i');
+        let _ = core.shopts.set("expand_aliases", false);
+
+        let mut feeder = Feeder::new(&(text.to_string() + "\n"));
+        let ans = Script::parse(&mut feeder, core, true);
+
+        core.db.flags = flags;
+        let _ = core.shopts.set("expand_aliases", expand_aliases);
+        core.db.exit_status = exit_status;
+        core.alias_memo.clear();
+        ans.ok().flatten()
+    }
+
+    fn highlight(&mut self, core: &mut ShellCore) {
+        let chars = self.chars[self.origin()..].to_vec();
+        let mut painter = Painter {
+            chars: &chars,
+            styles: vec![Style::Plain; chars.len()],
+            pos: 0,
+        };
+
+        let text: String = chars.iter().collect();
+        match Self::parse_buffer(core, &text) {
+            Some(mut script) => painter.paint_script(&mut script, core),
+            None => {
+                if let Some(pos) = unbalanced_quote(&chars) {
+                    painter.paint(pos, chars.len() - pos, Style::Unbalanced);
+                }
+            }
+        }
+        self.styles = painter.styles;
+    }
+
+    /* highlighting and autosuggestion, which are enabled by the variables */
+    pub fn decorate(&mut self, core: &mut ShellCore) {
+        let highlight = !core
+            .db
+            .get_param("SUSH_HIGHLIGHT")
+            .unwrap_or_default()
+            .is_empty();
+        let suggest = !core
+            .db
+            .get_param("SUSH_AUTOSUGGEST")
+            .unwrap_or_default()
+            .is_empty();
+        if self.plain
+            || (!highlight && !suggest && self.styles.is_empty() && self.suggestion.is_empty())
+        {
+            return;
+        }
+
+        self.styles.clear();
+        if highlight {
+            self.highlight(core);
+        }
+        self.suggestion.clear();
+        if suggest {
+            self.set_suggestion(core);
+        }
+        self.rewrite(true);
+    }
+
+    /* the buffer with the escape sequences for the colors */
+    pub fn decorated_text(&self) -> String {
+        let ps2 = self.ps2.as_ref().map(|p| p.0.as_str()).unwrap_or("");
+        let mut ans = String::new();
+        let mut current = Style::Plain;
+
+        for (i, c) in self.chars[self.origin()..].iter().enumerate() {
+            let style = match *c {
+                '\n' => Style::Plain,
+                _ => self.styles.get(i).copied().unwrap_or_default(),
+            };
+            if style != current {
+                ans += &style.escape();
+                current = style;
+            }
+            match c {
+                '\n' => ans += &format!("\n\r{}", ps2),
+                c => ans.push(*c),
+            }
+        }
+
+        if !self.suggestion.is_empty() {
+            ans += &Style::Suggestion.escape();
+            ans += &self.suggestion;
+            current = Style::Suggestion;
+        }
+        if current != Style::Plain {
+            ans += &Style::Plain.escape();
+        }
+        ans
+    }
+}
@@ -2,97 +2,279 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use super::Terminal;
+use crate::core::keymap::Binding;
 use crate::error::input::InputError;
 use crate::ShellCore;
 use std::sync::atomic::Ordering::Relaxed;
 use termion::event;
-use termion::event::Key;
+use termion::event::{Event, Key};
+
+/* the bytes of a key in the notation of the key bindings */
+pub fn key_bytes(key: &Key) -> Vec<u8> {
+    let seq = |s: &str| s.as_bytes().to_vec();
+    match key {
+        Key::Char('\n') => vec![b'\r'],
+        Key::Char(c) => c.to_string().into_bytes(),
+        Key::Ctrl(c @ '4'..='7') => vec![*c as u8 - b'4' + 0x1c],
+        Key::Ctrl(c) => vec![*c as u8 & 0x1f],
+        Key::Alt(c) => [vec![0x1b], c.to_string().into_bytes()].concat(),
+        Key::Backspace => vec![0x7f],
+        Key::Esc => vec![0x1b],
+        Key::Null => vec![0],
+        Key::Up => seq("\x1b[A"),
+        Key::Down => seq("\x1b[B"),
+        Key::Right => seq("\x1b[C"),
+        Key::Left => seq("\x1b[D"),
+        Key::Home => seq("\x1b[H"),
+        Key::End => seq("\x1b[F"),
+        Key::Insert => seq("\x1b[2~"),
+        Key::Delete => seq("\x1b[3~"),
+        Key::PageUp => seq("\x1b[5~"),
+        Key::PageDown => seq("\x1b[6~"),
+        Key::BackTab => seq("\x1b[Z"),
+        Key::ShiftUp => seq("\x1b[1;2A"),
+        Key::ShiftDown => seq("\x1b[1;2B"),
+        Key::ShiftRight => seq("\x1b[1;2C"),
+        Key::ShiftLeft => seq("\x1b[1;2D"),
+        Key::AltUp => seq("\x1b[1;3A"),
+        Key::AltDown => seq("\x1b[1;3B"),
+        Key::AltRight => seq("\x1b[1;3C"),
+        Key::AltLeft => seq("\x1b[1;3D"),
+        Key::CtrlUp => seq("\x1b[1;5A"),
+        Key::CtrlDown => seq("\x1b[1;5B"),
+        Key::CtrlRight => seq("\x1b[1;5C"),
+        Key::CtrlLeft => seq("\x1b[1;5D"),
+        Key::CtrlHome => seq("\x1b[1;5H"),
+        Key::CtrlEnd => seq("\x1b[1;5F"),
+        _ => vec![],
+    }
+}
 
 pub fn action(core: &mut ShellCore, term: &mut Terminal, c: &Key) -> Result<bool, InputError> {
-    match c {
-        event::Key::Ctrl(ch) => ctrl(core, term, *ch)?,
-        event::Key::Down | event::Key::Left | event::Key::Right | event::Key::Up => {
-            arrow(term, core, c)
+    if term.search.is_some() {
+        return term.search_action(core, c);
+    }
+    let map = match core.options.query("vi") {
+        true => term.vi_keymap(),
+        false => "emacs",
+    };
+    if core.options.query("vi") && !term.is_vi_rebound(core, c) {
+        if let Some(ans) = term.vi_action(core, c) {
+            term.last_function.clear();
+            return ans;
         }
-        event::Key::Backspace => term.backspace(),
-        event::Key::Delete => term.delete(),
-        event::Key::Char(c) => return char_key(term, core, c),
-        _ => {}
     }
-    Ok(false)
-}
 
-fn ctrl(core: &mut ShellCore, term: &mut Terminal, c: char) -> Result<(), InputError> {
     match c {
-        'a' => term.goto_origin(),
-        'b' => term.shift_cursor(-1),
-        'c' => {
+        Key::Ctrl('c') => {
             core.sigint.store(true, Relaxed);
+            term.pending_keys.clear();
             term.goto(term.chars.len());
             term.write("^C\r\n");
             return Err(InputError::Interrupt);
         }
-        'd' => {
-            if term.chars.len() == term.prompt.chars().count() {
-                term.write("\r\n");
-                return Err(InputError::Eof);
-            } else {
-                term.delete();
-            }
+        Key::Ctrl('d') if term.chars.len() == term.origin() && term.pending_keys.is_empty() => {
+            term.write("\r\n");
+            return Err(InputError::Eof);
+        }
+        Key::Down | Key::Left | Key::Right | Key::Up if term.tab_num > 1 => {
+            arrow(term, core, c);
+            return Ok(false);
         }
-        'e' => term.goto_end(),
-        'f' => term.shift_cursor(1),
         _ => {}
     }
-    Ok(())
+
+    let bytes = key_bytes(c);
+    if bytes.is_empty() {
+        return Ok(false);
+    }
+    term.pending_keys.extend(bytes.iter());
+    if core.keymap.is_prefix(map, &term.pending_keys) {
+        return Ok(false);
+    }
+
+    let keys = std::mem::take(&mut term.pending_keys);
+    match core.keymap.bindings(map).get(&keys).cloned() {
+        Some(Binding::Function(f)) => run_function(core, term, &f, c),
+        Some(Binding::Macro(m)) => run_macro(core, term, &m),
+        Some(Binding::Command(com)) => {
+            term.execute_command(core, &com);
+            Ok(false)
+        }
+        None if keys == bytes && matches!(c, Key::Char(_)) => {
+            run_function(core, term, "self-insert", c)
+        }
+        None => {
+            term.cloop();
+            Ok(false)
+        }
+    }
 }
 
-fn arrow(term: &mut Terminal, core: &mut ShellCore, key: &event::Key) {
-    if term.tab_num > 1 {
-        match key {
-            event::Key::Down => term.tab_row += 1,
-            event::Key::Up => term.tab_row -= 1,
-            event::Key::Right => term.tab_col += 1,
-            event::Key::Left => term.tab_col -= 1,
-            _ => {}
-        }
-        let _ = term.completion(core);
-    } else {
-        match key {
-            event::Key::Down => term.call_history(-1, core),
-            event::Key::Up => term.call_history(1, core),
-            event::Key::Right => term.shift_cursor(1),
-            event::Key::Left => term.shift_cursor(-1),
-            _ => {}
+/* inputs the keys of a macro one by one */
+fn run_macro(core: &mut ShellCore, term: &mut Terminal, text: &[u8]) -> Result<bool, InputError> {
+    if term.macro_level > 16 {
+        term.cloop();
+        return Ok(false);
+    }
+
+    term.macro_level += 1;
+    let mut bytes = text.iter().map(|b| Ok(*b));
+    let mut ans = Ok(false);
+    while let Some(Ok(b)) = bytes.next() {
+        if let Ok(Event::Key(k)) = event::parse_event(b, &mut bytes) {
+            ans = action(core, term, &k);
+            if !matches!(ans, Ok(false)) {
+                break;
+            }
+            term.prev_key = k;
         }
     }
+    term.macro_level -= 1;
+    ans
 }
 
-fn char_key(term: &mut Terminal, core: &mut ShellCore, c: &char) -> Result<bool, InputError> {
-    match c {
-        '\n' => {
-            if !term.completion_candidate.is_empty() {
-                term.set_double_tab_completion(core);
-            } else {
-                term.goto(term.chars.len());
-                term.write("\r\n");
-                term.chars.push('\n');
-                return Ok(true);
+fn run_function(
+    core: &mut ShellCore,
+    term: &mut Terminal,
+    name: &str,
+    key: &Key,
+) -> Result<bool, InputError> {
+    match name {
+        "abort" => term.cloop(),
+        "accept-line" => {
+            term.last_function = name.to_string();
+            return accept_line(term, core);
+        }
+        "backward-char" => term.shift_cursor(-1),
+        "backward-delete-char" => {
+            term.save_undo();
+            term.backspace();
+        }
+        "backward-kill-line" => term.unix_line_discard(core),
+        "backward-kill-word" => term.backward_kill_word(core),
+        "backward-word" => term.backward_word(),
+        "beginning-of-history" => {
+            let pos = core.oldest_history_pos();
+            term.set_history_pos(pos, core);
+        }
+        "beginning-of-line" => term.goto_origin(),
+        "capitalize-word" | "downcase-word" | "upcase-word" => term.change_word_case(name),
+        "clear-screen" => term.clear_screen(),
+        "complete" => complete(term, core),
+        "delete-char" => {
+            term.save_undo();
+            term.delete();
+        }
+        "edit-and-execute-command" => return term.edit_and_execute(core),
+        "emacs-editing-mode" => {
+            let _ = core.options.set("emacs", true);
+        }
+        "end-of-history" => term.set_history_pos(0, core),
+        "end-of-line" => {
+            if !term.accept_suggestion() {
+                term.goto_end()
+            }
+        }
+        "forward-char" => {
+            if !term.accept_suggestion() {
+                term.shift_cursor(1)
             }
         }
-        '\t' => {
-            if term.tab_num == 0 || term.prev_key == event::Key::Char('\t') {
-                term.tab_num += 1;
+        "forward-search-history" => term.start_search(true, core),
+        "forward-word" => term.forward_word(),
+        "history-search-backward" => term.history_search(false, core),
+        "history-search-forward" => term.history_search(true, core),
+        "insert-last-argument" | "yank-last-arg" => term.yank_last_arg(core),
+        "kill-line" => term.kill_line(core),
+        "kill-whole-line" => term.kill_whole_line(core),
+        "kill-word" => term.kill_word(core),
+        "next-history" => {
+            if !term.move_line(false) {
+                term.call_history(-1, core)
             }
-            if term.tab_num == 2 {
-                term.tab_row = -1;
-                term.tab_col = 0;
-            } else if term.tab_num > 2 {
-                term.tab_row += 1;
+        }
+        "previous-history" => {
+            if !term.move_line(true) {
+                term.call_history(1, core)
+            }
+        }
+        "re-read-init-file" => core.load_inputrc(),
+        "redraw-current-line" => term.rewrite(true),
+        "reverse-search-history" => term.start_search(false, core),
+        "self-insert" => match key {
+            Key::Char(c) => {
+                if term.last_function != "self-insert" || term.undo_stack.is_empty() {
+                    term.save_undo();
+                }
+                term.insert(*c)
             }
-            let _ = term.completion(core);
+            _ => term.cloop(),
+        },
+        "tab-insert" => {
+            term.save_undo();
+            term.insert('\t');
         }
-        c => term.insert(*c),
+        "transpose-chars" => term.transpose_chars(),
+        "transpose-words" => term.transpose_words(),
+        "undo" | "vi-undo" => term.undo(),
+        "unix-line-discard" => term.unix_line_discard(core),
+        "unix-word-rubout" | "vi-unix-word-rubout" => term.unix_word_rubout(core),
+        "vi-editing-mode" => {
+            let _ = core.options.set("vi", true);
+            term.vi_start();
+        }
+        "yank" => term.yank(core),
+        "yank-pop" => term.yank_pop(core),
+        _ => term.cloop(),
     }
+    term.last_function = name.to_string();
     Ok(false)
 }
+
+fn arrow(term: &mut Terminal, core: &mut ShellCore, key: &event::Key) {
+    match key {
+        event::Key::Down => term.tab_row += 1,
+        event::Key::Up => term.tab_row -= 1,
+        event::Key::Right => term.tab_col += 1,
+        event::Key::Left => term.tab_col -= 1,
+        _ => {}
+    }
+    let _ = term.completion(core);
+}
+
+fn accept_line(term: &mut Terminal, core: &mut ShellCore) -> Result<bool, InputError> {
+    if !term.completion_candidate.is_empty() {
+        term.set_double_tab_completion(core);
+        return Ok(false);
+    }
+
+    if !term.suggestion.is_empty() {
+        term.suggestion.clear();
+        term.rewrite(true);
+    }
+
+    let text = term.get_string(term.origin());
+    if !term.plain && Terminal::is_incomplete(core, &text) {
+        term.continue_line(core);
+        return Ok(false);
+    }
+
+    term.goto(term.chars.len());
+    term.write("\r\n");
+    term.chars.push('\n');
+    Ok(true)
+}
+
+fn complete(term: &mut Terminal, core: &mut ShellCore) {
+    if term.tab_num == 0 || term.last_function == "complete" {
+        term.tab_num += 1;
+    }
+    if term.tab_num == 2 {
+        term.tab_row = -1;
+        term.tab_col = 0;
+    } else if term.tab_num > 2 {
+        term.tab_row += 1;
+    }
+    let _ = term.completion(core);
+}
@@ -0,0 +1,89 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use super::Terminal;
+use crate::elements::script::Script;
+use crate::error::input::InputError;
+use crate::error::parse::ParseError;
+use crate::feeder::Feeder;
+use crate::ShellCore;
+
+impl Terminal {
+    /* true if the parser needs more lines (an open single or double quote, if without fi,
+     * a trailing backslash ...) */
+    pub fn is_incomplete(core: &mut ShellCore, text: &str) -> bool {
+        if text.chars().rev().take_while(|c| *c == '\\').count() % 2 == 1 {
+            return true;
+        }
+
+        let exit_status = core.db.exit_status;
+        let mut feeder = Feeder::new(&(text.to_string() + "\n"));
+        let ans = matches!(
+            Script::parse(&mut feeder, core, false),
+            Err(ParseError::Input(InputError::Eof))
+        );
+        core.db.exit_status = exit_status;
+        ans
+    }
+
+    pub fn set_ps2(&mut self, core: &mut ShellCore) {
+        if self.ps2.is_some() {
+            return;
+        }
+
+        let raw_prompt = core.db.get_param("PS2").unwrap_or_default();
+        let replaced_prompt = Self::make_prompt_string(&raw_prompt, core);
+        let width = Self::make_width_map(&replaced_prompt).iter().sum();
+        self.ps2 = Some((replaced_prompt.replace(['\x01', '\x02'], ""), width));
+    }
+
+    /* continues the editing on a new line instead of accepting the buffer */
+    pub fn continue_line(&mut self, core: &mut ShellCore) {
+        self.set_ps2(core);
+        self.save_undo();
+        self.chars.push('\n');
+        self.head = self.chars.len();
+        self.rewrite(false);
+    }
+
+    fn row_start(&self, pos: usize) -> usize {
+        let mut ans = pos;
+        while ans > self.origin() && self.chars[ans - 1] != '\n' {
+            ans -= 1;
+        }
+        ans
+    }
+
+    fn row_end(&self, pos: usize) -> usize {
+        let mut ans = pos;
+        while ans < self.chars.len() && self.chars[ans] != '\n' {
+            ans += 1;
+        }
+        ans
+    }
+
+    /* moves the cursor to the upper or lower line in the buffer.
+     * false if the cursor is already on the first or last line. */
+    pub fn move_line(&mut self, up: bool) -> bool {
+        let start = self.row_start(self.head);
+        let column = self.head - start;
+
+        self.head = if up {
+            if start == self.origin() {
+                return false;
+            }
+            let upper = self.row_start(start - 1);
+            std::cmp::min(upper + column, start - 1)
+        } else {
+            let end = self.row_end(self.head);
+            if end == self.chars.len() {
+                return false;
+            }
+            std::cmp::min(end + 1 + column, self.row_end(end + 1))
+        };
+
+        self.goto(self.head);
+        self.flush();
+        true
+    }
+}
@@ -0,0 +1,266 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use super::Terminal;
+use crate::utils::{clock, file};
+use crate::{file_check, ShellCore};
+use nix::unistd;
+use nix::unistd::User;
+use std::fs::File;
+use std::path::Path;
+use termion::input::TermRead;
+use unicode_width::UnicodeWidthChar;
+
+/* protects a value from the expansion with promptvars */
+fn quote(s: &str, core: &ShellCore) -> String {
+    match core.shopts.query("promptvars") {
+        true => s
+            .replace('\\', "\\\\")
+            .replace('$', "\\$")
+            .replace('`', "\\`"),
+        false => s.to_string(),
+    }
+}
+
+fn basename(path: &str) -> String {
+    match path.rsplit_once('/') {
+        Some((_, "")) | None => path.to_string(),
+        Some((_, name)) => name.to_string(),
+    }
+}
+
+/* PROMPT_DIRTRIM: the leading directories are replaced with ... */
+fn trim_dir(dir: &str, core: &mut ShellCore) -> String {
+    let num = match core
+        .db
+        .get_param("PROMPT_DIRTRIM")
+        .unwrap_or_default()
+        .parse::<usize>()
+    {
+        Ok(n) if n > 0 => n,
+        _ => return dir.to_string(),
+    };
+
+    let (prefix, rest) = match dir.strip_prefix("~/") {
+        Some(rest) => ("~/", rest),
+        None => ("", dir),
+    };
+    let pos = match rest.rmatch_indices('/').nth(num - 1) {
+        Some((pos, _)) => pos,
+        None => return dir.to_string(),
+    };
+
+    let ans = format!("{}...{}", prefix, &rest[pos..]);
+    match ans.len() < dir.len() {
+        true => ans,
+        false => dir.to_string(),
+    }
+}
+
+impl Terminal {
+    fn get_branch(cwd: &str) -> String {
+        let mut dirs: Vec<String> = cwd.split("/").map(|s| s.to_string()).collect();
+        while !dirs.is_empty() {
+            let path = dirs.join("/") + "/.git/HEAD";
+            dirs.pop();
+
+            if !file_check::is_regular_file(&path) {
+                continue;
+            }
+
+            if let Ok(mut f) = File::open(Path::new(&path)) {
+                return match f.read_line() {
+                    Ok(Some(s)) => s.replace("ref: refs/heads/", "") + "🌵",
+                    _ => "".to_string(),
+                };
+            }
+        }
+
+        "".to_string()
+    }
+
+    fn user_name() -> String {
+        match User::from_uid(unistd::getuid()) {
+            Ok(Some(u)) => u.name,
+            _ => "".to_string(),
+        }
+    }
+
+    fn host_name() -> String {
+        match unistd::gethostname() {
+            Ok(h) => file::oss_to_name(&h),
+            _ => "".to_string(),
+        }
+    }
+
+    fn working_dir(core: &mut ShellCore) -> String {
+        match core.db.get_param("PWD") {
+            Ok(d) if !d.is_empty() => d,
+            _ => match unistd::getcwd() {
+                Ok(p) => file::buf_to_name(&p),
+                _ => "".to_string(),
+            },
+        }
+    }
+
+    /* \w: the home directory is replaced with ~ */
+    fn tilde_dir(core: &mut ShellCore) -> String {
+        let cwd = Self::working_dir(core);
+        let home = core.db.get_param("HOME").unwrap_or_default();
+        if !home.is_empty() && home != "/" {
+            if cwd == home {
+                return "~".to_string();
+            }
+            if let Some(rest) = cwd.strip_prefix(&(home.clone() + "/")) {
+                return format!("~/{}", rest);
+            }
+        }
+        cwd
+    }
+
+    /* \W */
+    fn dir_basename(core: &mut ShellCore) -> String {
+        let cwd = Self::working_dir(core);
+        match cwd == core.db.get_param("HOME").unwrap_or_default() {
+            true => "~".to_string(),
+            false => basename(&cwd),
+        }
+    }
+
+    fn tty_name() -> String {
+        match unistd::ttyname(std::io::stdin()) {
+            Ok(p) => basename(&file::buf_to_name(&p)),
+            Err(_) => "tty".to_string(),
+        }
+    }
+
+    /* the number of the line being entered. HISTSIZE may have been changed. */
+    fn history_number(core: &mut ShellCore) -> usize {
+        core.stifle_history(0);
+        let pos = core.history_list().len();
+        core.history_number(pos)
+    }
+
+    fn command_number(core: &ShellCore) -> usize {
+        core.history.iter().filter(|h| !h.is_empty()).count() + 1
+    }
+
+    /* \nnn: up to three octal digits */
+    fn octal(chars: &[char], pos: &mut usize) -> char {
+        let mut n = 0;
+        let mut len = 0;
+        while len < 3 && *pos < chars.len() && chars[*pos].is_digit(8) {
+            n = n * 8 + chars[*pos].to_digit(8).unwrap();
+            *pos += 1;
+            len += 1;
+        }
+        char::from_u32(n & 0xff).unwrap_or('\0')
+    }
+
+    /* decodes the backslash escapes. \[ and \] become \x01 and \x02. */
+    fn decode_prompt(raw: &str, core: &mut ShellCore) -> String {
+        let chars: Vec<char> = raw.chars().collect();
+        let mut ans = String::new();
+        let mut pos = 0;
+
+        while pos < chars.len() {
+            let c = chars[pos];
+            pos += 1;
+            if c != '\\' || pos == chars.len() {
+                ans.push(c);
+                continue;
+            }
+
+            let e = chars[pos];
+            pos += 1;
+            let version = env!("CARGO_PKG_VERSION");
+            let text = match e {
+                'a' => "\x07".to_string(),
+                'd' => clock::strftime("%a %b %d", clock::epoch_seconds()),
+                'D' if chars.get(pos) == Some(&'{') => {
+                    let fmt: String = chars[pos + 1..].iter().take_while(|c| **c != '}').collect();
+                    pos = std::cmp::min(pos + fmt.chars().count() + 2, chars.len());
+                    let fmt = if fmt.is_empty() { "%X" } else { &fmt };
+                    clock::strftime(fmt, clock::epoch_seconds())
+                }
+                'e' => "\x1b".to_string(),
+                'h' => quote(Self::host_name().split('.').next().unwrap_or(""), core),
+                'H' => quote(&Self::host_name(), core),
+                'j' => core.job_table.len().to_string(),
+                'l' => quote(&Self::tty_name(), core),
+                'n' => "\n".to_string(),
+                'r' => "\r".to_string(),
+                's' => {
+                    let name = core.db.get_param("0").unwrap_or_default();
+                    quote(&basename(&name), core)
+                }
+                't' => clock::strftime("%H:%M:%S", clock::epoch_seconds()),
+                'T' => clock::strftime("%I:%M:%S", clock::epoch_seconds()),
+                '@' => clock::strftime("%I:%M %p", clock::epoch_seconds()),
+                'A' => clock::strftime("%H:%M", clock::epoch_seconds()),
+                'u' => quote(&Self::user_name(), core),
+                'v' => version
+                    .rsplitn(2, '.')
+                    .last()
+                    .unwrap_or(version)
+                    .to_string(),
+                'V' => version.to_string(),
+                'w' => {
+                    let dir = Self::tilde_dir(core);
+                    quote(&trim_dir(&dir, core), core)
+                }
+                'W' => {
+                    let dir = Self::dir_basename(core);
+                    quote(&dir, core)
+                }
+                'b' => {
+                    let cwd = Self::working_dir(core);
+                    quote(&Self::get_branch(&cwd), core)
+                }
+                '!' => Self::history_number(core).to_string(),
+                '#' => Self::command_number(core).to_string(),
+                '$' => match unistd::geteuid().is_root() {
+                    true => "#".to_string(),
+                    false => "$".to_string(),
+                },
+                '0'..='7' => {
+                    pos -= 1;
+                    Self::octal(&chars, &mut pos).to_string()
+                }
+                '\\' => quote("\\", core),
+                '[' => "\x01".to_string(),
+                ']' => "\x02".to_string(),
+                e => quote("\\", core) + &e.to_string(),
+            };
+            ans += &text;
+        }
+        ans
+    }
+
+    pub fn make_prompt_string(raw: &str, core: &mut ShellCore) -> String {
+        let decoded = Self::decode_prompt(raw, core);
+        match core.shopts.query("promptvars") {
+            true => core.expand_prompt(&decoded),
+            false => decoded,
+        }
+    }
+
+    /* the width of each char. Chars between \x01 and \x02 have no width. */
+    pub fn make_width_map(prompt: &str) -> Vec<usize> {
+        let mut in_escape = false;
+        let mut ans = vec![];
+        for c in prompt.chars() {
+            if c == '\x01' || c == '\x02' {
+                in_escape = c == '\x01';
+                continue;
+            }
+
+            let wid = match in_escape {
+                true => 0,
+                false => UnicodeWidthChar::width(c).unwrap_or(0),
+            };
+            ans.push(wid);
+        }
+        ans
+    }
+}
@@ -0,0 +1,194 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use super::{key, Terminal};
+use crate::error::input::InputError;
+use crate::ShellCore;
+use termion::event::Key;
+use termion::style;
+
+#[derive(Debug, Default)]
+pub struct Search {
+    query: String,
+    forward: bool,
+    start: usize,
+    pos: usize,
+    line: String,
+    failed: bool,
+    original: (Vec<char>, usize),
+}
+
+impl Terminal {
+    /* Ctrl-R and Ctrl-S. While searching, they go to the next match. */
+    pub fn start_search(&mut self, forward: bool, core: &mut ShellCore) {
+        match self.search.as_mut() {
+            Some(s) => {
+                s.forward = forward;
+                let from = match forward {
+                    true => s.pos.checked_sub(1),
+                    false => Some(s.pos + 1),
+                };
+                if let Some(from) = from {
+                    self.find(from, core);
+                }
+            }
+            None => {
+                self.search = Some(Search {
+                    forward,
+                    start: self.hist_ptr,
+                    pos: self.hist_ptr,
+                    original: (self.chars.clone(), self.head),
+                    ..Default::default()
+                });
+            }
+        }
+        self.show_search();
+    }
+
+    fn find(&mut self, from: usize, core: &mut ShellCore) {
+        let s = self.search.as_mut().unwrap();
+        match core.search_history(&s.query, from, s.forward) {
+            Some((pos, line)) => {
+                s.pos = pos;
+                s.line = line.replace("↵ \0", "\n");
+                s.failed = false;
+            }
+            None => s.failed = true,
+        }
+    }
+
+    fn match_position(s: &Search) -> usize {
+        let found = match s.forward {
+            true => s.line.find(&s.query),
+            false => s.line.rfind(&s.query),
+        };
+        found.unwrap_or(s.line.len())
+    }
+
+    fn show_search(&mut self) {
+        let s = self.search.as_ref().unwrap();
+        let label = match (s.failed, s.forward) {
+            (false, false) => "(reverse-i-search)",
+            (false, true) => "(i-search)",
+            (true, false) => "(failed reverse-i-search)",
+            (true, true) => "(failed i-search)",
+        };
+
+        let p = Self::match_position(s);
+        let q = match p < s.line.len() {
+            true => s.query.len(),
+            false => 0,
+        };
+        let text = format!(
+            "{}`{}': {}{}{}{}{}",
+            label,
+            &s.query,
+            &s.line[..p],
+            style::Invert,
+            &s.line[p..p + q],
+            style::NoInvert,
+            &s.line[p + q..]
+        );
+
+        self.goto(0);
+        self.write(termion::clear::AfterCursor.as_ref());
+        self.write(&text.replace("\n", "\r\n"));
+        self.flush();
+    }
+
+    /* leaves the search with the matched line in the buffer */
+    fn accept_search(&mut self) {
+        let s = match self.search.take() {
+            Some(s) => s,
+            None => return,
+        };
+
+        if s.line.is_empty() {
+            (self.chars, self.head) = s.original;
+        } else {
+            let pos = Self::match_position(&s);
+            self.chars = self.prompt.chars().collect();
+            self.head = self.chars.len() + s.line[..pos].chars().count();
+            self.chars.extend(s.line.chars());
+            self.hist_ptr = s.pos;
+        }
+        self.rewrite(true);
+    }
+
+    fn abort_search(&mut self) {
+        if let Some(s) = self.search.take() {
+            (self.chars, self.head) = s.original;
+            self.hist_ptr = s.start;
+        }
+        self.rewrite(true);
+    }
+
+    /* history-search-backward/forward: entries starting with the text before the cursor */
+    pub fn history_search(&mut self, forward: bool, core: &mut ShellCore) {
+        let prefix: String = self.chars[self.origin()..self.head].iter().collect();
+        if prefix.is_empty() {
+            let inc = if forward { -1 } else { 1 };
+            return self.call_history(inc, core);
+        }
+
+        let current = self.get_string(self.origin());
+        let mut from = match forward {
+            true => self.hist_ptr.checked_sub(1),
+            false => Some(self.hist_ptr + 1),
+        };
+        while let Some(f) = from {
+            let (pos, line) = match core.search_history(&prefix, f, forward) {
+                Some(found) => found,
+                None => break,
+            };
+            if line.starts_with(&prefix) && line.replace("↵ \0", "\n") != current {
+                let head = self.head;
+                self.set_history_pos(pos, core);
+                self.head = head;
+                self.goto(self.head);
+                self.flush();
+                return;
+            }
+            from = match forward {
+                true => pos.checked_sub(1),
+                false => Some(pos + 1),
+            };
+        }
+        self.cloop();
+    }
+
+    pub fn search_action(&mut self, core: &mut ShellCore, c: &Key) -> Result<bool, InputError> {
+        match c {
+            Key::Ctrl('r') => self.start_search(false, core),
+            Key::Ctrl('s') => self.start_search(true, core),
+            Key::Ctrl('g') => self.abort_search(),
+            Key::Char(ch) if *ch != '\n' && *ch != '\t' => {
+                let s = self.search.as_mut().unwrap();
+                s.query.push(*ch);
+                let from = s.pos;
+                self.find(from, core);
+                self.show_search();
+            }
+            Key::Backspace => {
+                let s = self.search.as_mut().unwrap();
+                s.query.pop();
+                s.pos = s.start;
+                s.line.clear();
+                s.failed = false;
+                let from = s.start;
+                self.find(from, core);
+                self.show_search();
+            }
+            Key::Esc => self.accept_search(),
+            Key::Ctrl('c') => {
+                self.abort_search();
+                return key::action(core, self, c);
+            }
+            _ => {
+                self.accept_search();
+                return key::action(core, self, c);
+            }
+        }
+        Ok(false)
+    }
+}
@@ -0,0 +1,49 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use super::Terminal;
+use crate::ShellCore;
+use unicode_width::UnicodeWidthChar;
+
+impl Terminal {
+    /* the rest of the newest history entry which starts with the buffer */
+    pub fn set_suggestion(&mut self, core: &mut ShellCore) {
+        let line = self.get_string(self.origin());
+        if line.trim().is_empty() || line.contains('\n') || self.head != self.chars.len() {
+            return;
+        }
+
+        let entry = match core.history_suggestion(&line) {
+            Some(e) => e,
+            None => return,
+        };
+
+        /* only the part which fits in the current row */
+        let mut space = self
+            .size
+            .0
+            .saturating_sub(self.head_to_cursor_pos(self.head, 0).0);
+        for c in entry[line.len()..].chars() {
+            let w = UnicodeWidthChar::width(c).unwrap_or(0);
+            if w > space {
+                break;
+            }
+            space -= w;
+            self.suggestion.push(c);
+        }
+    }
+
+    /* inserts the suggestion by forward-char or end-of-line at the end of the buffer */
+    pub fn accept_suggestion(&mut self) -> bool {
+        if self.suggestion.is_empty() || self.head != self.chars.len() {
+            return false;
+        }
+
+        self.save_undo();
+        let suggestion = std::mem::take(&mut self.suggestion);
+        self.chars.extend(suggestion.chars());
+        self.head = self.chars.len();
+        self.rewrite(true);
+        true
+    }
+}
@@ -0,0 +1,683 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use super::key::key_bytes;
+use super::Terminal;
+use crate::error::input::InputError;
+use crate::ShellCore;
+use termion::event::Key;
+
+const MOTIONS: &str = "hl wbeWBE0^$fFtT;,";
+const COMMANDS: &str = "xXpPu.iaIADCsSrjk/?nN~";
+
+#[derive(Debug, Default)]
+pub struct ViState {
+    command_mode: bool,
+    keys: Vec<char>,
+    /* keys and inserted text of the last change for `.` */
+    last_change: Option<(Vec<char>, String)>,
+    recording: Option<Vec<char>>,
+    inserted: String,
+    last_find: Option<(char, char)>,
+    search_input: Option<(char, String)>,
+    last_search: Option<(char, String)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ViCommand {
+    count: Option<usize>,
+    op: Option<char>,
+    key: char,
+    arg: Option<char>,
+}
+
+enum Parsed {
+    Incomplete,
+    Invalid,
+    Command(ViCommand),
+}
+
+fn char_class(c: char) -> u8 {
+    if c.is_whitespace() {
+        0
+    } else if c.is_alphanumeric() || c == '_' {
+        1
+    } else {
+        2
+    }
+}
+
+fn take_count(keys: &[char], i: &mut usize) -> Option<usize> {
+    let mut num = String::new();
+    while let Some(c) = keys.get(*i) {
+        if !c.is_ascii_digit() || (num.is_empty() && *c == '0') {
+            break;
+        }
+        num.push(*c);
+        *i += 1;
+    }
+    num.parse().ok()
+}
+
+fn parse(keys: &[char]) -> Parsed {
+    let mut i = 0;
+    let mut count = take_count(keys, &mut i);
+
+    let op = match keys.get(i) {
+        None => return Parsed::Incomplete,
+        Some(c) if "dcy".contains(*c) => {
+            i += 1;
+            Some(*c)
+        }
+        _ => None,
+    };
+    if op.is_some() {
+        if let Some(n) = take_count(keys, &mut i) {
+            count = Some(count.unwrap_or(1) * n);
+        }
+    }
+
+    let key = match keys.get(i) {
+        None => return Parsed::Incomplete,
+        Some(c) => *c,
+    };
+    let valid = match op {
+        Some(o) => MOTIONS.contains(key) || key == o,
+        None => MOTIONS.contains(key) || COMMANDS.contains(key),
+    };
+    if !valid {
+        return Parsed::Invalid;
+    }
+
+    let arg = match "fFtTr".contains(key) {
+        true => match keys.get(i + 1) {
+            None => return Parsed::Incomplete,
+            Some(c) => Some(*c),
+        },
+        false => None,
+    };
+
+    Parsed::Command(ViCommand {
+        count,
+        op,
+        key,
+        arg,
+    })
+}
+
+impl Terminal {
+    fn set_cursor_shape(&mut self) {
+        match self.vi.command_mode {
+            true => self.write(termion::cursor::SteadyBlock.as_ref()),
+            false => self.write(termion::cursor::SteadyBar.as_ref()),
+        }
+        self.flush();
+    }
+
+    pub fn vi_start(&mut self) {
+        self.vi.command_mode = false;
+        self.set_cursor_shape();
+    }
+
+    pub fn vi_finish(&mut self) {
+        self.write("\x1b[0 q");
+        self.flush();
+    }
+
+    fn line_end(&self) -> usize {
+        self.chars.len()
+    }
+
+    /* the cursor can't be beyond the last char in command mode */
+    fn clamp_head(&mut self) {
+        let max = std::cmp::max(self.origin(), self.line_end().saturating_sub(1));
+        self.head = self.head.clamp(self.origin(), max);
+    }
+
+    fn enter_command_mode(&mut self) {
+        if let Some(keys) = self.vi.recording.take() {
+            self.vi.last_change = Some((keys, std::mem::take(&mut self.vi.inserted)));
+        }
+        self.vi.command_mode = true;
+        if self.head > self.origin() {
+            self.head -= 1;
+        }
+        self.set_cursor_shape();
+        self.goto(self.head);
+        self.flush();
+    }
+
+    fn enter_insert_mode(&mut self, head: usize) {
+        self.head = head.clamp(self.origin(), self.line_end());
+        self.vi.command_mode = false;
+        self.vi.inserted.clear();
+        self.set_cursor_shape();
+        self.goto(self.head);
+        self.flush();
+    }
+
+    fn next_word(&self, pos: usize, big: bool) -> usize {
+        let class = |c: char| match big {
+            true => char_class(c).min(1),
+            false => char_class(c),
+        };
+        let mut p = pos;
+        if p < self.line_end() {
+            let cls = class(self.chars[p]);
+            while p < self.line_end() && cls != 0 && class(self.chars[p]) == cls {
+                p += 1;
+            }
+        }
+        while p < self.line_end() && class(self.chars[p]) == 0 {
+            p += 1;
+        }
+        p
+    }
+
+    fn prev_word(&self, pos: usize, big: bool) -> usize {
+        let class = |c: char| match big {
+            true => char_class(c).min(1),
+            false => char_class(c),
+        };
+        let mut p = pos;
+        while p > self.origin() && class(self.chars[p - 1]) == 0 {
+            p -= 1;
+        }
+        if p > self.origin() {
+            let cls = class(self.chars[p - 1]);
+            while p > self.origin() && class(self.chars[p - 1]) == cls {
+                p -= 1;
+            }
+        }
+        p
+    }
+
+    fn word_end_pos(&self, pos: usize, big: bool) -> usize {
+        let class = |c: char| match big {
+            true => char_class(c).min(1),
+            false => char_class(c),
+        };
+        let mut p = pos + 1;
+        while p < self.line_end() && class(self.chars[p]) == 0 {
+            p += 1;
+        }
+        if p >= self.line_end() {
+            return self.line_end().saturating_sub(1).max(pos);
+        }
+        let cls = class(self.chars[p]);
+        while p + 1 < self.line_end() && class(self.chars[p + 1]) == cls {
+            p += 1;
+        }
+        p
+    }
+
+    fn find_char(&self, kind: char, target: char, count: usize) -> Option<usize> {
+        let mut p = self.head;
+        for _ in 0..count {
+            let from = p;
+            p = match kind {
+                'f' | 't' => (from + 1..self.line_end()).find(|i| self.chars[*i] == target)?,
+                _ => (self.origin()..from)
+                    .rev()
+                    .find(|i| self.chars[*i] == target)?,
+            };
+        }
+        match kind {
+            't' => Some(p - 1),
+            'T' => Some(p + 1),
+            _ => Some(p),
+        }
+    }
+
+    /* the target of a motion and whether the char at the target is included */
+    fn motion_target(&mut self, cmd: &ViCommand) -> Option<(usize, bool)> {
+        let n = cmd.count.unwrap_or(1);
+        let mut p = self.head;
+        let big = cmd.key.is_ascii_uppercase();
+
+        let inclusive = match cmd.key {
+            'h' => {
+                p = p.saturating_sub(n).max(self.origin());
+                false
+            }
+            'l' | ' ' => {
+                p = (p + n).min(self.line_end());
+                false
+            }
+            '0' => {
+                p = self.origin();
+                false
+            }
+            '^' => {
+                p = self.origin();
+                while p < self.line_end() && self.chars[p].is_whitespace() {
+                    p += 1;
+                }
+                false
+            }
+            '$' => {
+                p = self.line_end().saturating_sub(1).max(self.origin());
+                true
+            }
+            'w' | 'W' => {
+                for _ in 0..n {
+                    p = self.next_word(p, big);
+                }
+                false
+            }
+            'b' | 'B' => {
+                for _ in 0..n {
+                    p = self.prev_word(p, big);
+                }
+                false
+            }
+            'e' | 'E' => {
+                for _ in 0..n {
+                    p = self.word_end_pos(p, big);
+                }
+                true
+            }
+            'f' | 'F' | 't' | 'T' => {
+                let target = cmd.arg?;
+                self.vi.last_find = Some((cmd.key, target));
+                p = self.find_char(cmd.key, target, n)?;
+                "ft".contains(cmd.key)
+            }
+            ';' | ',' => {
+                let (mut kind, target) = self.vi.last_find?;
+                if cmd.key == ',' {
+                    kind = match kind {
+                        'f' => 'F',
+                        'F' => 'f',
+                        't' => 'T',
+                        _ => 't',
+                    };
+                }
+                p = self.find_char(kind, target, n)?;
+                "ft".contains(kind)
+            }
+            _ => return None,
+        };
+        Some((p, inclusive))
+    }
+
+    fn register(core: &ShellCore) -> Option<String> {
+        core.kill_ring.last().cloned()
+    }
+
+    fn apply_operator(&mut self, op: char, from: usize, to: usize, core: &mut ShellCore) {
+        let to = to.min(self.line_end());
+        let text: String = self.chars[from..to].iter().collect();
+        if !text.is_empty() {
+            core.kill_ring.push(text);
+        }
+
+        match op {
+            'y' => self.head = from,
+            _ => {
+                self.chars.drain(from..to);
+                self.head = from;
+            }
+        }
+
+        match op {
+            'c' => self.enter_insert_mode(from),
+            _ => self.clamp_head(),
+        }
+    }
+
+    fn vi_operator(&mut self, cmd: &ViCommand, core: &mut ShellCore) -> bool {
+        let op = cmd.op.unwrap();
+        if cmd.key == op {
+            self.apply_operator(op, self.origin(), self.line_end(), core);
+            return true;
+        }
+
+        let mut cmd = *cmd;
+        if op == 'c'
+            && "wW".contains(cmd.key)
+            && self.head < self.line_end()
+            && !self.chars[self.head].is_whitespace()
+        {
+            cmd.key = if cmd.key == 'w' { 'e' } else { 'E' }; // cw works like ce
+        }
+
+        let (target, inclusive) = match self.motion_target(&cmd) {
+            Some(t) => t,
+            None => return false,
+        };
+        let (from, to) = match target < self.head {
+            true => (target, self.head),
+            false => (self.head, target + inclusive as usize),
+        };
+        self.apply_operator(op, from, to, core);
+        true
+    }
+
+    fn put(&mut self, after: bool, count: usize, core: &mut ShellCore) -> bool {
+        let text = match Self::register(core) {
+            Some(t) => t.repeat(count),
+            None => return false,
+        };
+        if after && self.head < self.line_end() {
+            self.head += 1;
+        }
+        for c in text.chars() {
+            self.chars.insert(self.head, c);
+            self.head += 1;
+        }
+        self.head -= 1;
+        true
+    }
+
+    fn replace_chars(&mut self, c: char, count: usize) -> bool {
+        if self.head + count > self.line_end() {
+            return false;
+        }
+        for i in 0..count {
+            self.chars[self.head + i] = c;
+        }
+        self.head += count - 1;
+        true
+    }
+
+    fn toggle_case(&mut self, count: usize) {
+        for _ in 0..count {
+            if self.head >= self.line_end() {
+                break;
+            }
+            let c = self.chars[self.head];
+            self.chars[self.head] = match c.is_uppercase() {
+                true => c.to_lowercase().next().unwrap_or(c),
+                false => c.to_uppercase().next().unwrap_or(c),
+            };
+            self.head += 1;
+        }
+    }
+
+    fn vi_history_search(&mut self, dir: char, word: &str, core: &mut ShellCore) -> bool {
+        let found = match dir {
+            '/' => core.search_history(word, self.hist_ptr + 1, false),
+            _ => match self.hist_ptr.checked_sub(1) {
+                Some(from) => core.search_history(word, from, true),
+                None => None,
+            },
+        };
+        match found {
+            Some((pos, _)) => {
+                self.set_history_pos(pos, core);
+                self.head = self.origin();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn is_change(cmd: &ViCommand) -> bool {
+        match cmd.op {
+            Some(op) => op != 'y',
+            None => "xXpPiaIADCsSr~".contains(cmd.key),
+        }
+    }
+
+    fn exec_vi_command(&mut self, cmd: &ViCommand, core: &mut ShellCore) -> bool {
+        let n = cmd.count.unwrap_or(1);
+        if Self::is_change(cmd) {
+            self.save_undo();
+        }
+
+        if cmd.op.is_some() {
+            return self.vi_operator(cmd, core);
+        }
+
+        let op_cmd = |op, key| ViCommand {
+            count: cmd.count,
+            op: Some(op),
+            key,
+            arg: None,
+        };
+
+        match cmd.key {
+            k if MOTIONS.contains(k) => match self.motion_target(cmd) {
+                Some((p, _)) => {
+                    self.head = p;
+                    self.clamp_head();
+                    true
+                }
+                None => false,
+            },
+            'x' => self.head < self.line_end() && self.vi_operator(&op_cmd('d', 'l'), core),
+            'X' => self.head > self.origin() && self.vi_operator(&op_cmd('d', 'h'), core),
+            'D' => self.vi_operator(&op_cmd('d', '$'), core),
+            'C' => self.vi_operator(&op_cmd('c', '$'), core),
+            's' => {
+                if self.head >= self.line_end() {
+                    self.enter_insert_mode(self.head);
+                    return true;
+                }
+                self.vi_operator(&op_cmd('c', 'l'), core)
+            }
+            'S' => self.vi_operator(&op_cmd('c', 'c'), core),
+            'i' => {
+                self.enter_insert_mode(self.head);
+                true
+            }
+            'a' => {
+                let h = match self.head < self.line_end() {
+                    true => self.head + 1,
+                    false => self.head,
+                };
+                self.enter_insert_mode(h);
+                true
+            }
+            'I' => {
+                self.enter_insert_mode(self.origin());
+                true
+            }
+            'A' => {
+                self.enter_insert_mode(self.line_end());
+                true
+            }
+            'p' | 'P' => self.put(cmd.key == 'p', n, core),
+            'r' => self.replace_chars(cmd.arg.unwrap(), n),
+            '~' => {
+                self.toggle_case(n);
+                self.clamp_head();
+                true
+            }
+            'u' => {
+                self.undo();
+                self.clamp_head();
+                true
+            }
+            '.' => self.repeat_change(core),
+            'j' | 'k' => {
+                let inc = if cmd.key == 'k' {
+                    n as i32
+                } else {
+                    -(n as i32)
+                };
+                self.call_history(inc, core);
+                self.head = self.origin();
+                true
+            }
+            '/' | '?' => {
+                self.vi.search_input = Some((cmd.key, String::new()));
+                self.show_vi_search();
+                true
+            }
+            'n' | 'N' => {
+                let (mut dir, word) = match self.vi.last_search.clone() {
+                    Some(s) => s,
+                    None => return false,
+                };
+                if cmd.key == 'N' {
+                    dir = if dir == '/' { '?' } else { '/' };
+                }
+                self.vi_history_search(dir, &word, core)
+            }
+            _ => false,
+        }
+    }
+
+    fn repeat_change(&mut self, core: &mut ShellCore) -> bool {
+        let (keys, text) = match self.vi.last_change.clone() {
+            Some(c) => c,
+            None => return false,
+        };
+        let cmd = match parse(&keys) {
+            Parsed::Command(c) => c,
+            _ => return false,
+        };
+
+        if !self.exec_vi_command(&cmd, core) {
+            return false;
+        }
+        if !self.vi.command_mode {
+            for c in text.chars() {
+                self.chars.insert(self.head, c);
+                self.head += 1;
+            }
+            self.vi.recording = None;
+            self.enter_command_mode();
+        }
+        true
+    }
+
+    fn run_vi_keys(&mut self, core: &mut ShellCore) {
+        let cmd = match parse(&self.vi.keys) {
+            Parsed::Incomplete => return,
+            Parsed::Invalid => {
+                self.vi.keys.clear();
+                return self.cloop();
+            }
+            Parsed::Command(c) => c,
+        };
+
+        let keys = std::mem::take(&mut self.vi.keys);
+        if cmd.key != '.' && Self::is_change(&cmd) {
+            self.vi.recording = Some(keys);
+        }
+
+        if !self.exec_vi_command(&cmd, core) {
+            self.vi.recording = None;
+            self.cloop();
+        } else if self.vi.command_mode {
+            if let Some(keys) = self.vi.recording.take() {
+                self.vi.last_change = Some((keys, String::new()));
+            }
+        }
+
+        if self.vi.search_input.is_none() {
+            self.rewrite(true);
+        }
+    }
+
+    fn show_vi_search(&mut self) {
+        let (dir, word) = self.vi.search_input.clone().unwrap();
+        self.goto(0);
+        self.write(termion::clear::AfterCursor.as_ref());
+        self.write(&format!("{}{}", dir, word));
+        self.flush();
+    }
+
+    fn vi_search_key(&mut self, core: &mut ShellCore, c: &Key) {
+        let (dir, mut word) = self.vi.search_input.take().unwrap();
+        match c {
+            Key::Char('\n') => {
+                if word.is_empty() {
+                    word = match &self.vi.last_search {
+                        Some(s) => s.1.clone(),
+                        None => String::new(),
+                    };
+                }
+                self.vi.last_search = Some((dir, word.clone()));
+                if !self.vi_history_search(dir, &word, core) {
+                    self.cloop();
+                }
+                self.rewrite(true);
+                return;
+            }
+            Key::Char(ch) => word.push(*ch),
+            Key::Backspace if !word.is_empty() => {
+                word.pop();
+            }
+            Key::Esc | Key::Ctrl('c') | Key::Backspace => {
+                self.rewrite(true);
+                return;
+            }
+            _ => {}
+        }
+        self.vi.search_input = Some((dir, word));
+        self.show_vi_search();
+    }
+
+    pub fn vi_keymap(&self) -> &'static str {
+        match self.vi.command_mode {
+            true => "vi-command",
+            false => "vi-insert",
+        }
+    }
+
+    /* a character bound with `bind -m vi-command` is not a vi command */
+    pub fn is_vi_rebound(&self, core: &ShellCore, c: &Key) -> bool {
+        if !self.vi.command_mode
+            || self.vi.search_input.is_some()
+            || !self.vi.keys.is_empty()
+            || !matches!(c, Key::Char(_))
+        {
+            return false;
+        }
+        let keys = [self.pending_keys.clone(), key_bytes(c)].concat();
+        !self.pending_keys.is_empty() || core.keymap.is_bound("vi-command", &keys)
+    }
+
+    /* None: handled with the key bindings of the keymap */
+    pub fn vi_action(&mut self, core: &mut ShellCore, c: &Key) -> Option<Result<bool, InputError>> {
+        if self.vi.search_input.is_some() {
+            self.vi_search_key(core, c);
+            return Some(Ok(false));
+        }
+
+        if !self.vi.command_mode {
+            match c {
+                Key::Esc => self.enter_command_mode(),
+                Key::Alt(ch) => {
+                    self.enter_command_mode();
+                    return self.vi_action(core, &Key::Char(*ch));
+                }
+                Key::Char(ch) if *ch != '\n' && *ch != '\t' => {
+                    self.vi.inserted.push(*ch);
+                    return None;
+                }
+                Key::Backspace | Key::Ctrl('h') => {
+                    self.vi.inserted.pop();
+                    return None;
+                }
+                _ => return None,
+            }
+            return Some(Ok(false));
+        }
+
+        match c {
+            Key::Char('\n') | Key::Ctrl(_) | Key::Up | Key::Down => None,
+            Key::Left | Key::Backspace => {
+                self.head = self.head.saturating_sub(1).max(self.origin());
+                self.rewrite(false);
+                Some(Ok(false))
+            }
+            Key::Right => {
+                self.head += 1;
+                self.clamp_head();
+                self.rewrite(false);
+                Some(Ok(false))
+            }
+            Key::Char(ch) => {
+                self.vi.keys.push(*ch);
+                self.run_vi_keys(core);
+                Some(Ok(false))
+            }
+            _ => Some(Ok(false)),
+        }
+    }
+}
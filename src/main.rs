@@ -19,7 +19,7 @@ use std::{env, process};
 use crate::core::builtins::source;
 use crate::core::{builtins, ShellCore};
 use crate::elements::script::Script;
-use crate::feeder::Feeder;
+use crate::feeder::{terminal, Feeder};
 use crate::i18n::FLUENT_BUNDLE;
 use builtins::option;
 use error::input::InputError;
@@ -81,6 +81,9 @@ fn main() {
     if core.script_name == "-" {
         read_rc_file(&mut core);
     }
+    if core.db.flags.contains('i') {
+        core.load_history();
+    }
     main_loop(&mut core, &command);
 }
 
@@ -214,7 +217,7 @@ fn main_loop(core: &mut ShellCore, command: &str) {
             break;
         }
     }
-    core.write_history_to_file();
+    core.save_history();
     exit::normal(core);
 }
 
@@ -228,6 +231,10 @@ fn feed_script(feeder: &mut Feeder, core: &mut ShellCore) -> (bool, bool) {
         core.jobtable_print_status_change();
     }
 
+    if core.db.flags.contains('i') {
+        run_prompt_command(core);
+    }
+
     match feeder.feed_line(core) {
         Ok(()) => (false, false),
         Err(InputError::Interrupt) => {
@@ -239,10 +246,27 @@ fn feed_script(feeder: &mut Feeder, core: &mut ShellCore) -> (bool, bool) {
     }
 }
 
+/* PROMPT_COMMAND can be a string or an array of commands */
+fn run_prompt_command(core: &mut ShellCore) {
+    let commands = match core.db.is_array("PROMPT_COMMAND") {
+        true => core.db.get_vec("PROMPT_COMMAND", false).unwrap_or_default(),
+        false => vec![core.db.get_param("PROMPT_COMMAND").unwrap_or_default()],
+    };
+
+    let exit_status = core.db.exit_status;
+    for command in commands.into_iter().filter(|c| !c.is_empty()) {
+        builtins::eval(core, &["eval".to_string(), command]);
+    }
+    core.db.exit_status = exit_status;
+}
+
 fn parse_and_exec(feeder: &mut Feeder, core: &mut ShellCore, set_hist: bool) {
     core.sigint.store(false, Relaxed);
     match Script::parse(feeder, core, false) {
         Ok(Some(mut s)) => {
+            if core.db.flags.contains('i') && !s.get_text().trim().is_empty() {
+                eprint!("{}", terminal::prompt_string(core, "PS0"));
+            }
             if let Err(e) = s.exec(core) {
                 e.print(core);
             }
@@ -264,15 +288,20 @@ fn parse_and_exec(feeder: &mut Feeder, core: &mut ShellCore, set_hist: bool) {
 }
 
 fn set_history(core: &mut ShellCore, s: &str) {
-    if core.db.flags.contains('i') || core.history.is_empty() {
+    if core.db.flags.contains('i') || !core.history_state.current_added {
         return;
     }
 
+    if !s.starts_with(&core.history[0]) {
+        return; // rewritten by fc
+    }
+
     core.history[0] = s.trim_end().replace("\n", "↵ \0").to_string();
-    if core.history[0].is_empty() || (core.history.len() > 1 && core.history[0] == core.history[1])
-    {
+    if core.history[0].is_empty() {
         core.history.remove(0);
+        return;
     }
+    core.arrange_multiline_history();
 }
 
 ///// Text related functions /////
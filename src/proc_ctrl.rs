@@ -31,7 +31,6 @@ pub fn wait_pipeline(
             core.flip_exit_status();
         }
         close_proc_sub(core);
-        exit::check_e_option(core);
         return vec![];
     }
 
@@ -73,7 +72,6 @@ pub fn wait_pipeline(
     }
 
     close_proc_sub(core);
-    exit::check_e_option(core);
 
     ans
 }
@@ -138,33 +136,111 @@ pub fn set_pgid(core: &ShellCore, pid: Pid, pgid: Pid) {
     }
 }
 
-fn show_time(core: &ShellCore) {
+/* seconds and milliseconds, rounded like bash */
+fn to_sec_msec(sec: i64, usec: i64) -> (i64, i64) {
+    let msec = (usec + 500) / 1000;
+    match msec >= 1000 {
+        true => (sec + 1, msec - 1000),
+        false => (sec, msec),
+    }
+}
+
+fn time_format(sec: i64, msec: i64, prec: usize, long: bool) -> String {
+    let mut ans = match long {
+        true => format!("{}m{}", sec / 60, sec % 60),
+        false => sec.to_string(),
+    };
+    if prec > 0 {
+        let frac = format!("{:03}", msec);
+        ans += &format!(".{}", &frac[..prec]);
+    }
+    if long {
+        ans.push('s');
+    }
+    ans
+}
+
+fn format_times(format: &str, times: &[(i64, i64); 3]) -> Result<String, char> {
+    let mut ans = String::new();
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' || chars.peek().is_none() {
+            ans.push(c);
+            continue;
+        }
+
+        match chars.peek() {
+            Some('%') => {
+                chars.next();
+                ans.push('%');
+                continue;
+            }
+            Some('P') => {
+                chars.next();
+                let real = times[0].0 * 1000 + times[0].1;
+                let cpu = times[1].0 * 1000 + times[1].1 + times[2].0 * 1000 + times[2].1;
+                let percent = match real {
+                    0 => 0,
+                    _ => (cpu * 10000 / real).min(10000),
+                };
+                ans += &time_format(percent / 100, (percent % 100) * 10, 2, false);
+                continue;
+            }
+            _ => {}
+        }
+
+        let mut prec = 3;
+        if let Some(d) = chars.peek().and_then(|c| c.to_digit(10)) {
+            chars.next();
+            prec = (d as usize).min(3);
+        }
+        let long = chars.next_if_eq(&'l').is_some();
+
+        let (sec, msec) = match chars.next() {
+            Some('R' | 'E') => times[0],
+            Some('U') => times[1],
+            Some('S') => times[2],
+            other => return Err(other.unwrap_or('\0')),
+        };
+        ans += &time_format(sec, msec, prec, long);
+    }
+    Ok(ans)
+}
+
+fn show_time(core: &mut ShellCore) {
     let real_end_time = clock_gettime(ClockId::CLOCK_MONOTONIC).unwrap();
 
     let core_usage = resource::getrusage(UsageWho::RUSAGE_SELF).unwrap();
     let children_usage = resource::getrusage(UsageWho::RUSAGE_CHILDREN).unwrap();
 
     let real_diff = real_end_time - core.measured_time.real;
-    eprintln!(
-        "\nreal\t{}m{}.{:06}s",
-        real_diff.tv_sec() / 60,
-        real_diff.tv_sec() % 60,
-        real_diff.tv_nsec() / 1000
-    );
     let user_diff = core_usage.user_time() + children_usage.user_time() - core.measured_time.user;
-    eprintln!(
-        "user\t{}m{}.{:06}s",
-        user_diff.tv_sec() / 60,
-        user_diff.tv_sec() % 60,
-        user_diff.tv_usec()
-    );
     let sys_diff = core_usage.system_time() + children_usage.system_time() - core.measured_time.sys;
-    eprintln!(
-        "sys \t{}m{}.{:06}s",
-        sys_diff.tv_sec() / 60,
-        sys_diff.tv_sec() % 60,
-        sys_diff.tv_usec()
-    );
+    let times = [
+        to_sec_msec(real_diff.tv_sec(), real_diff.tv_nsec() / 1000),
+        to_sec_msec(user_diff.tv_sec(), user_diff.tv_usec()),
+        to_sec_msec(sys_diff.tv_sec(), sys_diff.tv_usec()),
+    ];
+
+    let format = match core.measured_time.posix {
+        true => "real %2R\nuser %2U\nsys %2S".to_string(),
+        false => match core.db.exist("TIMEFORMAT") {
+            true => core.db.get_param("TIMEFORMAT").unwrap_or_default(),
+            false => "\nreal\t%3lR\nuser\t%3lU\nsys\t%3lS".to_string(),
+        },
+    };
+    if format.is_empty() {
+        return;
+    }
+
+    match format_times(&format, &times) {
+        Ok(s) => eprintln!("{}", s),
+        Err(c) => {
+            let msg = format!("TIMEFORMAT: `{}': invalid format character", c);
+            error::print(&msg, core);
+        }
+    }
 }
 
 pub fn exec_command(args: &[String], core: &mut ShellCore, fullpath: &str) -> ! {
@@ -79,22 +79,94 @@ pub fn check_trap(core: &mut ShellCore) {
     }
 
     for s in scripts {
-        let mut feeder = Feeder::new(&s);
-        let mut script = match Script::parse(&mut feeder, core, true) {
-            Ok(None) => {
-                continue;
-            }
-            Ok(s) => s.unwrap(),
-            Err(e) => {
-                e.print(core);
-                continue;
-            }
-        };
+        run_trap_script(&s, core, 1);
+    }
+
+    core.db.exit_status = bkup;
+}
 
-        if let Err(e) = script.exec(core) {
+fn run_trap_script(s: &str, core: &mut ShellCore, lineno: usize) {
+    let mut feeder = Feeder::new(s);
+    feeder.lineno = lineno;
+    let mut script = match Script::parse(&mut feeder, core, true) {
+        Ok(Some(s)) => s,
+        Ok(None) => return,
+        Err(e) => {
             e.print(core);
+            return;
         }
+    };
+
+    core.trap_level += 1;
+    if let Err(e) = script.exec(core) {
+        e.print(core);
     }
+    core.trap_level -= 1;
+}
 
+/* for ERR, DEBUG and RETURN. $? is kept and the exit status of the script is returned. */
+fn run_pseudo_signal_trap(script: &str, core: &mut ShellCore) -> i32 {
+    let bkup = core.db.exit_status;
+    let suspend_e_option = core.suspend_e_option;
+    core.suspend_e_option = true;
+
+    let lineno = core.db.get_param("LINENO").unwrap_or_default();
+    run_trap_script(script, core, lineno.parse::<usize>().unwrap_or(1));
+    let ans = core.db.exit_status;
+
+    core.suspend_e_option = suspend_e_option;
     core.db.exit_status = bkup;
+    ans
+}
+
+pub fn run_err_trap(core: &mut ShellCore) {
+    if core.err_script.is_empty() {
+        return;
+    }
+
+    let script = core.err_script.clone();
+    run_pseudo_signal_trap(&script, core);
+}
+
+/* the trap is not run for the commands in itself, which may also remove it */
+pub fn run_debug_trap(core: &mut ShellCore) -> i32 {
+    if core.debug_script.is_empty() || core.debug_trap_running {
+        return 0;
+    }
+
+    let script = core.debug_script.clone();
+    core.debug_trap_running = true;
+    let ans = run_pseudo_signal_trap(&script, core);
+    core.debug_trap_running = false;
+    ans
+}
+
+/* runs the DEBUG trap before a command and returns true if the command is skipped.
+ * a non-zero status skips it with extdebug, and 2 also returns from the function or the sourced file */
+pub fn skip_by_debug_trap(core: &mut ShellCore, command: &str) -> bool {
+    if core.trap_level == 0 {
+        let _ = core.db.set_param("BASH_COMMAND", command, None);
+    }
+
+    let status = run_debug_trap(core);
+    if status == 0 || !core.shopts.query("extdebug") {
+        return false;
+    }
+
+    if status == 2 && core.source_function_level > 0 {
+        core.return_flag = true;
+        core.db.exit_status = 2;
+    }
+    true
+}
+
+pub fn run_return_trap(core: &mut ShellCore) {
+    if core.return_script.is_empty() || core.return_trap_running {
+        return;
+    }
+
+    let script = core.return_script.clone();
+    core.return_trap_running = true;
+    run_pseudo_signal_trap(&script, core);
+    core.return_trap_running = false;
 }
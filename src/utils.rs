@@ -39,6 +39,8 @@ pub fn reserved(w: &str) -> bool {
             | "case"
             | "esac"
             | "repeat"
+            | "select"
+            | "coproc"
     )
 }
 
@@ -94,6 +94,41 @@ pub fn dissolve_options(args: &[String]) -> Vec<String> {
     ans
 }
 
+/* dissolves the options like getopts. The rest of an option in with_value
+ * is its argument (e.g. -n1 -> -n 1), and the options end at a non-option word. */
+pub fn dissolve_options_with_values(args: &[String], with_value: &str) -> Vec<String> {
+    let mut ans = vec![];
+    let mut pos = 0;
+    while pos < args.len() {
+        let a = &args[pos];
+        pos += 1;
+        if ans.is_empty() {
+            ans.push(a.to_string());
+            continue;
+        }
+        if a == "--" || !a.starts_with('-') || a == "-" {
+            ans.extend(args[pos - 1..].iter().cloned());
+            break;
+        }
+
+        for (i, c) in a.char_indices().skip(1) {
+            ans.push(format!("-{}", c));
+            if with_value.contains(c) {
+                let value = &a[i + c.len_utf8()..];
+                if !value.is_empty() {
+                    ans.push(value.to_string());
+                } else if pos < args.len() {
+                    ans.push(args[pos].clone());
+                    pos += 1;
+                }
+                break;
+            }
+        }
+    }
+
+    ans
+}
+
 pub fn dissolve_options_main() -> Vec<String> {
     let mut ans = vec![];
     let mut stop = false;
@@ -1,8 +1,10 @@
 //SPDX-FileCopyrightText: 2024 @caro@mi.shellgei.org
 //SPDX-License-Identifier: BSD-3-Clause
 
+use nix::libc;
 use nix::time;
 use nix::time::ClockId;
+use std::ffi::CString;
 use std::time::Duration;
 
 pub fn monotonic_time() -> Duration {
@@ -36,12 +38,46 @@ pub fn get_seconds(v: &mut Vec<String>) -> String {
 }
 */
 
-pub fn get_epochseconds() -> String {
+pub fn epoch_seconds() -> i64 {
     let real = time::clock_gettime(ClockId::CLOCK_REALTIME).unwrap();
-    real.tv_sec().to_string()
+    real.tv_sec()
+}
+
+pub fn get_epochseconds() -> String {
+    epoch_seconds().to_string()
 }
 
 pub fn get_epochrealtime() -> String {
     let real = time::clock_gettime(ClockId::CLOCK_REALTIME).unwrap();
     format!("{}.{:06}", real.tv_sec(), real.tv_nsec() / 1000).to_string()
 }
+
+extern "C" {
+    fn tzset();
+}
+
+/* formats the local time with strftime(3) */
+pub fn strftime(format: &str, epoch: i64) -> String {
+    let fmt = match CString::new(format) {
+        Ok(f) => f,
+        Err(_) => return String::new(),
+    };
+    let mut buf = vec![0u8; 1024];
+    let t = epoch as libc::time_t;
+
+    let len = unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        tzset();
+        if libc::localtime_r(&t, &mut tm).is_null() {
+            return String::new();
+        }
+        libc::strftime(
+            buf.as_mut_ptr() as *mut libc::c_char,
+            buf.len(),
+            fmt.as_ptr(),
+            &tm,
+        )
+    };
+    buf.truncate(len);
+    String::from_utf8_lossy(&buf).to_string()
+}
@@ -93,3 +93,65 @@ pub fn glob(dir: &str, pattern: &str, shopts: &Options) -> Vec<String> {
     ans.dedup();
     ans
 }
+
+/* 0: same, 1: two chars are transposed, 2: a char is changed, added or removed, 3: others */
+fn spell_distance(cur: &str, new: &str) -> usize {
+    let cur: Vec<char> = cur.chars().collect();
+    let new: Vec<char> = new.chars().collect();
+    let common = cur
+        .iter()
+        .zip(new.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (cur, new) = (&cur[common..], &new[common..]);
+
+    if cur.is_empty() && new.is_empty() {
+        0
+    } else if cur.len() > 1
+        && new.len() > 1
+        && cur[0] == new[1]
+        && cur[1] == new[0]
+        && cur[2..] == new[2..]
+    {
+        1
+    } else if (!cur.is_empty() && !new.is_empty() && cur[1..] == new[1..])
+        || (!cur.is_empty() && cur[1..] == *new)
+        || (!new.is_empty() && *cur == new[1..])
+    {
+        2
+    } else {
+        3
+    }
+}
+
+/* corrects a simple typo in each component of the path (cdspell, dirspell) */
+pub fn spell_correct(path: &str) -> Option<String> {
+    let mut ans = String::new();
+    for (i, name) in path.split('/').enumerate() {
+        if i > 0 {
+            ans.push('/');
+        }
+        if name.is_empty() || name == "." || name == ".." {
+            ans += name;
+            continue;
+        }
+
+        let mut entries = files(&ans);
+        if entries.iter().any(|e| e == name) {
+            ans += name;
+            continue;
+        }
+        entries.sort();
+        let best = entries
+            .iter()
+            .map(|e| (spell_distance(e, name), e))
+            .filter(|(d, _)| *d < 3)
+            .min_by_key(|(d, _)| *d)?;
+        ans += best.1;
+    }
+
+    match ans != path {
+        true => Some(ans),
+        false => None,
+    }
+}
@@ -1,13 +1,13 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::{error, Feeder, Script, ShellCore};
+use crate::{error, signal, Feeder, Script, ShellCore};
 use std::process;
 
 pub fn normal(core: &mut ShellCore) -> ! {
     run_script(core);
 
-    core.write_history_to_file();
+    core.save_history();
     process::exit(core.db.exit_status % 256)
 }
 
@@ -21,6 +21,7 @@ fn run_script(core: &mut ShellCore) {
         return;
     }
 
+    let exit_status = core.db.exit_status;
     let mut feeder = Feeder::new(&core.exit_script);
     match Script::parse(&mut feeder, core, true) {
         Ok(Some(mut s)) => {
@@ -33,6 +34,7 @@ fn run_script(core: &mut ShellCore) {
         }
         Ok(None) => {}
     };
+    core.db.exit_status = exit_status;
 }
 
 /* error at exec */
@@ -60,7 +62,12 @@ pub fn internal(s: &str) -> ! {
 }
 
 pub fn check_e_option(core: &mut ShellCore) {
-    if core.db.exit_status != 0 && core.db.flags.contains("e") && !core.suspend_e_option {
+    if core.db.exit_status == 0 || core.suspend_e_option {
+        return;
+    }
+
+    signal::run_err_trap(core);
+    if core.db.flags.contains("e") {
         normal(core);
     }
 }
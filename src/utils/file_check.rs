@@ -60,7 +60,7 @@ pub fn metadata_check(name: &str, tp: &str) -> bool {
         "-b" => return meta.file_type().is_block_device(),
         "-c" => return meta.file_type().is_char_device(),
         "-p" => return meta.file_type().is_fifo(),
-        "-s" => return meta.len() == 0,
+        "-s" => return meta.len() > 0,
         "-G" => return unistd::getgid() == meta.st_gid().into(),
         "-N" => {
             let modified_time = match meta.modified() {
@@ -110,3 +110,22 @@ pub fn is_tty(name: &str) -> bool {
     };
     unistd::isatty(fd) == Ok(true)
 }
+
+/* file test operators of [[ ]] and test. None for an unknown operator. */
+pub fn unary(op: &str, name: &str) -> Option<bool> {
+    let ans = match op {
+        "-a" | "-e" => exists(name),
+        "-d" => is_dir(name),
+        "-f" => is_regular_file(name),
+        "-h" | "-L" => is_symlink(name),
+        "-r" => is_readable(name),
+        "-t" => is_tty(name),
+        "-w" => is_writable(name),
+        "-x" => is_executable(name),
+        "-b" | "-c" | "-g" | "-k" | "-p" | "-s" | "-u" | "-G" | "-N" | "-O" | "-S" => {
+            metadata_check(name, op)
+        }
+        _ => return None,
+    };
+    Some(ans)
+}
@@ -0,0 +1,19 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use std::process::{Command, Stdio};
+
+/* the stdout, the stderr and the exit status of `sush -c script` */
+pub fn sush(script: &str) -> (String, String, i32) {
+    let out = Command::new(env!("CARGO_BIN_EXE_sush"))
+        .args(["-c", script])
+        .stdin(Stdio::null())
+        .output()
+        .expect("cannot run sush");
+
+    (
+        String::from_utf8_lossy(&out.stdout).to_string(),
+        String::from_utf8_lossy(&out.stderr).to_string(),
+        out.status.code().unwrap_or(-1),
+    )
+}
@@ -0,0 +1,17 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+mod common;
+use common::sush;
+
+#[test]
+fn numbers_after_histsize_shrinks() {
+    let script = "set -o history
+echo 1 >/dev/null
+echo 2 >/dev/null
+echo 3 >/dev/null
+HISTSIZE=2
+history";
+    let (out, _, _) = sush(script);
+    assert_eq!(out, "    3  HISTSIZE=2\n    4  history\n");
+}
@@ -0,0 +1,27 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+mod common;
+use common::sush;
+
+#[test]
+fn star_precision_out_of_range() {
+    let (out, err, status) = sush("printf '%.*s|' 9999999999 abc");
+    assert_eq!(out, "abc|");
+    assert!(err.contains("9999999999: Numerical result out of range"));
+    assert_eq!(status, 0);
+}
+
+#[test]
+fn literal_precision_out_of_range() {
+    let (out, err, _) = sush("printf '%.99999999999s|' abc");
+    assert_eq!(out, "abc|");
+    assert!(err.contains("99999999999: Numerical result out of range"));
+}
+
+#[test]
+fn grouping_flag() {
+    let (out, err, _) = sush("printf \"%'d %'5d|\" 1234567 12");
+    assert_eq!(out, "1234567    12|");
+    assert_eq!(err, "");
+}
@@ -0,0 +1,17 @@
+//SPDX-FileCopyrightText: 2025 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+mod common;
+use common::sush;
+
+#[test]
+fn debug_trap_removing_itself() {
+    let (out, _, _) = sush("trap 'echo D; trap - DEBUG' DEBUG; echo a; echo b");
+    assert_eq!(out, "D\na\nb\n");
+}
+
+#[test]
+fn return_trap_removing_itself() {
+    let (out, _, _) = sush("set -T; trap 'echo R; trap - RETURN' RETURN; f(){ echo f; }; f; f");
+    assert_eq!(out, "f\nR\nf\n");
+}